@@ -0,0 +1,182 @@
+//! Benchmarks parse, part 1 and part 2 of every day on the real and the example inputs. The
+//! results are compared with the previous ones stored in `output/bench/<baseline>.json`:
+//!
+//!     cargo bench [-- FILTER] [--save-baseline NAME | --baseline NAME] [--threshold PERCENT]
+use adventofcode::bench::{
+    format_duration, load_baseline, save_baseline, timed, Change, Measurement, Plan, Stats,
+};
+use adventofcode::verify::{read_answers, ANSWERS_FILE};
+use adventofcode::{input_variants, read_file, resolve_input, solutions, Answer, Result, Solution};
+use ansi_term::Colour::{Green, Red};
+use ansi_term::Style;
+use clap::Parser;
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+const BASELINE_DIR: &str = "output/bench";
+
+#[derive(Parser)]
+struct Args {
+    /// only run benchmarks whose name contains this, e.g. `day15` or `real/part2`
+    filter: Option<String>,
+    /// compare with this baseline and replace it with the new results
+    #[arg(long, default_value = "base")]
+    save_baseline: String,
+    /// only compare with this baseline, keeping it as it is
+    #[arg(long, conflicts_with = "save_baseline")]
+    baseline: Option<String>,
+    /// medians which got slower by more than this many percent are regressions
+    #[arg(long, default_value_t = 10.0)]
+    threshold: f64,
+    /// how long each benchmark is measured, in seconds
+    #[arg(long, default_value_t = 1.0)]
+    measurement_time: f64,
+    /// also run the parts marked as slow in the answers file
+    #[arg(long)]
+    slow: bool,
+    /// directory containing the inputs
+    #[arg(long, default_value = "input")]
+    input_dir: String,
+    /// passed by `cargo bench`
+    #[arg(long, hide = true)]
+    bench: bool,
+}
+
+/// The real input `dayNN.txt` as `real`, then the examples `dayNN-<variant>.txt` by variant
+fn inputs(dir: &str, day: u32) -> Vec<(String, PathBuf)> {
+    let path = |variant: Option<&str>| {
+        PathBuf::from(resolve_input(day, None, variant, Some(dir)).to_string())
+    };
+    let real = ("real".to_string(), path(None));
+    let examples = input_variants(day, Some(dir)).into_iter().map(|variant| {
+        let path = path(Some(&variant));
+        (variant, path)
+    });
+    iter::once(real)
+        .chain(examples)
+        .filter(|(_, path)| path.is_file())
+        .collect()
+}
+
+/// Runs `f` once to see whether it works and how long it takes, then measures it
+fn measure(args: &Args, f: impl Fn() -> Result<Answer>) -> Option<Stats> {
+    let (answer, once) = timed(&f);
+    match answer {
+        Ok(Answer::Unsolved) | Err(_) => None,
+        Ok(_) => {
+            let plan = Plan::new(once, Duration::from_secs_f64(args.measurement_time));
+            Stats::new(&plan.measure(f))
+        }
+    }
+}
+
+fn report(name: &str, stats: &Stats, change: Change) {
+    let change = match change {
+        Change::New => String::new(),
+        Change::Unchanged(relative) => format!("change: {:+.2}%", relative * 100.0),
+        Change::Regressed(relative) => Red
+            .bold()
+            .paint(format!("change: {:+.2}% regressed", relative * 100.0))
+            .to_string(),
+        Change::Improved(relative) => Green
+            .paint(format!("change: {:+.2}% improved", relative * 100.0))
+            .to_string(),
+    };
+    println!(
+        "{:<24} time: [{:>10} {} {:>10}]  {}",
+        name,
+        format_duration(stats.min),
+        Style::new()
+            .bold()
+            .paint(format!("{:>10}", format_duration(stats.median))),
+        format_duration(stats.max),
+        change
+    );
+}
+
+fn run(args: &Args) -> Result<bool> {
+    let name = args.baseline.as_ref().unwrap_or(&args.save_baseline);
+    let path = Path::new(BASELINE_DIR).join(format!("{}.json", name));
+    let previous = load_baseline(&path)?;
+    if previous.is_none() {
+        println!("no baseline `{}` yet, nothing to compare with", name);
+    }
+    let slow: Vec<(u32, u32, String)> = read_answers(ANSWERS_FILE)?
+        .into_iter()
+        .filter(|answer| answer.slow)
+        .map(|answer| (answer.day, answer.part, answer.input))
+        .collect();
+    let is_slow = |solution: &dyn Solution, part: u32, input: &Path| {
+        let input = input.to_string_lossy();
+        slow.iter().any(|(day, slow_part, slow_input)| {
+            (*day, *slow_part) == (solution.day(), part) && input.ends_with(slow_input.as_str())
+        })
+    };
+
+    let mut current = previous.clone().unwrap_or_default();
+    let mut regressions = 0;
+    for solution in solutions() {
+        for (variant, input_path) in inputs(&args.input_dir, solution.day()) {
+            let input = read_file(&input_path.to_string_lossy())?;
+            for (step, part) in [("parse", 0), ("part1", 1), ("part2", 2)] {
+                let name = format!("day{:02}/{}/{}", solution.day(), variant, step);
+                let skipped = args
+                    .filter
+                    .as_ref()
+                    .is_some_and(|filter| !name.contains(filter))
+                    || (!args.slow && part > 0 && is_slow(*solution, part, &input_path));
+                if skipped {
+                    continue;
+                }
+                let stats = match part {
+                    0 => measure(args, || solution.parse(&input).map(|_| Answer::Number(0))),
+                    _ => measure(args, || solution.solve(part, &input)),
+                };
+                // unsolved or broken, nothing to measure
+                let Some(stats) = stats else {
+                    continue;
+                };
+                let measurement = Measurement::from(stats);
+                let change = Change::new(
+                    previous.as_ref().and_then(|previous| previous.get(&name)),
+                    &measurement,
+                    args.threshold / 100.0,
+                );
+                if let Change::Regressed(_) = change {
+                    regressions += 1;
+                }
+                report(&name, &stats, change);
+                current.insert(name, measurement);
+            }
+        }
+    }
+
+    if args.baseline.is_none() {
+        save_baseline(&path, &current)?;
+        println!("saved baseline `{}` to {}", name, path.display());
+    }
+    if regressions > 0 {
+        println!(
+            "{}",
+            Red.bold().paint(format!(
+                "{} benchmarks regressed by more than {}%",
+                regressions, args.threshold
+            ))
+        );
+    }
+    Ok(regressions == 0)
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            println!("{}", Red.bold().paint(error.to_string()));
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,495 @@
+//! Plays simulations in the terminal. A day draws its state into a [`Frame`] after every step and
+//! hands it to an [`Animation`], which cuts out the part around the frame's focus, colours it with
+//! a [`Palette`] and keeps the frame rate. It can record the frames for an image, too, see
+//! [`image`](crate::image).
+
+use crate::image::{ImageOptions, Recording};
+use crate::{Error, Grid, Point, Result};
+use ansi_term::Style;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Moves the cursor to the top-left corner without clearing, so frames don't flicker
+pub(crate) const HOME: &str = "\x1b[H";
+pub(crate) const CLEAR_SCREEN: &str = "\x1b[2J";
+const CLEAR_LINE: &str = "\x1b[K";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+/// One picture of a simulation. The grid keeps the coordinates of the simulation, so the viewport
+/// can follow the focus across frames of different sizes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub grid: Grid<char>,
+    /// The point which should stay in sight, e.g. the head of a rope
+    pub focus: Option<Point<i64>>,
+    /// A line shown below the picture
+    pub caption: String,
+}
+
+impl Frame {
+    pub fn new(grid: Grid<char>) -> Frame {
+        Frame {
+            grid,
+            focus: None,
+            caption: String::new(),
+        }
+    }
+
+    /// Draws single points onto `background`, the grid just covers all of them. Later points are
+    /// drawn over earlier ones.
+    pub fn from_points(
+        points: impl IntoIterator<Item = (Point<i64>, char)>,
+        background: char,
+    ) -> Frame {
+        let points: Vec<(Point<i64>, char)> = points.into_iter().collect();
+        let (mut min, mut max) = (Point::ORIGIN, Point::ORIGIN);
+        if let Some((first, _)) = points.first() {
+            (min, max) = (*first, *first);
+        }
+        for (point, _) in &points {
+            min = Point::new(min.x.min(point.x), min.y.min(point.y));
+            max = Point::new(max.x.max(point.x), max.y.max(point.y));
+        }
+        let size = max - min;
+        let mut grid =
+            Grid::new(size.x as usize + 1, size.y as usize + 1, background).with_origin(min);
+        for (point, cell) in points {
+            grid[point] = cell;
+        }
+        Frame::new(grid)
+    }
+
+    pub fn with_focus(mut self, focus: Point<i64>) -> Frame {
+        self.focus = Some(focus);
+        self
+    }
+
+    pub fn with_caption(mut self, caption: &str) -> Frame {
+        self.caption = caption.to_string();
+        self
+    }
+}
+
+/// The picture a puzzle ends with, e.g. the sand at rest, along with its colours. The focus of
+/// the frame decides where the view starts.
+#[derive(Debug, Clone)]
+pub struct View {
+    pub frame: Frame,
+    pub palette: Palette,
+}
+
+impl View {
+    pub fn new(frame: Frame, palette: Palette) -> View {
+        View { frame, palette }
+    }
+}
+
+/// The colours of the cells, by character. Characters without a style are printed as they are.
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    styles: Vec<(char, Style)>,
+}
+
+impl Palette {
+    pub fn new() -> Palette {
+        Palette { styles: vec![] }
+    }
+
+    pub fn with(mut self, cell: char, style: Style) -> Palette {
+        self.styles.retain(|(known, _)| *known != cell);
+        self.styles.push((cell, style));
+        self
+    }
+
+    pub fn style(&self, cell: char) -> Style {
+        self.styles
+            .iter()
+            .find(|(known, _)| *known == cell)
+            .map_or_else(Style::new, |(_, style)| *style)
+    }
+}
+
+/// The part of a frame which fits onto the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    width: usize,
+    height: usize,
+    top_left: Option<Point<i64>>,
+}
+
+impl Viewport {
+    pub fn new(width: usize, height: usize) -> Viewport {
+        Viewport {
+            width: width.max(1),
+            height: height.max(1),
+            top_left: None,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The coordinates of the top-left cell in sight, `None` before the first frame
+    pub fn top_left(&self) -> Option<Point<i64>> {
+        self.top_left
+    }
+
+    /// Moves as little as possible to keep `focus` a quarter of the size away from the edges, so
+    /// the picture doesn't jump with every step
+    pub fn follow(&mut self, focus: Point<i64>) {
+        let follow = |start: i64, size: usize, focus: i64| {
+            let margin = (size / 4) as i64;
+            let size = size as i64;
+            match focus {
+                _ if focus < start + margin => focus - margin,
+                _ if focus > start + size - 1 - margin => focus - size + 1 + margin,
+                _ => start,
+            }
+        };
+        let top_left = self
+            .top_left
+            .unwrap_or_else(|| focus - Point::new(self.width as i64 / 2, self.height as i64 / 2));
+        self.top_left = Some(Point::new(
+            follow(top_left.x, self.width, focus.x),
+            follow(top_left.y, self.height, focus.y),
+        ));
+    }
+
+    /// Moves the part in sight by `offset`, the next frame stops it at its edges
+    pub fn scroll(&mut self, offset: Point<i64>) {
+        if let Some(top_left) = self.top_left {
+            self.top_left = Some(top_left + offset);
+        }
+    }
+
+    /// Decides which part of `frame` is shown: the part around its focus, but never beyond the
+    /// edges of the frame unless it's smaller than the viewport
+    fn place(&mut self, frame: &Frame) -> Point<i64> {
+        if let Some(focus) = frame.focus {
+            self.follow(focus);
+        }
+        let origin = frame.grid.origin();
+        let top_left = self.top_left.unwrap_or(origin);
+        let clamp = |start: i64, origin: i64, frame_size: usize, size: usize| {
+            let last_start = origin + frame_size.saturating_sub(size) as i64;
+            start.clamp(origin, last_start)
+        };
+        let top_left = Point::new(
+            clamp(top_left.x, origin.x, frame.grid.width(), self.width),
+            clamp(top_left.y, origin.y, frame.grid.height(), self.height),
+        );
+        self.top_left = Some(top_left);
+        top_left
+    }
+}
+
+/// How an animation is played
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Frames per second, 0 plays them as fast as possible
+    pub fps: f64,
+    pub width: usize,
+    pub height: usize,
+    /// Whether the palette is used, otherwise the cells are printed plainly
+    pub colour: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            fps: 20.0,
+            width: 80,
+            height: 24,
+            colour: true,
+        }
+    }
+}
+
+/// Plays frames one after another on a terminal
+pub struct Animation {
+    /// `None` if the frames aren't shown, e.g. when they're only recorded
+    out: Option<Box<dyn Write>>,
+    settings: Settings,
+    viewport: Viewport,
+    palette: Palette,
+    frames: usize,
+    next_frame: Option<Instant>,
+    recording: Option<Recording>,
+}
+
+impl Animation {
+    pub fn new(out: Box<dyn Write>, settings: Settings) -> Animation {
+        Animation {
+            out: Some(out),
+            viewport: Viewport::new(settings.width, settings.height),
+            settings,
+            palette: Palette::new(),
+            frames: 0,
+            next_frame: None,
+            recording: None,
+        }
+    }
+
+    /// An animation on standard output
+    pub fn stdout(settings: Settings) -> Animation {
+        Animation::new(Box::new(io::stdout()), settings)
+    }
+
+    /// An animation nobody sees, played as fast as possible, e.g. to only record or count the
+    /// frames of a day
+    pub fn sink() -> Animation {
+        Animation {
+            out: None,
+            ..Animation::new(
+                Box::new(io::sink()),
+                Settings {
+                    fps: 0.0,
+                    ..Settings::default()
+                },
+            )
+        }
+    }
+
+    /// Keeps every `every`th frame from now on, to be saved as image in the end
+    pub fn record(&mut self, every: usize) {
+        self.recording = Some(Recording::new(every));
+    }
+
+    pub fn recording(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+
+    /// Writes the recorded frames in the colours of the palette, see [`Recording::save`]
+    pub fn save(&self, path: &Path, options: ImageOptions) -> Result<()> {
+        match &self.recording {
+            Some(recording) => recording.save(path, &self.palette, options),
+            None => Err(Error::invalid("Nothing was recorded")),
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    /// Moves the part in sight, see [`Viewport::scroll`]
+    pub fn scroll(&mut self, offset: Point<i64>) {
+        self.viewport.scroll(offset);
+    }
+
+    /// The number of frames shown so far
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The text of the part of `frame` in sight, one line per row and the caption below
+    pub fn render(&mut self, frame: &Frame) -> String {
+        let top_left = self.viewport.place(frame);
+        let mut text = String::new();
+        for y in 0..self.viewport.height as i64 {
+            let cells = (0..self.viewport.width as i64)
+                .map_while(|x| frame.grid.get(top_left + Point::new(x, y)))
+                .map(|cell| match self.settings.colour {
+                    true => self
+                        .palette
+                        .style(*cell)
+                        .paint(cell.to_string())
+                        .to_string(),
+                    false => cell.to_string(),
+                });
+            let row: String = cells.collect();
+            if row.is_empty() {
+                break;
+            }
+            text.push_str(&row);
+            text.push('\n');
+        }
+        text.push_str(&frame.caption);
+        text
+    }
+
+    /// Draws `frame` over the previous one, waiting long enough to keep the frame rate
+    pub fn show(&mut self, frame: &Frame) -> Result<()> {
+        if let Some(recording) = &mut self.recording {
+            recording.offer(&frame.grid);
+        }
+        if self.out.is_some() {
+            self.draw(frame)?;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &Frame) -> Result<()> {
+        let text = self.render(frame);
+        let lines: Vec<String> = text
+            .lines()
+            .map(|line| format!("{}{}", line, CLEAR_LINE))
+            .collect();
+        let start = match self.frames {
+            0 => format!("{}{}", HIDE_CURSOR, CLEAR_SCREEN),
+            _ => String::new(),
+        };
+        self.wait();
+        if let Some(out) = &mut self.out {
+            write!(out, "{}{}{}", start, HOME, lines.join("\n")).map_err(output_error)?;
+            out.flush().map_err(output_error)?;
+        }
+        Ok(())
+    }
+
+    /// Leaves the last frame on the screen and moves the cursor below it
+    pub fn finish(&mut self) -> Result<()> {
+        match &mut self.out {
+            Some(out) if self.frames > 0 => {
+                writeln!(out, "{}", SHOW_CURSOR).map_err(output_error)?;
+                out.flush().map_err(output_error)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn wait(&mut self) {
+        if self.settings.fps <= 0.0 {
+            return;
+        }
+        let now = Instant::now();
+        let next_frame = self.next_frame.unwrap_or(now);
+        if next_frame > now {
+            thread::sleep(next_frame - now);
+        }
+        self.next_frame =
+            Some(next_frame.max(now) + Duration::from_secs_f64(1.0 / self.settings.fps));
+    }
+}
+
+fn output_error(source: io::Error) -> Error {
+    Error::Io {
+        file: "<stdout>".to_string(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ansi_term::Colour::Red;
+
+    fn plain(width: usize, height: usize) -> Animation {
+        Animation::new(
+            Box::new(io::sink()),
+            Settings {
+                fps: 0.0,
+                width,
+                height,
+                colour: false,
+            },
+        )
+    }
+
+    #[test]
+    fn points_are_framed() {
+        let frame = Frame::from_points(
+            [
+                (Point::new(-1, 2), '#'),
+                (Point::new(1, 0), 'H'),
+                (Point::new(-1, 2), 'T'),
+            ],
+            '.',
+        );
+        assert_eq!(Point::new(-1, 0), frame.grid.origin());
+        assert_eq!("..H\n...\nT..", frame.grid.to_string());
+    }
+
+    #[test]
+    fn the_viewport_follows_the_focus() {
+        let mut viewport = Viewport::new(8, 4);
+        viewport.follow(Point::new(0, 0));
+        assert_eq!(Some(Point::new(-4, -2)), viewport.top_left());
+        // within the margins nothing moves
+        viewport.follow(Point::new(1, 0));
+        assert_eq!(Some(Point::new(-4, -2)), viewport.top_left());
+        viewport.follow(Point::new(5, 3));
+        assert_eq!(Some(Point::new(0, 1)), viewport.top_left());
+        viewport.follow(Point::new(-3, 3));
+        assert_eq!(Some(Point::new(-5, 1)), viewport.top_left());
+    }
+
+    #[test]
+    fn scrolling_stops_at_the_edges() {
+        let grid = Grid::parse("abcd\nefgh\nijkl", "", Some).unwrap();
+        let frame = Frame::new(grid);
+        let mut animation = plain(2, 2);
+        // nothing to scroll before the first frame
+        animation.scroll(Point::new(1, 0));
+        assert_eq!("ab\nef\n", animation.render(&frame));
+        animation.scroll(Point::new(1, 1));
+        assert_eq!("fg\njk\n", animation.render(&frame));
+        animation.scroll(Point::new(5, -5));
+        assert_eq!("cd\ngh\n", animation.render(&frame));
+        assert_eq!(Some(Point::new(2, 0)), animation.viewport().top_left());
+    }
+
+    #[test]
+    fn frames_are_cut_to_the_viewport() {
+        let grid = Grid::parse("abcd\nefgh\nijkl", "", Some).unwrap();
+        let mut animation = plain(2, 2);
+        let frame = Frame::new(grid.clone()).with_caption("step 1");
+        assert_eq!("ab\nef\nstep 1", animation.render(&frame));
+
+        let frame = Frame::new(grid.clone()).with_focus(Point::new(3, 2));
+        assert_eq!("gh\nkl\n", animation.render(&frame));
+
+        // small frames are shown from their top-left corner
+        let mut animation = plain(10, 10);
+        assert_eq!("abcd\nefgh\nijkl\n", animation.render(&Frame::new(grid)));
+    }
+
+    #[test]
+    fn frames_are_coloured_and_counted() {
+        let mut animation = Animation::sink();
+        animation.set_palette(Palette::new().with('#', Red.normal()).with('#', Red.bold()));
+        let frame = Frame::new(Grid::parse("#.", "", Some).unwrap());
+        assert_eq!(
+            format!("{}.\n", Red.bold().paint("#")),
+            animation.render(&frame)
+        );
+
+        animation.show(&frame).unwrap();
+        animation.show(&frame).unwrap();
+        animation.finish().unwrap();
+        assert_eq!(2, animation.frames());
+    }
+
+    #[test]
+    fn shown_frames_are_recorded() {
+        let mut animation = Animation::sink();
+        assert!(animation
+            .save(Path::new("never.gif"), ImageOptions::default())
+            .is_err());
+        animation.record(2);
+        for text in ["a", "b", "c", "d"] {
+            animation
+                .show(&Frame::new(Grid::parse(text, "", Some).unwrap()))
+                .unwrap();
+        }
+        let recorded: Vec<String> = animation
+            .recording()
+            .unwrap()
+            .frames()
+            .map(|grid| grid.to_string())
+            .collect();
+        assert_eq!(vec!["a", "c", "d"], recorded);
+    }
+}
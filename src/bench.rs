@@ -0,0 +1,350 @@
+use crate::{read_file, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+
+/// Stack size of the threads running a part, some days still search recursively
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Runs `f` once and returns its result together with the elapsed wall-clock time
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+
+    (result, start.elapsed())
+}
+
+/// Why [`timed_with_limit`] came back without a result
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Interrupted {
+    TimedOut,
+    Panicked,
+}
+
+/// Runs `f` on its own thread like [`timed`], but gives up waiting after `limit`. A thread can't
+/// be cancelled, so it keeps running in the background until it's done or the process ends.
+pub fn timed_with_limit<T: Send + 'static>(
+    limit: Option<Duration>,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<(T, Duration), Interrupted> {
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            // the receiver is gone once the limit has passed, nobody needs the result anymore
+            let _ = sender.send(timed(f));
+        })
+        .expect("failed to spawn a thread");
+
+    // the sender is dropped without sending anything if `f` panics
+    match limit {
+        Some(limit) => receiver.recv_timeout(limit).map_err(|error| match error {
+            RecvTimeoutError::Timeout => Interrupted::TimedOut,
+            RecvTimeoutError::Disconnected => Interrupted::Panicked,
+        }),
+        None => receiver.recv().map_err(|_| Interrupted::Panicked),
+    }
+}
+
+/// Runs `f` for all `items` at once, each on a thread of its own, and returns the results in
+/// order. Unlike the workers of a rayon pool, a thread which only waits for [`timed_with_limit`]
+/// doesn't keep the parallel work of the part it waits for from running.
+pub fn run_each<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = items.iter().map(|item| scope.spawn(|| f(item))).collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("a thread panicked"))
+            .collect()
+    })
+}
+
+/// Spread of repeated measurements of the same code
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn new(samples: &[Duration]) -> Option<Stats> {
+        let mut samples = samples.to_vec();
+        samples.sort();
+
+        let count = samples.len();
+        let median = match count {
+            0 => return None,
+            _ if count.is_multiple_of(2) => (samples[count / 2 - 1] + samples[count / 2]) / 2,
+            _ => samples[count / 2],
+        };
+
+        Some(Stats {
+            min: samples[0],
+            median,
+            max: samples[count - 1],
+        })
+    }
+}
+
+/// Formats a duration with a unit fitting its size, e.g. `312.05 µs` or `1.20 s`
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos() as f64;
+
+    if nanos < 1_000.0 {
+        format!("{:.0} ns", nanos)
+    } else if nanos < 1_000_000.0 {
+        format!("{:.2} µs", nanos / 1_000.0)
+    } else if nanos < 1_000_000_000.0 {
+        format!("{:.2} ms", nanos / 1_000_000.0)
+    } else {
+        format!("{:.2} s", nanos / 1_000_000_000.0)
+    }
+}
+
+/// How often a benchmark runs: `samples` measurements of `iterations` runs each
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Plan {
+    pub samples: usize,
+    pub iterations: u32,
+}
+
+impl Plan {
+    const MIN_SAMPLES: usize = 5;
+    const MAX_SAMPLES: usize = 100;
+    /// Quick code runs in batches of at least this long, so the resolution of the clock doesn't
+    /// matter
+    const BATCH: Duration = Duration::from_millis(1);
+
+    /// Fits the runs into `budget`, given that a single run took `once`. Slow code still runs
+    /// `MIN_SAMPLES` times.
+    pub fn new(once: Duration, budget: Duration) -> Plan {
+        let once = once.max(Duration::from_nanos(1));
+        let iterations = (Self::BATCH.as_nanos() / once.as_nanos()).clamp(1, u32::MAX as u128);
+        let batch = once.as_nanos() * iterations;
+        let samples = (budget.as_nanos() / batch) as usize;
+        Plan {
+            samples: samples.clamp(Self::MIN_SAMPLES, Self::MAX_SAMPLES),
+            iterations: iterations as u32,
+        }
+    }
+
+    /// The time of one run, averaged over each batch
+    pub fn measure<T>(&self, f: impl Fn() -> T) -> Vec<Duration> {
+        (0..self.samples)
+            .map(|_| {
+                let (_, duration) = timed(|| {
+                    for _ in 0..self.iterations {
+                        black_box(f());
+                    }
+                });
+                duration / self.iterations
+            })
+            .collect()
+    }
+}
+
+/// The result of one benchmark as it's stored in a baseline
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Measurement {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub max_ns: u64,
+}
+
+impl From<Stats> for Measurement {
+    fn from(stats: Stats) -> Measurement {
+        Measurement {
+            min_ns: stats.min.as_nanos() as u64,
+            median_ns: stats.median.as_nanos() as u64,
+            max_ns: stats.max.as_nanos() as u64,
+        }
+    }
+}
+
+/// How the median of a benchmark compares with its baseline
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Change {
+    /// There's no baseline for the benchmark yet
+    New,
+    /// Within the threshold, the relative change is given
+    Unchanged(f64),
+    Regressed(f64),
+    Improved(f64),
+}
+
+/// Differences below this are noise, however large they are relative to the baseline
+const NOISE: Duration = Duration::from_micros(1);
+
+impl Change {
+    /// Compares medians, `threshold` is the largest relative change which still counts as noise,
+    /// e.g. `0.1` for ten percent
+    pub fn new(previous: Option<&Measurement>, current: &Measurement, threshold: f64) -> Change {
+        let previous = match previous {
+            Some(previous) => previous.median_ns.max(1),
+            None => return Change::New,
+        };
+        let difference = current.median_ns.abs_diff(previous);
+        let relative = current.median_ns as f64 / previous as f64 - 1.0;
+        if relative.abs() <= threshold || difference < NOISE.as_nanos() as u64 {
+            Change::Unchanged(relative)
+        } else if relative > 0.0 {
+            Change::Regressed(relative)
+        } else {
+            Change::Improved(relative)
+        }
+    }
+}
+
+/// Stored benchmark results by name, like `day15/real/part2`
+pub type Baseline = BTreeMap<String, Measurement>;
+
+/// Reads a baseline, which doesn't exist before the first run
+pub fn load_baseline(path: &Path) -> crate::Result<Option<Baseline>> {
+    let file = path.display().to_string();
+    if !path.exists() {
+        return Ok(None);
+    }
+    serde_json::from_str(&read_file(&file)?)
+        .map(Some)
+        .map_err(|error| Error::invalid(&error.to_string()).in_file(&file))
+}
+
+pub fn save_baseline(path: &Path, baseline: &Baseline) -> crate::Result<()> {
+    let io_error = |source: io::Error| Error::Io {
+        file: path.display().to_string(),
+        source,
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    let json = serde_json::to_string_pretty(baseline).expect("a baseline is plain data");
+    fs::write(path, json + "\n").map_err(io_error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn slow_code_is_given_up() {
+        let (result, _) = timed_with_limit(Some(Duration::from_secs(10)), || 42).unwrap();
+        assert_eq!(42, result);
+        assert_eq!(
+            Ok(7),
+            timed_with_limit(None, || 7).map(|(result, _)| result)
+        );
+
+        let slow = timed_with_limit(Some(Duration::from_millis(10)), || {
+            thread::sleep(Duration::from_secs(1));
+        });
+        assert_eq!(Err(Interrupted::TimedOut), slow);
+
+        let broken = timed_with_limit(Some(Duration::from_secs(10)), || -> u32 {
+            panic!("broken");
+        });
+        assert_eq!(Err(Interrupted::Panicked), broken);
+    }
+
+    #[test]
+    fn stats_are_computed() {
+        let samples = [5, 1, 4, 2, 3].map(Duration::from_millis);
+        assert_eq!(
+            Some(Stats {
+                min: Duration::from_millis(1),
+                median: Duration::from_millis(3),
+                max: Duration::from_millis(5),
+            }),
+            Stats::new(&samples)
+        );
+
+        let samples = [4, 1, 2, 8].map(Duration::from_millis);
+        assert_eq!(
+            Duration::from_millis(3),
+            Stats::new(&samples).unwrap().median
+        );
+        assert_eq!(None, Stats::new(&[]));
+    }
+
+    #[test]
+    fn durations_are_formatted() {
+        assert_eq!("999 ns", format_duration(Duration::from_nanos(999)));
+        assert_eq!("312.05 µs", format_duration(Duration::from_nanos(312_050)));
+        assert_eq!("12.00 ms", format_duration(Duration::from_millis(12)));
+        assert_eq!("1.20 s", format_duration(Duration::from_millis(1_200)));
+    }
+
+    #[test]
+    fn plans_fit_the_budget() {
+        let second = Duration::from_secs(1);
+        assert_eq!(
+            Plan {
+                samples: 100,
+                iterations: 100
+            },
+            Plan::new(Duration::from_micros(10), second)
+        );
+        assert_eq!(
+            Plan {
+                samples: 20,
+                iterations: 1
+            },
+            Plan::new(Duration::from_millis(50), second)
+        );
+        assert_eq!(
+            Plan {
+                samples: 5,
+                iterations: 1
+            },
+            Plan::new(Duration::from_secs(3), second)
+        );
+        let plan = Plan::new(Duration::ZERO, Duration::from_millis(1));
+        assert_eq!(5, plan.measure(|| 1).len());
+    }
+
+    #[test]
+    fn changes_are_flagged() {
+        let measurement = |median_ns| Measurement {
+            min_ns: median_ns,
+            median_ns,
+            max_ns: median_ns,
+        };
+        let previous = measurement(100_000);
+        assert_eq!(Change::New, Change::new(None, &previous, 0.1));
+        assert_eq!(
+            Change::Regressed(0.5),
+            Change::new(Some(&previous), &measurement(150_000), 0.1)
+        );
+        assert_eq!(
+            Change::Improved(-0.5),
+            Change::new(Some(&previous), &measurement(50_000), 0.1)
+        );
+        assert_eq!(
+            Change::Unchanged(0.25),
+            Change::new(Some(&previous), &measurement(125_000), 0.3)
+        );
+        // twice as slow, but only by half a microsecond
+        assert_eq!(
+            Change::Unchanged(1.0),
+            Change::new(Some(&measurement(500)), &measurement(1000), 0.1)
+        );
+    }
+
+    #[test]
+    fn baselines_are_stored() {
+        let path = std::env::temp_dir().join(format!("baseline-{}/base.json", std::process::id()));
+        assert_eq!(None, load_baseline(&path).unwrap());
+
+        let stats = Stats::new(&[1, 2, 3].map(Duration::from_micros)).unwrap();
+        let baseline = Baseline::from([("day01/real/part1".to_string(), stats.into())]);
+        save_baseline(&path, &baseline).unwrap();
+        assert_eq!(Some(baseline), load_baseline(&path).unwrap());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A repetition in a deterministic simulation: the state after `start + length` steps is the same
+/// as after `start` steps, so everything from `start` on repeats every `length` steps
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The step at which the repetition was noticed
+    pub fn end(&self) -> usize {
+        self.start + self.length
+    }
+
+    /// The first step with the same state as `step`
+    pub fn equivalent_step(&self, step: usize) -> usize {
+        match step < self.start {
+            true => step,
+            false => self.start + (step - self.start) % self.length,
+        }
+    }
+
+    /// The value after `step` steps of something which grows by the same amount in every pass of
+    /// the cycle, like the height of a tower. `value_at` only has to know the steps up to
+    /// [`end`](Cycle::end).
+    pub fn extrapolate(&self, value_at: impl Fn(usize) -> i64, step: usize) -> i64 {
+        if step <= self.end() {
+            return value_at(step);
+        }
+        let passes = ((step - self.start) / self.length) as i64;
+        let growth = value_at(self.end()) - value_at(self.start);
+        value_at(self.equivalent_step(step)) + passes * growth
+    }
+}
+
+/// Remembers the fingerprint of every state of a simulation to notice when one comes back
+pub struct Detector<K> {
+    seen: HashMap<K, usize>,
+    steps: usize,
+}
+
+impl<K: Hash + Eq> Detector<K> {
+    pub fn new() -> Detector<K> {
+        Detector {
+            seen: HashMap::new(),
+            steps: 0,
+        }
+    }
+
+    /// The number of states recorded so far, which is the step of the next one
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Records the state of the next step, starting at step 0. Returns the cycle once a
+    /// fingerprint was seen before, counted from the first time it was seen.
+    pub fn record(&mut self, fingerprint: K) -> Option<Cycle> {
+        let step = self.steps;
+        self.steps += 1;
+        match self.seen.get(&fingerprint) {
+            Some(&start) => Some(Cycle {
+                start,
+                length: step - start,
+            }),
+            None => {
+                self.seen.insert(fingerprint, step);
+                None
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq> Default for Detector<K> {
+    fn default() -> Self {
+        Detector::new()
+    }
+}
+
+/// The value of a simulation after `steps` steps. `initial` is the fingerprint and value of the
+/// state before the first step, `step` advances the simulation by one step and returns the new
+/// fingerprint and value. Once a fingerprint repeats, the simulation runs one more pass of the
+/// cycle, and only if the value grows by the same amount at every step of it, the rest is
+/// extrapolated. That way a fingerprint which leaves out a little of the state can't go wrong
+/// unnoticed.
+pub fn extrapolate<K: Hash + Eq>(
+    steps: usize,
+    initial: (K, i64),
+    mut step: impl FnMut() -> (K, i64),
+) -> i64 {
+    let mut detector = Detector::new();
+    let mut values = vec![];
+    let mut candidate: Option<Cycle> = None;
+    let (mut fingerprint, mut value) = initial;
+    while values.len() < steps {
+        values.push(value);
+        let repeated = detector.record(fingerprint);
+        candidate = candidate.or(repeated);
+        if let Some(cycle) = candidate {
+            if values.len() > cycle.end() + cycle.length {
+                if confirmed(&cycle, &values) {
+                    return cycle.extrapolate(|step| values[step], steps);
+                }
+                candidate = None;
+            }
+        }
+        (fingerprint, value) = step();
+    }
+    value
+}
+
+/// Whether `values` grow by the same amount in the pass after `cycle` as in the cycle itself
+fn confirmed(cycle: &Cycle, values: &[i64]) -> bool {
+    let growth = values[cycle.end()] - values[cycle.start];
+    (cycle.end()..=cycle.end() + cycle.length)
+        .all(|step| values[step] - values[step - cycle.length] == growth)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn repetitions_are_detected() {
+        let mut detector = Detector::new();
+        // 7 → 4 → 2 → 1 → 4 → …
+        for fingerprint in [7, 4, 2, 1] {
+            assert_eq!(None, detector.record(fingerprint));
+        }
+        let cycle = detector.record(4).unwrap();
+        assert_eq!(
+            Cycle {
+                start: 1,
+                length: 3
+            },
+            cycle
+        );
+        assert_eq!(4, cycle.end());
+        assert_eq!(5, detector.steps());
+
+        assert_eq!(0, cycle.equivalent_step(0));
+        assert_eq!(3, cycle.equivalent_step(3));
+        assert_eq!(1, cycle.equivalent_step(4));
+        assert_eq!(2, cycle.equivalent_step(1_000_000_000_001));
+    }
+
+    #[test]
+    fn values_are_extrapolated() {
+        // the value grows by 10 per pass of 3 steps after a lead-in of 2 steps
+        let values = [0, 5, 7, 9, 14, 17, 19];
+        let cycle = Cycle {
+            start: 2,
+            length: 3,
+        };
+        assert_eq!(14, cycle.extrapolate(|step| values[step], 4));
+        assert_eq!(19, cycle.extrapolate(|step| values[step], 6));
+        assert_eq!(24, cycle.extrapolate(|step| values[step], 7));
+        assert_eq!(3 + 10 * 4, cycle.extrapolate(|step| values[step] - 4, 14));
+    }
+
+    #[test]
+    fn simulations_are_skipped_ahead() {
+        // a counter running modulo 5 which sums up everything it passes
+        let mut counter = 0;
+        let mut sum = 0;
+        let steps = 1_000_000_000_000_usize;
+        let result = extrapolate(steps, (0, 0), || {
+            counter = (counter + 1) % 5;
+            sum += counter;
+            (counter, sum)
+        });
+        assert_eq!(steps as i64 / 5 * 10, result);
+        assert_eq!(3, extrapolate(2, (0, 0), || (1, 3)));
+
+        // the fingerprint only sees the counter modulo 3, but the sum repeats every 6 steps
+        let mut counter = 0;
+        let mut sum = 0;
+        let result = extrapolate(20, (0, 0), || {
+            counter = (counter + 1) % 6;
+            sum += counter;
+            (counter % 3, sum)
+        });
+        assert_eq!(48, result);
+    }
+}
@@ -1,10 +1,10 @@
-use super::read_file;
-use ansi_term::Colour::Yellow;
+use super::error::{parse_number, Context};
+use super::{Answer, Error, Result, Solution};
+use crate::random::Random;
 
-pub fn sum_per_elf(filename: &str) -> Vec<i32> {
+pub fn sum_per_elf(input: &str) -> Result<Vec<i32>> {
     let mut counts: Vec<i32> = Vec::new();
 
-    let input = read_file(filename);
     let chunks = input.trim().split("\n\n");
 
     for chunk in chunks {
@@ -13,72 +13,88 @@ pub fn sum_per_elf(filename: &str) -> Vec<i32> {
         let mut count = 0;
 
         for item in items {
-            count += match item.parse() {
-                Ok(t) => t,
-                Err(_) => {
-                    println!("No integer found");
-                    0
-                }
-            };
+            count += parse_number::<i32>(item).within(input, item)?;
         }
 
         counts.push(count);
     }
 
-    counts
+    Ok(counts)
 }
 
-pub fn max_count(counts: Vec<i32>) -> i32 {
-    match counts.iter().max() {
-        Some(max) => *max,
-        None => {
-            println!("Should have a maximum value. Did you input values?");
-            0
-        }
-    }
+pub fn max_count(counts: Vec<i32>) -> Result<i32> {
+    counts
+        .into_iter()
+        .max()
+        .ok_or_else(|| Error::invalid("There are no calories to count"))
 }
 
-pub fn calculate_best(filename: &str) -> i32 {
-    let mut counts = sum_per_elf(filename);
+pub fn calculate_best(input: &str) -> Result<i32> {
+    let mut counts = sum_per_elf(input)?;
     counts.sort();
 
     max_count(counts)
 }
 
-pub fn calculate_best3(filename: &str) -> i32 {
-    let mut counts = sum_per_elf(filename);
+pub fn calculate_best3(input: &str) -> Result<i32> {
+    let mut counts = sum_per_elf(input)?;
     counts.sort();
 
-    counts.iter().rev().take(3).sum()
+    Ok(counts.iter().rev().take(3).sum())
 }
 
-pub fn run() {
-    println!("Hello Elves! Let's get started!");
-    let max_counts = calculate_best("input/day01-01.txt");
+/// `size` elves carrying one to ten snacks each
+fn generate(size: usize, random: &mut Random) -> String {
+    let elves: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            let snacks: Vec<String> = (0..random.range(1..=10))
+                .map(|_| random.range(1000..=60000).to_string())
+                .collect();
+            snacks.join("\n")
+        })
+        .collect();
+    elves.join("\n\n") + "\n"
+}
 
-    println!(
-        "The most calories carried are: {}",
-        Yellow.bold().paint(max_counts.to_string())
-    );
+pub fn part1(input: &str) -> Result<i32> {
+    calculate_best(input)
 }
-pub fn run2() {
-    println!("Find the best Three carriers!");
-
-    println!(
-        "The top three elves carry {} calories",
-        Yellow
-            .bold()
-            .paint(format!("{}", calculate_best3("input/day01-01.txt")))
-    );
+
+pub fn part2(input: &str) -> Result<i32> {
+    calculate_best3(input)
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    fn day(&self) -> u32 {
+        1
+    }
+    fn title(&self) -> &'static str {
+        "Calorie Counting"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        sum_per_elf(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn sums_per_elf_matches() {
-        let counts = sum_per_elf("input/day01-test.txt");
+        let counts = sum_per_elf(&read_file("input/day01-test.txt").unwrap()).unwrap();
         assert_eq!(6000, counts[0]);
         assert_eq!(4000, counts[1]);
         assert_eq!(11000, counts[2]);
@@ -87,23 +103,35 @@ mod tests {
     }
     #[test]
     fn max_count_works() {
-        assert_eq!(24000, max_count(vec![24000, 10000, 4000]));
+        assert_eq!(24000, max_count(vec![24000, 10000, 4000]).unwrap());
     }
     #[test]
-    fn max_count_works_for_empty_vector() {
-        assert_eq!(0, max_count(vec![]));
+    fn max_count_fails_for_empty_vector() {
+        let error = max_count(vec![]).unwrap_err();
+        assert_eq!("There are no calories to count", error.to_string());
     }
     #[test]
     fn max_sum_gets_returned() {
-        let counts = sum_per_elf("input/day01-test.txt");
-        assert_eq!(24000, max_count(counts));
+        let counts = sum_per_elf(&read_file("input/day01-test.txt").unwrap()).unwrap();
+        assert_eq!(24000, max_count(counts).unwrap());
+    }
+    #[test]
+    fn non_numbers_are_reported() {
+        let error = sum_per_elf("100\n200\n\n3x0\n").unwrap_err();
+        assert_eq!("4:1: expected a number `3x0`", error.to_string());
     }
     #[test]
     fn calculate_best_works() {
-        assert_eq!(24000, calculate_best("input/day01-test.txt"));
+        assert_eq!(
+            24000,
+            calculate_best(&read_file("input/day01-test.txt").unwrap()).unwrap()
+        );
     }
     #[test]
     fn calculate_best3_works() {
-        assert_eq!(45000, calculate_best3("input/day01-test.txt"));
+        assert_eq!(
+            45000,
+            calculate_best3(&read_file("input/day01-test.txt").unwrap()).unwrap()
+        );
     }
 }
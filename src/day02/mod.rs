@@ -1,19 +1,21 @@
-use super::read_file;
-use ansi_term::Colour::Yellow;
+use super::{Answer, Error, Result, Solution};
+use crate::random::Random;
 use std::collections::HashMap;
 
-pub fn get_parsed_input(filename: &str) -> HashMap<String, u32> {
+pub fn get_parsed_input(input: &str) -> Result<HashMap<String, u32>> {
     let mut hash_map = HashMap::new();
-    let input: String = read_file(filename);
     for line in input.lines() {
         if !line.is_empty() {
+            if !matches!(line.as_bytes(), [b'A'..=b'C', b' ', b'X'..=b'Z']) {
+                return Err(Error::parse(line, "expected a round like `A Y`").within(input, line));
+            }
             hash_map
                 .entry(line.to_owned())
                 .and_modify(|count| *count += 1)
                 .or_insert(1);
         }
     }
-    hash_map
+    Ok(hash_map)
 }
 
 fn build_pattern(a: char, b: char) -> String {
@@ -90,43 +92,70 @@ pub fn calculate_score(result_map: HashMap<String, u32>, score_map: HashMap<Stri
     score
 }
 
-pub fn run() {
-    println!("whose tent gets to be closest to the snack storage");
-    let score = calculate_score(
-        get_parsed_input("input/day02.txt"),
-        score_matrix('X', 'Y', 'Z'),
-    );
-
-    println!(
-        "Your totaled score: {}",
-        Yellow.bold().paint(format!("{}", score))
-    );
+/// `size` rounds of rock, paper, scissors
+fn generate(size: usize, random: &mut Random) -> String {
+    (0..size)
+        .map(|_| {
+            let opponent = random.pick(&['A', 'B', 'C']);
+            format!("{} {}\n", opponent, random.pick(&['X', 'Y', 'Z']))
+        })
+        .collect()
 }
 
-pub fn run2() {
-    println!("Let's be unconspicuous!");
+pub fn part1(input: &str) -> Result<u32> {
+    Ok(calculate_score(
+        get_parsed_input(input)?,
+        score_matrix('X', 'Y', 'Z'),
+    ))
+}
 
-    let score = calculate_score(
-        get_parsed_input("input/day02.txt"),
+pub fn part2(input: &str) -> Result<u32> {
+    Ok(calculate_score(
+        get_parsed_input(input)?,
         score_matrix2('Z', 'Y', 'X'),
-    );
+    ))
+}
 
-    println!(
-        "You're adapted score is {}.",
-        Yellow.bold().paint(format!("{}", score))
-    );
+pub struct Day02;
+
+impl Solution for Day02 {
+    fn day(&self) -> u32 {
+        2
+    }
+    fn title(&self) -> &'static str {
+        "Rock Paper Scissors"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        get_parsed_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn input_parsing_works() {
-        let hash_map = get_parsed_input("input/day02-test.txt");
+        let hash_map = get_parsed_input(&read_file("input/day02-test.txt").unwrap()).unwrap();
         assert_eq!(3, hash_map.len());
     }
 
+    #[test]
+    fn unknown_moves_are_reported() {
+        let error = get_parsed_input("A Y\nB Q\n").unwrap_err();
+        assert_eq!("2:1: expected a round like `A Y` `B Q`", error.to_string());
+    }
+
     #[test]
     fn score_matrix_is_correct() {
         let score_matrix = score_matrix('X', 'Y', 'Z');
@@ -140,7 +169,7 @@ mod tests {
         assert_eq!(
             15,
             calculate_score(
-                get_parsed_input("input/day02-test.txt"),
+                get_parsed_input(&read_file("input/day02-test.txt").unwrap()).unwrap(),
                 score_matrix('X', 'Y', 'Z'),
             )
         );
@@ -151,7 +180,7 @@ mod tests {
         assert_eq!(
             12,
             calculate_score(
-                get_parsed_input("input/day02-test.txt"),
+                get_parsed_input(&read_file("input/day02-test.txt").unwrap()).unwrap(),
                 score_matrix2('Z', 'Y', 'X'),
             )
         );
@@ -1,5 +1,5 @@
-use super::read_file;
-use ansi_term::Colour::Yellow;
+use super::{Answer, Error, Result, Solution};
+use crate::random::Random;
 use std::collections::HashMap;
 
 fn priority(a: char) -> u32 {
@@ -28,15 +28,39 @@ fn get_common_char(items: &str) -> Option<char> {
         .find(|&letter| letters.contains_key(&letter))
 }
 
-pub fn priority_sum(input: String) -> u32 {
+/// The rucksacks, one per line, holding items `a` to `z` and `A` to `Z` in two equal halves
+fn parse_rucksacks(input: &str) -> Result<Vec<&str>> {
     input
         .lines()
-        .map(|line| priority(get_common_char(line).unwrap()))
-        .sum()
+        .map(|line| {
+            let unknown = line
+                .char_indices()
+                .find(|(_, item)| !item.is_ascii_alphabetic());
+            if let Some((at, item)) = unknown {
+                let item = &line[at..at + item.len_utf8()];
+                return Err(
+                    Error::parse(item, "expected an item `a` to `z` or `A` to `Z`")
+                        .within(input, item),
+                );
+            }
+            if line.len() % 2 != 0 {
+                return Err(
+                    Error::parse(line, "the compartments differ in size").within(input, line)
+                );
+            }
+            Ok(line)
+        })
+        .collect()
 }
 
-pub fn priority_for_file(filename: &str) -> u32 {
-    priority_sum(read_file(filename))
+pub fn priority_sum(input: &str) -> Result<u32> {
+    parse_rucksacks(input)?
+        .into_iter()
+        .map(|line| match get_common_char(line) {
+            Some(item) => Ok(priority(item)),
+            None => Err(Error::parse(line, "no item is in both compartments").within(input, line)),
+        })
+        .sum()
 }
 
 fn unique_items(items: &str) -> HashMap<char, u32> {
@@ -71,42 +95,91 @@ pub fn find_valid_badge(backpacks: &[&str]) -> Option<char> {
         .find_map(|(key, &val)| if val == 3 { Some(*key) } else { None })
 }
 
-pub fn calculate_group_sums_for_file(filename: &str) -> u32 {
-    let input = read_file(filename);
+pub fn calculate_group_sums(input: &str) -> Result<u32> {
     let mut sum = 0;
 
-    for item in input.lines().collect::<Vec<&str>>().chunks(3) {
-        sum += priority(find_valid_badge(item).unwrap())
+    for item in parse_rucksacks(input)?.chunks(3) {
+        let badge = match item {
+            [_, _, _] => find_valid_badge(item),
+            _ => None,
+        };
+        match badge {
+            Some(badge) => sum += priority(badge),
+            None => {
+                return Err(Error::parse(item[0], "no badge is shared by this group")
+                    .within(input, item[0]))
+            }
+        }
     }
 
-    sum
+    Ok(sum)
 }
 
-pub fn run() {
-    println!("The Rucksacks are wrong Packed!");
+/// `size` rucksacks in groups of three. Every elf of a group has its own 17 kinds of items, so
+/// only the badge is shared.
+fn generate(size: usize, random: &mut Random) -> String {
+    let mut kinds: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+    let mut rucksacks = String::new();
+    for _ in 0..(size / 3).max(1) {
+        random.shuffle(&mut kinds);
+        let (badge, others) = kinds.split_first().expect("there are 52 kinds of items");
+        for items in others.chunks(17) {
+            rucksacks += &rucksack(*badge, items, random);
+            rucksacks.push('\n');
+        }
+    }
+    rucksacks
+}
 
-    let score = priority_for_file("input/day03.txt");
+/// A rucksack with the first of `items` in both compartments and the badge in the first one
+fn rucksack(badge: char, items: &[char], random: &mut Random) -> String {
+    let (common, others) = items.split_first().expect("an elf has items");
+    let (left, right) = others.split_at(others.len() / 2);
+    let size = random.range(2..=16);
+    let mut first = vec![*common, badge];
+    first.extend((2..size).map(|_| *random.pick(left)));
+    let mut second = vec![*common];
+    second.extend((1..size).map(|_| *random.pick(right)));
+    random.shuffle(&mut first);
+    random.shuffle(&mut second);
+    first.into_iter().chain(second).collect()
+}
 
-    println!(
-        "This is the priority score: {}",
-        Yellow.bold().paint(format!("{}", score))
-    );
+pub fn part1(input: &str) -> Result<u32> {
+    priority_sum(input)
 }
 
-pub fn run2() {
-    println!("Oh no! What's wrong with the badges???");
+pub fn part2(input: &str) -> Result<u32> {
+    calculate_group_sums(input)
+}
 
-    let score = calculate_group_sums_for_file("input/day03.txt");
+pub struct Day03;
 
-    println!(
-        "Corrected Badge-score: {}",
-        Yellow.bold().paint(format!("{}", score))
-    );
+impl Solution for Day03 {
+    fn day(&self) -> u32 {
+        3
+    }
+    fn title(&self) -> &'static str {
+        "Rucksack Reorganization"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_rucksacks(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn priority_is_correct() {
@@ -131,13 +204,25 @@ mod tests {
     }
 
     #[test]
-    fn calculates_correct_priority_sum() {
-        assert_eq!(157, priority_sum(read_file("input/day03-test.txt")));
+    fn broken_rucksacks_are_reported() {
+        let error = parse_rucksacks("abcA\nab!B\n").unwrap_err();
+        assert_eq!(
+            "2:3: expected an item `a` to `z` or `A` to `Z` `!`",
+            error.to_string()
+        );
+        let error = parse_rucksacks("abcA\nabc\n").unwrap_err();
+        assert_eq!(
+            "2:1: the compartments differ in size `abc`",
+            error.to_string()
+        );
     }
 
     #[test]
-    fn calculates_correct_priority_sum_for_file() {
-        assert_eq!(157, priority_for_file("input/day03-test.txt"));
+    fn calculates_correct_priority_sum() {
+        assert_eq!(
+            157,
+            priority_sum(&read_file("input/day03-test.txt").unwrap()).unwrap()
+        );
     }
 
     #[test]
@@ -163,6 +248,9 @@ mod tests {
 
     #[test]
     fn calculates_correct_sum() {
-        assert_eq!(70, calculate_group_sums_for_file("input/day03-test.txt"));
+        assert_eq!(
+            70,
+            calculate_group_sums(&read_file("input/day03-test.txt").unwrap()).unwrap()
+        );
     }
 }
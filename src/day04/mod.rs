@@ -1,132 +1,151 @@
-use crate::read_file;
-use ansi_term::Colour::Yellow;
+use crate::error::{parse_number, Context};
+use crate::random::Random;
+use crate::{Answer, Error, IntervalSet, Result, Solution};
 
-#[derive(PartialEq, Debug)]
-struct Range {
-    min: i32,
-    max: i32,
-}
+type Range = IntervalSet<i32>;
 
-impl Range {
-    pub fn new(range: &str) -> Range {
-        let mut split = range.split('-');
-        let min: i32 = split.next().unwrap().parse().unwrap();
-        let max: i32 = split.next().unwrap().parse().unwrap();
-
-        if min > max {
-            Range { min: max, max: min }
-        } else {
-            Range { min, max }
-        }
-    }
+fn parse_range(range: &str) -> Result<Range> {
+    let (min, max) = range
+        .split_once('-')
+        .ok_or_else(|| Error::parse(range, "expected a range like `2-4`"))?;
+    let min: i32 = parse_number(min).within(range, min)?;
+    let max: i32 = parse_number(max).within(range, max)?;
 
-    pub fn contains(&self, b: &Range) -> bool {
-        self.min <= b.min && self.max >= b.max
-    }
-    pub fn overlap(&self, b: &Range) -> bool {
-        self.min <= b.max && self.max >= b.min
-    }
+    Ok(Range::interval(min, max))
 }
 
-fn get_ranges(ranges: &str) -> (Range, Range) {
-    let mut split = ranges.split(',');
+fn get_ranges(ranges: &str) -> Result<(Range, Range)> {
+    let (left, right) = ranges
+        .split_once(',')
+        .ok_or_else(|| Error::parse(ranges, "expected two ranges separated by `,`"))?;
 
-    let left = Range::new(split.next().unwrap());
-    let right = Range::new(split.next().unwrap());
+    let left = parse_range(left).within(ranges, left)?;
+    let right = parse_range(right).within(ranges, right)?;
 
-    (left, right)
+    Ok((left, right))
 }
 
-pub fn calculate(filename: &str) -> i32 {
-    let input = read_file(filename);
-
+/// The pair of section assignments on every line
+fn parse_input(input: &str) -> Result<Vec<(Range, Range)>> {
     input
         .lines()
-        .map(|line| {
-            let (left, right) = get_ranges(line);
-            if left.contains(&right) {
-                return 1;
-            }
-            if right.contains(&left) {
-                return 1;
-            }
-            0
-        })
-        .sum()
+        .map(|line| get_ranges(line).within(input, line))
+        .collect()
 }
 
-pub fn calculate2(filename: &str) -> i32 {
-    let input = read_file(filename);
+pub fn calculate(input: &str) -> Result<i32> {
+    let pairs = parse_input(input)?;
+    Ok(pairs
+        .iter()
+        .filter(|(left, right)| left.is_superset(right) || right.is_superset(left))
+        .count() as i32)
+}
 
-    input
-        .split('\n')
-        .map(|line| {
-            let (left, right) = get_ranges(line);
-            if left.overlap(&right) {
-                return 1;
-            }
-            0
+pub fn calculate2(input: &str) -> Result<i32> {
+    let pairs = parse_input(input)?;
+    Ok(pairs
+        .iter()
+        .filter(|(left, right)| left.overlaps(right))
+        .count() as i32)
+}
+
+/// `size` pairs of section assignments
+fn generate(size: usize, random: &mut Random) -> String {
+    let mut range = || {
+        let start = random.range(1..=99);
+        format!("{}-{}", start, random.range(start..=99))
+    };
+    (0..size)
+        .map(|_| {
+            let (left, right) = (range(), range());
+            format!("{},{}\n", left, right)
         })
-        .sum()
+        .collect()
 }
 
-pub fn run() {
-    println!("Camp Cleanup");
-    let count = calculate("input/day04.txt");
-    println!(
-        "There are {} doubled ranges",
-        Yellow.bold().paint(format!("{}", count))
-    );
+pub fn part1(input: &str) -> Result<i32> {
+    calculate(input)
 }
 
-pub fn run2() {
-    println!("This wasn't extensive enough!");
-    let count = calculate2("input/day04.txt");
-    println!(
-        "There are {} overlapping ranges",
-        Yellow.bold().paint(format!("{}", count))
-    );
+pub fn part2(input: &str) -> Result<i32> {
+    calculate2(input)
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    fn day(&self) -> u32 {
+        4
+    }
+    fn title(&self) -> &'static str {
+        "Camp Cleanup"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn range_is_correct_parsed() {
-        assert_eq!(Range { min: 2, max: 4 }, Range::new("2-4"));
-        assert_eq!(Range { min: 6, max: 8 }, Range::new("8-6"));
+        assert_eq!(Range::interval(2, 4), parse_range("2-4").unwrap());
+        assert_eq!(Range::interval(6, 8), parse_range("8-6").unwrap());
     }
 
     #[test]
     fn line_is_correct_parsed() {
         assert_eq!(
-            (Range { min: 2, max: 4 }, Range { min: 6, max: 8 }),
-            get_ranges("2-4,6-8")
+            (Range::interval(2, 4), Range::interval(6, 8)),
+            get_ranges("2-4,6-8").unwrap()
         );
     }
 
+    #[test]
+    fn broken_line_is_reported() {
+        let error = calculate("2-4,6-8\n2-3,4-x\n").unwrap_err();
+        assert_eq!("2:7: expected a number `x`", error.to_string());
+    }
+
     #[test]
     fn range_is_contained() {
-        let a = Range { min: 1, max: 2 };
-        let b = Range { min: 1, max: 3 };
-        assert!(b.contains(&a));
+        let a = Range::interval(1, 2);
+        let b = Range::interval(1, 3);
+        assert!(b.is_superset(&a));
     }
 
     #[test]
     fn range_is_not_contained() {
-        let a = Range { min: 1, max: 2 };
-        let b = Range { min: 1, max: 3 };
-        assert_eq!(false, a.contains(&b));
+        let a = Range::interval(1, 2);
+        let b = Range::interval(1, 3);
+        assert!(!a.is_superset(&b));
     }
 
     #[test]
     fn test_data_produced_correct_result() {
-        assert_eq!(2, calculate("input/day04-test.txt"));
+        assert_eq!(
+            2,
+            calculate(&read_file("input/day04-test.txt").unwrap()).unwrap()
+        );
     }
 
     #[test]
     fn test_data_produced_correct_result_for_part_two() {
-        assert_eq!(4, calculate2("input/day04-test.txt"));
+        assert_eq!(
+            4,
+            calculate2(&read_file("input/day04-test.txt").unwrap()).unwrap()
+        );
     }
 }
@@ -1,6 +1,7 @@
-use crate::read_file;
-use ansi_term::Colour::Yellow;
-use regex::Regex;
+use crate::error::Context;
+use crate::parse::{columns, numbers};
+use crate::random::Random;
+use crate::{Answer, Error, Result, Solution};
 
 #[derive(PartialEq, Debug)]
 struct Stack {
@@ -46,102 +47,183 @@ impl Stacks {
         }
     }
 
-    pub fn perform_instructions(&mut self, instructions: String) {
-        for instruction in instructions.lines() {
-            let instruction = parse_instruction(instruction);
+    pub fn perform_instructions(&mut self, instructions: &str) -> Result<()> {
+        for line in instructions.lines() {
+            let instruction = self.parse_instruction(line).within(instructions, line)?;
             for _i in 0..instruction.count {
                 self.mv(instruction.from - 1, instruction.to - 1);
             }
         }
+        Ok(())
     }
 
-    pub fn perform_instructions9001(&mut self, instructions: String) {
-        for instruction in instructions.lines() {
-            let instruction = parse_instruction(instruction);
+    pub fn perform_instructions9001(&mut self, instructions: &str) -> Result<()> {
+        for line in instructions.lines() {
+            let instruction = self.parse_instruction(line).within(instructions, line)?;
             self.mv_stack(instruction.from - 1, instruction.to - 1, instruction.count);
         }
+        Ok(())
     }
 
-    pub fn get_tops(&self) -> Vec<char> {
-        let mut tops: Vec<char> = Vec::new();
-        for stack in &self.items {
-            tops.push(stack.items[stack.items.len() - 1].to_owned());
+    /// Parses an instruction and checks that it can be performed on the current stacks
+    fn parse_instruction(&self, line: &str) -> Result<Instruction> {
+        let instruction = parse_instruction(line)?;
+        let stack_count = self.items.len();
+
+        if !(1..=stack_count).contains(&instruction.from)
+            || !(1..=stack_count).contains(&instruction.to)
+        {
+            return Err(Error::parse(line, "no such stack"));
+        }
+        if self.items[instruction.from - 1].items.len() < instruction.count {
+            return Err(Error::parse(line, "not enough crates to move"));
         }
-        tops
+        Ok(instruction)
+    }
+
+    pub fn get_tops(&self) -> Vec<char> {
+        self.items
+            .iter()
+            .filter_map(|stack| stack.items.last().copied())
+            .collect()
     }
 }
 
 fn parse_to_stacks(input: &str) -> Stacks {
     let mut stacks: Vec<Stack> = Vec::new();
 
-    for cols in input.chars().collect::<Vec<char>>().chunks(4) {
-        if cols[1] != ' ' {
-            stacks.push(Stack {
-                items: vec![cols[1]],
-            });
-        } else {
-            stacks.push(Stack { items: vec![] })
+    for cell in columns(input, 4) {
+        match cell.chars().nth(1) {
+            Some(' ') | None => stacks.push(Stack { items: vec![] }),
+            Some(item) => stacks.push(Stack { items: vec![item] }),
         }
     }
 
     Stacks { items: stacks }
 }
 
-fn parse_input(filename: &str) -> (Stacks, String) {
-    let input = read_file(filename);
-
-    let mut split = input.split("\n\n");
-
-    let stack_input = split.next().unwrap();
-    let instructions = split.next().unwrap().to_owned();
+fn parse_input(input: &str) -> Result<(Stacks, &str)> {
+    let (stack_input, instructions) = input.split_once("\n\n").ok_or_else(|| {
+        Error::invalid("Expected stacks and instructions separated by a blank line")
+    })?;
 
     let mut stack_input = stack_input.lines().rev();
 
-    let _counter_line = stack_input.next().to_owned();
-    let first_line = stack_input.next().to_owned();
+    let _counter_line = stack_input.next();
+    let first_line = stack_input
+        .next()
+        .ok_or_else(|| Error::invalid("Expected at least one line of crates"))?;
 
-    let mut stacks = parse_to_stacks(first_line.unwrap());
+    let mut stacks = parse_to_stacks(first_line);
     for line in stack_input {
-        stacks.add(parse_to_stacks(line));
+        let row = parse_to_stacks(line);
+        if row.items.len() > stacks.items.len() {
+            return Err(
+                Error::parse(line, "more stacks than in the bottom row").within(input, line)
+            );
+        }
+        stacks.add(row);
     }
 
-    (stacks, instructions)
+    Ok((stacks, instructions))
 }
 
-fn parse_instruction(instruction: &str) -> Instruction {
-    let regex = Regex::new(r"move (\d+) from (.*) to (.*)").unwrap();
-
-    let cap = regex.captures(instruction).unwrap();
+fn parse_instruction(instruction: &str) -> Result<Instruction> {
+    let [count, from, to] = numbers(instruction, "move {} from {} to {}")?;
+    Ok(Instruction { from, to, count })
+}
 
-    Instruction {
-        count: cap[1].parse().unwrap(),
-        from: cap[2].parse().unwrap(),
-        to: cap[3].parse().unwrap(),
+/// Nine stacks of crates and `size` moves between them
+fn generate(size: usize, random: &mut Random) -> String {
+    let mut stacks: Vec<Vec<char>> = (0..9)
+        .map(|_| {
+            (0..random.range(1..=8))
+                .map(|_| char::from(b'A' + random.range(0..=25) as u8))
+                .collect()
+        })
+        .collect();
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+    let mut drawing: Vec<String> = (0..height)
+        .rev()
+        .map(|level| {
+            let cells: Vec<String> = stacks
+                .iter()
+                .map(|stack| {
+                    stack
+                        .get(level)
+                        .map_or("   ".to_string(), |item| format!("[{}]", item))
+                })
+                .collect();
+            cells.join(" ").trim_end().to_string()
+        })
+        .collect();
+    let numbers: Vec<String> = (1..=stacks.len())
+        .map(|number| format!(" {} ", number))
+        .collect();
+    drawing.push(numbers.join(" "));
+
+    // the moves are played along, so there are always enough crates to move
+    let mut moves = vec![];
+    for _ in 0..size {
+        let from = loop {
+            let from = random.index(stacks.len());
+            if !stacks[from].is_empty() {
+                break from;
+            }
+        };
+        let to = (from + 1 + random.index(stacks.len() - 1)) % stacks.len();
+        let count = random.range(1..=stacks[from].len() as i64) as usize;
+        let rest = stacks[from].len() - count;
+        let moved = stacks[from].split_off(rest);
+        stacks[to].extend(moved);
+        moves.push(format!("move {} from {} to {}", count, from + 1, to + 1));
     }
+    format!("{}\n\n{}\n", drawing.join("\n"), moves.join("\n"))
 }
 
-pub fn run() {
-    println!("Supply Stacks");
-    let (mut stacks, instructions) = parse_input("input/day05.txt");
-    stacks.perform_instructions(instructions);
-    let answer: String = stacks.get_tops().iter().collect();
-    println!(
-        "Final Supply Stack top crates: {}",
-        Yellow.bold().paint(answer)
-    );
+pub fn part1(input: &str) -> Result<String> {
+    let (mut stacks, instructions) = parse_input(input)?;
+    stacks
+        .perform_instructions(instructions)
+        .within(input, instructions)?;
+    Ok(stacks.get_tops().iter().collect())
 }
 
-pub fn run2() {
-    println!("Oh, it's a 9001");
-    let (mut stacks, instructions) = parse_input("input/day05.txt");
-    stacks.perform_instructions9001(instructions);
-    let answer: String = stacks.get_tops().iter().collect();
-    println!("Then the answer is {}", Yellow.bold().paint(answer));
+pub fn part2(input: &str) -> Result<String> {
+    let (mut stacks, instructions) = parse_input(input)?;
+    stacks
+        .perform_instructions9001(instructions)
+        .within(input, instructions)?;
+    Ok(stacks.get_tops().iter().collect())
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    fn day(&self) -> u32 {
+        5
+    }
+    fn title(&self) -> &'static str {
+        "Supply Stacks"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn parse_to_stacks_work() {
@@ -169,7 +251,8 @@ mod test {
 
     #[test]
     fn parsing_input() {
-        let (stacks, _instructions) = parse_input("input/day05-test.txt");
+        let input = read_file("input/day05-test.txt").unwrap();
+        let (stacks, _instructions) = parse_input(&input).unwrap();
         assert_eq!(
             Stack {
                 items: vec!['M', 'C', 'D']
@@ -180,7 +263,8 @@ mod test {
 
     #[test]
     fn move_from_stack_to_stack() {
-        let (mut stacks, _instructions) = parse_input("input/day05-test.txt");
+        let input = read_file("input/day05-test.txt").unwrap();
+        let (mut stacks, _instructions) = parse_input(&input).unwrap();
         stacks.mv(1, 0);
         assert_eq!(
             Stack {
@@ -205,15 +289,24 @@ mod test {
                 to: 1,
                 count: 3
             },
-            parse_instruction(instruction)
+            parse_instruction(instruction).unwrap()
         );
     }
 
+    #[test]
+    fn impossible_instructions_are_reported() {
+        let input = read_file("input/day05-test.txt").unwrap();
+        let input = input.replace("move 1 from 1 to 2", "move 1 from 4 to 2");
+        let error = part1(&input).unwrap_err();
+        assert_eq!("9:1: no such stack `move 1 from 4 to 2`", error.to_string());
+    }
+
     #[test]
     fn moving_instructions_are_fulfilled() {
-        let (mut stacks, instructions) = parse_input("input/day05-test.txt");
+        let input = read_file("input/day05-test.txt").unwrap();
+        let (mut stacks, instructions) = parse_input(&input).unwrap();
 
-        stacks.perform_instructions(instructions);
+        stacks.perform_instructions(instructions).unwrap();
 
         assert_eq!(Stack { items: vec!['C'] }, stacks.items[0]);
         assert_eq!(Stack { items: vec!['M'] }, stacks.items[1]);
@@ -227,14 +320,16 @@ mod test {
 
     #[test]
     fn top_crates_are_returned() {
-        let (mut stacks, instructions) = parse_input("input/day05-test.txt");
-        stacks.perform_instructions(instructions);
+        let input = read_file("input/day05-test.txt").unwrap();
+        let (mut stacks, instructions) = parse_input(&input).unwrap();
+        stacks.perform_instructions(instructions).unwrap();
         assert_eq!(vec!['C', 'M', 'Z'], stacks.get_tops());
     }
 
     #[test]
     fn moving_stacks() {
-        let (mut stacks, _instructions) = parse_input("input/day05-test.txt");
+        let input = read_file("input/day05-test.txt").unwrap();
+        let (mut stacks, _instructions) = parse_input(&input).unwrap();
         stacks.mv_stack(1, 0, 1);
         assert_eq!(
             Stack {
@@ -1,12 +1,15 @@
-use crate::read_file;
-use ansi_term::Colour::Yellow;
+use crate::random::Random;
+use crate::{Answer, Error, Result, Solution};
 use itertools::Itertools;
 
 pub fn validate(input: &str) -> bool {
-    input.chars().into_iter().unique().collect::<String>().len() == input.len()
+    input.chars().unique().collect::<String>().len() == input.len()
 }
 
 pub fn parse_line(input: &str) -> Option<usize> {
+    if 4 > input.len() {
+        return None;
+    }
     for i in 0..input.len() - 4 {
         if validate(&input[i..i + 4]) {
             return Some(i + 4);
@@ -27,22 +30,62 @@ pub fn parse_line_with_packet_size(input: &str, packetsize: usize) -> Option<usi
     None
 }
 
-pub fn run() {
-    println!("Tuning Trouble");
-    let answer = parse_line(&read_file("input/day06.txt")).unwrap();
-    println!(
-        "Starting packet marker starts at: {}",
-        Yellow.bold().paint(format!("{}", answer))
-    );
+/// A stream of `size` characters and 15 more, the start-of-message marker is among the last
+fn generate(size: usize, random: &mut Random) -> String {
+    // fourteen characters out of only thirteen letters always repeat one
+    let mut stream: String = (0..size)
+        .map(|_| char::from(b'a' + random.range(0..=12) as u8))
+        .collect();
+    let mut letters: Vec<char> = ('a'..='z').collect();
+    random.shuffle(&mut letters);
+    stream.extend(&letters[..15]);
+    stream + "\n"
+}
+
+/// The datastream buffer, a single line of the letters `a` to `z`
+fn parse_stream(input: &str) -> Result<&str> {
+    let stream = input.trim();
+    if let Some((at, letter)) = stream
+        .char_indices()
+        .find(|(_, letter)| !letter.is_ascii_lowercase())
+    {
+        let letter = &stream[at..at + letter.len_utf8()];
+        return Err(Error::parse(letter, "expected a letter `a` to `z`").within(input, letter));
+    }
+    Ok(stream)
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    parse_line(parse_stream(input)?)
+        .ok_or_else(|| Error::invalid("No start-of-packet marker found"))
 }
 
-pub fn run2() {
-    println!("... and for the messaage: ");
-    let answer = parse_line_with_packet_size(&read_file("input/day06.txt"), 14).unwrap();
-    println!(
-        "Message stream starts at: {}",
-        Yellow.bold().paint(format!("{}", answer))
-    );
+pub fn part2(input: &str) -> Result<usize> {
+    parse_line_with_packet_size(parse_stream(input)?, 14)
+        .ok_or_else(|| Error::invalid("No start-of-message marker found"))
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    fn day(&self) -> u32 {
+        6
+    }
+    fn title(&self) -> &'static str {
+        "Tuning Trouble"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_stream(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
@@ -52,7 +95,7 @@ mod test {
     #[test]
     fn pattern_validator_works() {
         assert!(validate("jpqm"));
-        assert_eq!(false, validate("jpqj"));
+        assert!(!validate("jpqj"));
     }
 
     #[test]
@@ -64,6 +107,12 @@ mod test {
         assert_eq!(11, parse_line("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").unwrap());
     }
 
+    #[test]
+    fn broken_streams_are_reported() {
+        let error = parse_stream("mjqjpq1mgbljs\n").unwrap_err();
+        assert_eq!("1:7: expected a letter `a` to `z` `1`", error.to_string());
+    }
+
     #[test]
     fn parse_line_with_packetsize_works() {
         assert_eq!(
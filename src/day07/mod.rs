@@ -1,7 +1,7 @@
-use crate::read_file;
-use ansi_term::Colour::Yellow;
+use crate::error::{parse_number, Context};
+use crate::random::Random;
+use crate::{Answer, Error, Result, Solution};
 use std::collections::HashMap;
-use std::process::exit;
 
 #[derive(PartialEq)]
 enum LineMode {
@@ -9,8 +9,7 @@ enum LineMode {
     Output,
 }
 
-pub fn calculate() -> HashMap<String, u32> {
-    let input = read_file("input/day07.txt");
+pub fn calculate(input: &str) -> Result<HashMap<String, u32>> {
     let mut dir_map: HashMap<String, u32> = HashMap::new();
     let mut current_dir: Vec<String> = vec!["/".to_string()];
     let mut line_mode = LineMode::Command;
@@ -18,14 +17,17 @@ pub fn calculate() -> HashMap<String, u32> {
 
     for line in input.lines() {
         if line_mode == LineMode::Output {
-            if line[0..1] == *"$" {
+            if line.starts_with('$') {
                 line_mode = LineMode::Command;
             } else {
-                (size_string, _) = line.split_once(' ').unwrap();
+                (size_string, _) = line.split_once(' ').ok_or_else(|| {
+                    Error::parse(line, "expected `dir <name>` or `<size> <name>`")
+                        .within(input, line)
+                })?;
                 if size_string == "dir" {
                     continue;
                 }
-                let size: u32 = size_string.parse().unwrap();
+                let size: u32 = parse_number(size_string).within(input, size_string)?;
                 for i in 0..current_dir.len() {
                     let key = current_dir[0..=i].join("/");
                     dir_map
@@ -36,19 +38,15 @@ pub fn calculate() -> HashMap<String, u32> {
             }
         }
         if line_mode == LineMode::Command {
-            let chars: Vec<char> = line.chars().collect();
-            if chars[0] != '$' {
-                panic!("Expected command");
-            }
-
-            // Command
+            let command = line
+                .strip_prefix("$ ")
+                .ok_or_else(|| Error::parse(line, "expected a command").within(input, line))?;
 
-            match &line[2..=3] {
-                "ls" => {
+            match command.split_once(' ') {
+                None if command == "ls" => {
                     line_mode = LineMode::Output;
                 }
-                "cd" => {
-                    let dir = &line[5..];
+                Some(("cd", dir)) => {
                     if dir == ".." && !current_dir.is_empty() {
                         current_dir.pop();
                     } else if dir == "/" {
@@ -58,34 +56,21 @@ pub fn calculate() -> HashMap<String, u32> {
                     }
                 }
                 _ => {
-                    println!("Unknown command {}", &line[2..3]);
-                    exit(1)
+                    return Err(Error::parse(command, "unknown command").within(input, command));
                 }
             }
         }
     }
-    dir_map
+    Ok(dir_map)
 }
 
-pub fn run() {
-    let dir_map = calculate();
-    let v: Vec<u32> = dir_map
-        .into_values()
-        .filter(|value| *value < 100_000)
-        .collect();
-    let answer = v.iter().sum::<u32>();
-    println!(
-        "Sum of below 100_000 dirs is: {}",
-        Yellow.bold().paint(format!("{}", answer))
-    );
+pub fn sum_of_small_dirs(dir_map: &HashMap<String, u32>) -> u32 {
+    dir_map.values().filter(|value| **value < 100_000).sum()
 }
 
-pub fn run2() {
-    let dir_map = calculate();
-    let total = *dir_map.get("/").unwrap();
-    let needed: u32 = total - 40_000_000;
-    println!("Total used is {}", total);
-    println!("To be freed {}", needed);
+pub fn dir_to_delete(dir_map: &HashMap<String, u32>) -> (String, u32) {
+    let total = dir_map.get("/").copied().unwrap_or(0);
+    let needed: u32 = total.saturating_sub(40_000_000);
     let mut target_key = "/".to_string();
     let mut min_val = total;
     for (key, val) in dir_map.iter() {
@@ -94,19 +79,158 @@ pub fn run2() {
             min_val = *val;
         }
     }
-    println!(
-        "We should delete {} with size: {}",
-        Yellow.bold().paint(target_key),
-        Yellow.bold().paint(min_val.to_string())
-    );
+    (target_key, min_val)
+}
+
+/// A generated directory, written as the terminal session which explores it
+struct Directory {
+    name: String,
+    files: Vec<(i64, String)>,
+    directories: Vec<Directory>,
+}
+
+impl Directory {
+    /// A directory with up to five of the `files` still to place and some subdirectories
+    fn generate(name: String, files: &mut usize, largest: i64, random: &mut Random) -> Directory {
+        let count = (random.range(0..=5) as usize).min(*files);
+        *files -= count;
+        let mut directory = Directory {
+            name,
+            files: (0..count)
+                .map(|_| (random.range(1..=largest), file_name(random)))
+                .collect(),
+            directories: vec![],
+        };
+        for _ in 0..random.range(0..=3) {
+            if *files == 0 {
+                break;
+            }
+            directory.add_directory(files, largest, random);
+        }
+        directory
+    }
+
+    fn add_directory(&mut self, files: &mut usize, largest: i64, random: &mut Random) {
+        let name = loop {
+            let name = name(random);
+            if self
+                .directories
+                .iter()
+                .all(|directory| directory.name != name)
+            {
+                break name;
+            }
+        };
+        let directory = Directory::generate(name, files, largest, random);
+        self.directories.push(directory);
+    }
+
+    fn explore(&self, session: &mut Vec<String>, random: &mut Random) {
+        session.push(format!("$ cd {}", self.name));
+        session.push("$ ls".to_string());
+        let mut entries: Vec<String> = self
+            .directories
+            .iter()
+            .map(|directory| format!("dir {}", directory.name))
+            .chain(
+                self.files
+                    .iter()
+                    .map(|(size, name)| format!("{} {}", size, name)),
+            )
+            .collect();
+        random.shuffle(&mut entries);
+        session.extend(entries);
+        for directory in &self.directories {
+            directory.explore(session, random);
+            session.push("$ cd ..".to_string());
+        }
+    }
+}
+
+fn name(random: &mut Random) -> String {
+    (0..random.range(1..=8))
+        .map(|_| char::from(b'a' + random.range(0..=25) as u8))
+        .collect()
+}
+
+fn file_name(random: &mut Random) -> String {
+    match random.chance(0.5) {
+        true => format!(
+            "{}.{}",
+            name(random),
+            random.pick(&["txt", "dat", "lst", "log"])
+        ),
+        false => name(random),
+    }
+}
+
+/// A terminal session exploring `size` files in nested directories
+fn generate(size: usize, random: &mut Random) -> String {
+    // the sizes of all files together still fit into an u32
+    let largest = (4_000_000_000 / size.max(1) as i64).clamp(1, 300_000);
+    let mut files = size;
+    let mut root = Directory::generate("/".to_string(), &mut files, largest, random);
+    while files > 0 {
+        root.add_directory(&mut files, largest, random);
+    }
+    let mut session = vec![];
+    root.explore(&mut session, random);
+    session.join("\n") + "\n"
+}
+
+pub fn part1(input: &str) -> Result<u32> {
+    Ok(sum_of_small_dirs(&calculate(input)?))
+}
+
+pub fn part2(input: &str) -> Result<u32> {
+    Ok(dir_to_delete(&calculate(input)?).1)
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    fn day(&self) -> u32 {
+        7
+    }
+    fn title(&self) -> &'static str {
+        "No Space Left On Device"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        calculate(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn parse_command() {
-        run();
+        let dir_map = calculate(&read_file("input/day07-test.txt").unwrap()).unwrap();
+        assert_eq!(48381165, dir_map["/"]);
+        assert_eq!(584, dir_map["//a/e"]);
+    }
+
+    #[test]
+    fn parts_work() {
+        let input = read_file("input/day07-test.txt").unwrap();
+        assert_eq!(95437, part1(&input).unwrap());
+        assert_eq!(24933642, part2(&input).unwrap());
+    }
+
+    #[test]
+    fn unknown_commands_are_reported() {
+        let error = calculate("$ cd /\n$ ls\n10 a\n$ rm a\n").unwrap_err();
+        assert_eq!("4:3: unknown command `rm a`", error.to_string());
     }
 }
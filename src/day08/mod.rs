@@ -1,7 +1,9 @@
+use crate::animation::{Frame, Palette, View};
 use crate::day08::Direction::{East, North, South, West};
-use crate::read_file;
-use ansi_term::Colour::Yellow;
-use std::cmp::max;
+use crate::random::Random;
+use crate::{Answer, Grid, Point, Result, Solution};
+use ansi_term::Colour::{Green, Yellow};
+use ansi_term::Style;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Tree {
@@ -12,7 +14,7 @@ pub struct Tree {
     height: i32,
 }
 
-type Forest = Vec<Vec<Tree>>;
+type Forest = Grid<Tree>;
 
 enum Direction {
     North,
@@ -35,46 +37,27 @@ impl Tree {
     }
 }
 
-fn parse_input(input: String) -> Forest {
-    let mut rows: Forest = Vec::new();
-    for line in input.lines() {
-        let mut cols: Vec<Tree> = Vec::new();
-        for item in line.chars() {
-            cols.push(Tree {
-                north: false,
-                east: false,
-                south: false,
-                west: false,
-                height: item.to_digit(10).unwrap() as i32,
-            })
-        }
-        rows.push(cols);
-    }
-    rows
-}
-
-fn scan_horizontal(forest: &mut Forest, y_range: Vec<usize>, x_range: Vec<usize>, dir: Direction) {
-    for y in &y_range {
-        let mut act_height: i32 = -1;
-        for x in &x_range {
-            if forest[*y][*x].height > act_height {
-                forest[*y][*x].visible_from_dir(&dir);
-                act_height = forest[*y][*x].height;
-                if act_height == 9 {
-                    break;
-                }
-            }
-        }
-    }
+fn parse_input(input: &str) -> Result<Forest> {
+    Grid::parse(input, "expected a tree height", |item| {
+        item.to_digit(10).map(|height| Tree {
+            north: false,
+            east: false,
+            south: false,
+            west: false,
+            height: height as i32,
+        })
+    })
 }
 
-fn scan_vertical(forest: &mut Forest, y_range: Vec<usize>, x_range: Vec<usize>, dir: Direction) {
-    for x in &x_range {
+/// Walks along every line of sight, marking the trees which can be seen from its start
+fn scan(forest: &mut Forest, lines: Vec<Vec<Point<i64>>>, dir: Direction) {
+    for line in lines {
         let mut act_height: i32 = -1;
-        for y in &y_range {
-            if forest[*y][*x].height > act_height {
-                forest[*y][*x].visible_from_dir(&dir);
-                act_height = forest[*y][*x].height;
+        for point in line {
+            let tree = &mut forest[point];
+            if tree.height > act_height {
+                tree.visible_from_dir(&dir);
+                act_height = tree.height;
                 if act_height == 9 {
                     break;
                 }
@@ -84,141 +67,152 @@ fn scan_vertical(forest: &mut Forest, y_range: Vec<usize>, x_range: Vec<usize>,
 }
 
 fn scan_forest(forest: &mut Forest) -> &mut Forest {
-    let height = forest.len();
-    let width = forest[0].len();
+    let height = forest.height() as i64;
+    let width = forest.width() as i64;
+    let rows = |xs: Vec<i64>| -> Vec<Vec<Point<i64>>> {
+        (0..height)
+            .map(|y| xs.iter().map(|x| Point::new(*x, y)).collect())
+            .collect()
+    };
+    let columns = |ys: Vec<i64>| -> Vec<Vec<Point<i64>>> {
+        (0..width)
+            .map(|x| ys.iter().map(|y| Point::new(x, *y)).collect())
+            .collect()
+    };
 
-    scan_horizontal(
-        forest,
-        (0..height).collect::<Vec<_>>(),
-        (0..width).collect::<Vec<_>>(),
-        East,
-    );
-    scan_horizontal(
-        forest,
-        (0..height).collect::<Vec<_>>(),
-        (0..width).rev().collect::<Vec<_>>(),
-        West,
-    );
-    scan_vertical(
-        forest,
-        (0..height).collect::<Vec<_>>(),
-        (0..width).collect::<Vec<_>>(),
-        South,
-    );
-    scan_vertical(
-        forest,
-        (0..height).rev().collect::<Vec<_>>(),
-        (0..width).collect::<Vec<_>>(),
-        North,
-    );
+    scan(forest, rows((0..width).collect()), East);
+    scan(forest, rows((0..width).rev().collect()), West);
+    scan(forest, columns((0..height).collect()), South);
+    scan(forest, columns((0..height).rev().collect()), North);
 
     forest
 }
 
-pub fn input_forest(filename: &str) -> Forest {
-    let input = read_file(filename);
-    let mut forest = parse_input(input);
+pub fn input_forest(input: &str) -> Result<Forest> {
+    let mut forest = parse_input(input)?;
     scan_forest(&mut forest);
 
-    forest
+    Ok(forest)
 }
 
-pub fn count_visibles(forest: &mut Forest) -> i32 {
-    let mut count = 0;
-    for trees in forest {
-        for tree in trees {
-            count += i32::from(tree.is_visible());
-        }
-    }
-
-    count
+pub fn count_visibles(forest: &Forest) -> i32 {
+    forest.iter().filter(|tree| tree.is_visible()).count() as i32
 }
 
 pub fn max_scenic_score(forest: &Forest) -> i32 {
-    let height = forest.len();
-    let width = forest[0].len();
-    let mut scenic_score = 0;
-
-    for y in 0..height {
-        for x in 0..width {
-            scenic_score = max(scenic_score, calc_scenic_score(forest, y, x));
-        }
-    }
-    scenic_score
+    forest
+        .positions()
+        .map(|point| scenic_score(forest, point))
+        .max()
+        .unwrap_or(0)
 }
 
-fn calc_scenic_score(forest: &Forest, y: usize, x: usize) -> i32 {
-    let height = forest.len();
-    let width = forest[0].len();
+fn scenic_score(forest: &Forest, point: Point<i64>) -> i32 {
+    [(1, 0), (-1, 0), (0, 1), (0, -1)]
+        .iter()
+        .map(|direction| viewing_distance(forest, point, *direction))
+        .product()
+}
 
-    let tree_height = forest[y][x].height;
-    let mut d1 = 0;
-    let mut d2 = 0;
-    let mut d3 = 0;
-    let mut d4 = 0;
+/// Counts the trees which can be seen from `from` looking into `(dx, dy)`
+fn viewing_distance(forest: &Forest, from: Point<i64>, (dx, dy): (i64, i64)) -> i32 {
+    let tree_height = forest[from].height;
+    let mut distance = 0;
+    let mut point = Point::new(from.x + dx, from.y + dy);
 
-    if x < width - 1 {
-        for x1 in (x + 1)..width {
-            d1 += 1;
-            if tree_height <= forest[y][x1].height {
-                break;
-            }
-        }
-    }
-    if x > 0 {
-        for x1 in 0..x {
-            d2 += 1;
-            if tree_height <= forest[y][x - 1 - x1].height {
-                break;
-            }
+    while let Some(tree) = forest.get(point) {
+        distance += 1;
+        if tree_height <= tree.height {
+            break;
         }
+        point = Point::new(point.x + dx, point.y + dy);
     }
+    distance
+}
 
-    if y < height - 1 {
-        for y1 in (y + 1)..height as usize {
-            d3 += 1;
-            if tree_height <= forest[y1][x].height {
-                break;
-            }
-        }
-    }
-    if y > 0 {
-        for y1 in 0..y {
-            d4 += 1;
-            if tree_height <= forest[y - 1 - y1][x].height {
-                break;
-            }
-        }
+/// The heights of the trees which can be seen from outside, the hidden ones as `.` and the tree
+/// with the best scenic score as `*`
+fn view(input: &str) -> Result<View> {
+    let forest = input_forest(input)?;
+    let mut grid = forest.map(|tree| match tree.is_visible() {
+        true => char::from_digit(tree.height as u32, 10).unwrap_or('?'),
+        false => '.',
+    });
+    let mut caption = format!("{} trees can be seen from outside", count_visibles(&forest));
+    if let Some(best) = forest
+        .positions()
+        .max_by_key(|point| scenic_score(&forest, *point))
+    {
+        grid[best] = '*';
+        caption += &format!(
+            ", the best scenic score is {} at {}",
+            scenic_score(&forest, best),
+            best
+        );
     }
-    d1 * d2 * d3 * d4
+    let palette = ('0'..='9')
+        .fold(Palette::new(), |palette, digit| {
+            palette.with(digit, Green.normal())
+        })
+        .with('.', Style::new().dimmed())
+        .with('*', Yellow.bold());
+    Ok(View::new(Frame::new(grid).with_caption(&caption), palette))
 }
 
-pub fn run() {
-    let mut forest = input_forest("input/day08.txt");
+/// A forest of `size` by `size` trees
+fn generate(size: usize, random: &mut Random) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let row: String = (0..size.max(1))
+                .map(|_| char::from(b'0' + random.range(0..=9) as u8))
+                .collect();
+            row + "\n"
+        })
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<i32> {
+    Ok(count_visibles(&input_forest(input)?))
+}
 
-    let count = count_visibles(&mut forest);
-    println!(
-        "There are {} trees visible",
-        Yellow.bold().paint(format!("{}", count))
-    );
+pub fn part2(input: &str) -> Result<i32> {
+    Ok(max_scenic_score(&input_forest(input)?))
 }
 
-pub fn run2() {
-    let forest = input_forest("input/day08.txt");
-    let score = max_scenic_score(&forest);
-    println!(
-        "The highest scenic scoring tree scores {}",
-        Yellow.bold().paint(format!("{}", score))
-    );
+pub struct Day08;
+
+impl Solution for Day08 {
+    fn day(&self) -> u32 {
+        8
+    }
+    fn title(&self) -> &'static str {
+        "Treetop Tree House"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+    fn view(&self, input: &str) -> Result<View> {
+        view(input)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn parse_input_works() {
-        let forest = input_forest("input/day08-test.txt");
+        let forest = input_forest(&read_file("input/day08-test.txt").unwrap()).unwrap();
 
         assert_eq!(
             Tree {
@@ -228,7 +222,7 @@ mod test {
                 south: false,
                 height: 3
             },
-            forest[0][0]
+            forest[Point::new(0, 0)]
         );
         assert_eq!(
             Tree {
@@ -238,7 +232,7 @@ mod test {
                 south: false,
                 height: 5
             },
-            forest[1][1]
+            forest[Point::new(1, 1)]
         );
         assert_eq!(
             Tree {
@@ -248,7 +242,7 @@ mod test {
                 south: false,
                 height: 5
             },
-            forest[1][2]
+            forest[Point::new(2, 1)]
         );
         assert_eq!(
             Tree {
@@ -258,7 +252,7 @@ mod test {
                 south: false,
                 height: 1
             },
-            forest[1][3]
+            forest[Point::new(3, 1)]
         );
         assert_eq!(
             Tree {
@@ -268,7 +262,7 @@ mod test {
                 south: false,
                 height: 5
             },
-            forest[2][1]
+            forest[Point::new(1, 2)]
         );
         assert_eq!(
             Tree {
@@ -278,7 +272,7 @@ mod test {
                 south: false,
                 height: 3
             },
-            forest[2][2]
+            forest[Point::new(2, 2)]
         );
         assert_eq!(
             Tree {
@@ -288,24 +282,45 @@ mod test {
                 south: false,
                 height: 3
             },
-            forest[2][3]
+            forest[Point::new(3, 2)]
         );
     }
 
+    #[test]
+    fn broken_forest_is_reported() {
+        let error = input_forest("123\n4a6\n").unwrap_err();
+        assert_eq!("2:2: expected a tree height `a`", error.to_string());
+        let error = input_forest("123\n45\n").unwrap_err();
+        assert_eq!("2:1: rows differ in length `45`", error.to_string());
+    }
+
     #[test]
     fn summing_workd() {
-        let mut forest = input_forest("input/day08-test.txt");
+        let forest = input_forest(&read_file("input/day08-test.txt").unwrap()).unwrap();
 
-        let count = count_visibles(&mut forest);
+        let count = count_visibles(&forest);
         assert_eq!(21, count);
     }
 
     #[test]
     fn scenic_score_is_correct() {
-        let mut forest = input_forest("input/day08-test.txt");
+        let forest = input_forest(&read_file("input/day08-test.txt").unwrap()).unwrap();
 
-        assert_eq!(4, calc_scenic_score(&mut forest, 1, 2));
-        assert_eq!(8, calc_scenic_score(&mut forest, 3, 2));
+        assert_eq!(4, scenic_score(&forest, Point::new(2, 1)));
+        assert_eq!(8, scenic_score(&forest, Point::new(2, 3)));
         assert_eq!(8, max_scenic_score(&forest));
     }
+
+    #[test]
+    fn visible_trees_are_viewed() {
+        let view = view(&read_file("input/day08-test.txt").unwrap()).unwrap();
+        assert_eq!(
+            "30373\n255.2\n65.32\n3.*.9\n35390",
+            view.frame.grid.to_string()
+        );
+        assert_eq!(
+            "21 trees can be seen from outside, the best scenic score is 8 at 2/3",
+            view.frame.caption
+        );
+    }
 }
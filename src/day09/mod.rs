@@ -1,8 +1,11 @@
+use crate::animation::{Animation, Frame, Palette};
 use crate::day09::Direction::{D, L, R, U};
-use crate::{read_file, Point};
-use ansi_term::Colour::Yellow;
-use std::collections::HashMap;
-use std::process::exit;
+use crate::error::{parse_number, Context};
+use crate::random::Random;
+use crate::svg::Svg;
+use crate::{Answer, Error, Point, Result, Solution};
+use ansi_term::Colour::{Blue, Green, Red, Yellow};
+use std::collections::HashSet;
 
 #[derive(Debug, PartialEq)]
 enum Direction {
@@ -18,30 +21,32 @@ struct Command {
     amount: u32,
 }
 
-impl Point<i32> {
-    pub fn chase(&mut self, target: Point<i32>) {
-        if (target.x - self.x).abs() > 1 || (target.y - self.y).abs() > 1 {
-            if target.x < self.x {
-                self.x -= 1;
-            }
-            if target.x > self.x {
-                self.x += 1;
-            }
-            if target.y > self.y {
-                self.y += 1;
-            }
-            if target.y < self.y {
-                self.y -= 1;
-            }
+impl Direction {
+    fn step(&self) -> Point<i32> {
+        match self {
+            U => Point::UP,
+            R => Point::RIGHT,
+            D => Point::DOWN,
+            L => Point::LEFT,
         }
     }
 }
 
-fn parse_input(input: String) -> Vec<Command> {
+/// Moves `knot` one step towards `target` unless they're still touching
+fn chase(knot: Point<i32>, target: Point<i32>) -> Point<i32> {
+    match knot.chebyshev(&target) > 1 {
+        true => knot + (target - knot).signum(),
+        false => knot,
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<Command>> {
     let mut commands: Vec<Command> = vec![];
 
     for line in input.lines() {
-        let (command, amount) = line.split_once(' ').unwrap();
+        let (command, amount) = line
+            .split_once(' ')
+            .ok_or_else(|| Error::parse(line, "expected a move like `R 4`").within(input, line))?;
         commands.push(Command {
             dir: match command {
                 "U" => U,
@@ -49,113 +54,207 @@ fn parse_input(input: String) -> Vec<Command> {
                 "L" => L,
                 "R" => R,
                 _ => {
-                    println!("Unknown Command");
-                    exit(1);
+                    return Err(Error::parse(command, "unknown direction").within(input, command));
                 }
             },
-            amount: amount.parse().unwrap(),
+            amount: parse_number(amount).within(input, amount)?,
         })
     }
 
-    commands
+    Ok(commands)
 }
 
-fn walk(input: String) -> usize {
-    let commands: Vec<Command> = parse_input(input);
-
-    let mut positions: HashMap<String, bool> = HashMap::new();
-
-    let mut head: Point<i32> = Point { x: 0, y: 0 };
-    let mut tail: Point<i32> = Point { x: 0, y: 0 };
-
-    positions.entry(tail.to_string()).or_insert(true);
+/// Pulls a rope of `knots` knots along the commands and counts the positions its tail visits.
+/// `observe` sees the rope, head first, and the visited positions after every step.
+fn pull_rope(
+    commands: &[Command],
+    knots: usize,
+    mut observe: impl FnMut(&[Point<i32>], &HashSet<Point<i32>>) -> Result<()>,
+) -> Result<usize> {
+    let mut rope = vec![Point::ORIGIN; knots];
+    let mut positions: HashSet<Point<i32>> = HashSet::from([Point::ORIGIN]);
 
     for command in commands {
         for _step in 0..command.amount {
-            match command.dir {
-                U => head.y -= 1,
-                R => head.x += 1,
-                D => head.y += 1,
-                L => head.x -= 1,
+            rope[0] += command.dir.step();
+            for i in 1..knots {
+                rope[i] = chase(rope[i], rope[i - 1]);
             }
-            tail.chase(head);
-            positions.entry(tail.to_string()).or_insert(true);
+            positions.insert(rope[knots - 1]);
+            observe(&rope, &positions)?;
         }
     }
 
-    positions.len()
+    Ok(positions.len())
 }
 
-fn walk10(input: String) -> usize {
-    let commands: Vec<Command> = parse_input(input);
+fn walk(input: &str) -> Result<usize> {
+    pull_rope(&parse_input(input)?, 2, |_, _| Ok(()))
+}
 
-    let mut positions: HashMap<String, bool> = HashMap::new();
-    let mut rope = [Point { x: 0_i32, y: 0_i32 }; 10];
+fn walk10(input: &str) -> Result<usize> {
+    pull_rope(&parse_input(input)?, 10, |_, _| Ok(()))
+}
 
-    positions.entry(rope[9].to_string()).or_insert(true);
+/// The visited positions as `#`, the start as `s` and the knots as `H`, `1`, `2`, … up to `9`
+fn draw_rope(rope: &[Point<i32>], positions: &HashSet<Point<i32>>) -> Frame {
+    let visited = positions.iter().map(|position| (*position, '#'));
+    let knots = rope
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, knot)| match index {
+            0 => (*knot, 'H'),
+            _ => (*knot, char::from_digit(index as u32, 10).unwrap_or('T')),
+        });
+    let points = visited
+        .chain([(Point::ORIGIN, 's')])
+        .chain(knots)
+        .map(|(point, cell)| (Point::from(point), cell));
+    Frame::from_points(points, '.').with_focus(rope[0].into())
+}
 
-    for command in commands {
-        for _step in 0..command.amount {
-            match command.dir {
-                U => rope[0].y -= 1,
-                R => rope[0].x += 1,
-                D => rope[0].y += 1,
-                L => rope[0].x -= 1,
-            }
-            for i in 1..10 {
-                rope[i].chase(rope[i - 1]);
-            }
-            positions.entry(rope[9].to_string()).or_insert(true);
+fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let commands = parse_input(input)?;
+    let palette = Palette::new()
+        .with('.', Blue.dimmed())
+        .with('#', Blue.normal())
+        .with('s', Green.bold())
+        .with('H', Red.bold());
+    animation.set_palette(
+        ('1'..='9').fold(palette, |palette, knot| palette.with(knot, Yellow.normal())),
+    );
+    let mut steps = 0;
+    pull_rope(&commands, 10, |rope, positions| {
+        steps += 1;
+        let caption = format!(
+            "step {}, the tail visited {} positions",
+            steps,
+            positions.len()
+        );
+        animation.show(&draw_rope(rope, positions).with_caption(&caption))
+    })?;
+    Ok(())
+}
+
+/// The trails of the head and the tail of the long rope
+fn draw(input: &str) -> Result<Svg> {
+    let commands = parse_input(input)?;
+    let mut head = vec![Point::ORIGIN];
+    let mut tail = vec![Point::ORIGIN];
+    pull_rope(&commands, 10, |rope, _| {
+        head.push(rope[0]);
+        if tail.last() != rope.last() {
+            tail.extend(rope.last());
         }
+        Ok(())
+    })?;
+    let mut svg = Svg::new("Rope Bridge");
+    svg.polyline(head.iter().copied(), "#9ab")
+        .polyline(tail.iter().copied(), "#c22")
+        .marker(Point::<i32>::ORIGIN, "s", "green");
+    if let (Some(head), Some(tail)) = (head.last(), tail.last()) {
+        svg.marker(*head, "H", "#357").marker(*tail, "9", "#c22");
     }
+    Ok(svg)
+}
 
-    positions.len()
+/// `size` moves of the head
+fn generate(size: usize, random: &mut Random) -> String {
+    (0..size)
+        .map(|_| {
+            let direction = random.pick(&['U', 'R', 'D', 'L']);
+            format!("{} {}\n", direction, random.range(1..=20))
+        })
+        .collect()
 }
 
-pub fn run() {
-    let input = read_file("input/day09.txt");
-    let count = walk(input);
+pub fn part1(input: &str) -> Result<usize> {
+    walk(input)
+}
 
-    println!(
-        "The Tail visits {} positions.",
-        Yellow.bold().paint(format!("{}", count))
-    );
+pub fn part2(input: &str) -> Result<usize> {
+    walk10(input)
 }
 
-pub fn run2() {
-    let input = read_file("input/day09.txt");
-    let count = walk10(input);
+pub struct Day09;
 
-    println!(
-        "The 10-Foot-Rope-Tail visits {} positions.",
-        Yellow.bold().paint(format!("{}", count))
-    );
+impl Solution for Day09 {
+    fn day(&self) -> u32 {
+        9
+    }
+    fn title(&self) -> &'static str {
+        "Rope Bridge"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+    fn animate(&self, input: &str, animation: &mut Animation) -> Result<()> {
+        animate(input, animation)
+    }
+    fn draw(&self, input: &str) -> Result<Svg> {
+        draw(input)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::read_file;
 
     #[test]
     pub fn parse_test() {
-        let input = read_file("input/day09-test.txt");
-        let commands: Vec<Command> = parse_input(input);
+        let input = read_file("input/day09-test.txt").unwrap();
+        let commands: Vec<Command> = parse_input(&input).unwrap();
         assert_eq!(Command { dir: R, amount: 4 }, commands[0]);
         assert_eq!(Command { dir: U, amount: 4 }, commands[1]);
         assert_eq!(Command { dir: L, amount: 3 }, commands[2]);
         assert_eq!(Command { dir: D, amount: 1 }, commands[3]);
     }
 
+    #[test]
+    pub fn unknown_direction_is_reported() {
+        let error = parse_input("R 4\nX 2\n").unwrap_err();
+        assert_eq!("2:1: unknown direction `X`", error.to_string());
+    }
+
+    #[test]
+    pub fn trails_are_drawn() {
+        let svg = draw(&read_file("input/day09-test.txt").unwrap()).unwrap();
+        let text = svg.to_string();
+        assert_eq!(2, text.matches("<polyline").count());
+        // the head ends at (2,-2), the tail never leaves the start
+        assert!(text.contains(r##"<polyline points="0,0" fill="none" stroke="#c22""##));
+        assert!(text.contains(">H</text>"));
+    }
+
+    #[test]
+    pub fn knots_follow() {
+        let tail = Point::new(0, 0);
+        assert_eq!(tail, chase(tail, Point::new(1, 1)));
+        assert_eq!(Point::new(1, 0), chase(tail, Point::new(2, 0)));
+        assert_eq!(Point::new(1, -1), chase(tail, Point::new(2, -1)));
+    }
+
     #[test]
     pub fn walker_test() {
-        let input = read_file("input/day09-test.txt");
-        assert_eq!(13, walk(input));
+        let input = read_file("input/day09-test.txt").unwrap();
+        assert_eq!(13, walk(&input).unwrap());
     }
 
     #[test]
     pub fn walker2_test() {
-        let input = read_file("input/day09-test.txt");
-        assert_eq!(1, walk10(input));
+        let input = read_file("input/day09-test.txt").unwrap();
+        assert_eq!(1, walk10(&input).unwrap());
 
         let input = "R 5
 U 8
@@ -165,6 +264,15 @@ R 17
 D 10
 L 25
 U 20";
-        assert_eq!(36, walk10(input.to_string()));
+        assert_eq!(36, walk10(input).unwrap());
+    }
+
+    #[test]
+    pub fn rope_is_drawn() {
+        let rope = [Point::new(2, -1), Point::new(1, 0), Point::new(1, 0)];
+        let positions = HashSet::from([Point::ORIGIN, Point::new(1, 0)]);
+        let frame = draw_rope(&rope, &positions);
+        assert_eq!("..H\ns1.", frame.grid.to_string());
+        assert_eq!(Some(Point::new(2, -1)), frame.focus);
     }
 }
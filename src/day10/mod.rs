@@ -1,7 +1,10 @@
+use crate::animation::{Animation, Frame, Palette, View};
 use crate::day10::Op::{AddX, Noop};
-use crate::read_file;
-use ansi_term::Colour;
-use ansi_term::Colour::{Blue, Yellow};
+use crate::error::{parse_number, Context};
+use crate::random::Random;
+use crate::{Answer, Error, Grid, Point, Result, Solution};
+use ansi_term::Colour::Green;
+use ansi_term::Style;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Op {
@@ -11,28 +14,42 @@ pub enum Op {
 
 type Program = Vec<Op>;
 
-type Screen = [[char; 40]; 6];
-
-fn parse_line(input: &str) -> Option<Op> {
-    match input[0..4].as_ref() {
-        "noop" => Some(Noop),
-        _ => {
-            let (command, param) = input.split_once(' ').unwrap();
-            match command {
-                "addx" => Some(AddX(param.parse().unwrap())),
-                _ => {
-                    println!("No such command: {}", command);
-                    None
-                }
-            }
-        }
+type Screen = Grid<char>;
+
+/// The 4x6 glyphs the CRT draws its capital letters with, row by row
+const LETTERS: [(char, [&str; 6]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+fn parse_line(input: &str) -> Result<Op> {
+    match input.split_once(' ') {
+        None if input == "noop" => Ok(Noop),
+        Some(("addx", param)) => Ok(AddX(parse_number(param).within(input, param)?)),
+        _ => Err(Error::parse(input, "no such command")),
     }
 }
 
-fn parse_program(input: String) -> Program {
+fn parse_program(input: &str) -> Result<Program> {
     let mut token_stream: Program = vec![];
     for line in input.lines() {
-        let op = parse_line(line).unwrap();
+        let op = parse_line(line).within(input, line)?;
         match op {
             Noop => token_stream.push(Noop),
             AddX(_) => {
@@ -41,7 +58,7 @@ fn parse_program(input: String) -> Program {
             }
         }
     }
-    token_stream
+    Ok(token_stream)
 }
 
 pub fn execute_program(program: Program) -> i32 {
@@ -68,19 +85,25 @@ pub fn execute_program(program: Program) -> i32 {
 }
 
 pub fn draw_screen(program: Program) -> Screen {
-    let mut crt: Screen = [['.'; 40]; 6];
+    scan(&program, |_, _, _| Ok(())).expect("drawing alone doesn't fail")
+}
 
-    let mut x: i32 = 1;
+/// Runs the program while the CRT draws its pixels. `observe` sees the screen after every cycle
+/// along with the pixel just drawn and the sprite position.
+fn scan(
+    program: &Program,
+    mut observe: impl FnMut(&Screen, Point<i64>, i32) -> Result<()>,
+) -> Result<Screen> {
+    let mut crt: Screen = Grid::new(40, 6, '.');
 
-    for (i, item) in program.iter().enumerate() {
-        let screen_x = i % 40;
-        let screen_y = (i - screen_x) / 40;
+    let pixels: Vec<Point<i64>> = crt.positions().collect();
+    let mut x: i32 = 1;
 
-        if (x - screen_x as i32).abs() < 2 {
-            crt[screen_y][screen_x] = '#';
-        } else {
-            crt[screen_y][screen_x] = '.';
+    for (pixel, item) in pixels.into_iter().zip(program) {
+        if (x as i64 - pixel.x).abs() < 2 {
+            crt[pixel] = '#';
         }
+        observe(&crt, pixel, x)?;
 
         // after cycle
         match item {
@@ -90,42 +113,120 @@ pub fn draw_screen(program: Program) -> Screen {
             Noop => {}
         }
     }
-    crt
+    Ok(crt)
+}
+
+/// Lit and dark pixels
+fn palette() -> Palette {
+    Palette::new()
+        .with('#', Green.bold())
+        .with('.', Style::new().dimmed())
 }
 
-pub fn run() {
-    let program = parse_program(read_file("input/day10.txt"));
-    println!(
-        "Sum of signal strengths is {}",
-        Yellow.bold().paint(execute_program(program).to_string())
-    );
+fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let program = parse_program(input)?;
+    animation.set_palette(palette());
+    let mut cycle = 0;
+    scan(&program, |screen, pixel, x| {
+        cycle += 1;
+        let caption = format!("cycle {:3}, drawing {}, sprite at {}", cycle, pixel, x);
+        animation.show(&Frame::new(screen.clone()).with_caption(&caption))
+    })?;
+    Ok(())
 }
 
-pub fn run2() {
-    let program = parse_program(read_file("input/day10.txt"));
-    let screen = draw_screen(program);
+/// The screen once the program ran, along with the letters read off it
+fn view(input: &str) -> Result<View> {
+    let screen = draw_screen(parse_program(input)?);
+    let caption = format!("the screen shows {}", read_letters(&screen));
+    Ok(View::new(
+        Frame::new(screen).with_caption(&caption),
+        palette(),
+    ))
+}
+
+/// Reads the eight letters off the screen, unknown glyphs become '?'
+pub fn read_letters(screen: &Screen) -> String {
+    (0..8)
+        .map(|letter| {
+            let glyph: Vec<String> = screen
+                .rows()
+                .map(|scan_line| scan_line[letter * 5..letter * 5 + 4].iter().collect())
+                .collect();
+            LETTERS
+                .iter()
+                .find(|(_, pattern)| pattern.iter().zip(&glyph).all(|(a, b)| a == b))
+                .map_or('?', |(letter, _)| *letter)
+        })
+        .collect()
+}
 
-    println!("Screen output of program:");
-    for scan_line in screen {
-        for pixel in scan_line {
-            match pixel {
-                '#' => print!("{}", Yellow.bold().on(Colour::Yellow).paint(" ")),
-                _ => print!("{}", Blue.bold().on(Colour::Blue).paint(" ")),
+/// A program of `size` instructions which keeps the sprite on the screen
+fn generate(size: usize, random: &mut Random) -> String {
+    let mut x = 1;
+    (0..size)
+        .map(|_| match random.chance(0.4) {
+            true => "noop\n".to_string(),
+            false => {
+                let value = random.range((-1 - x).max(-10)..=(40 - x).min(10));
+                x += value;
+                format!("addx {}\n", value)
             }
-        }
-        println!();
+        })
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<i32> {
+    Ok(execute_program(parse_program(input)?))
+}
+
+pub fn part2(input: &str) -> Result<String> {
+    Ok(read_letters(&draw_screen(parse_program(input)?)))
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    fn day(&self) -> u32 {
+        10
+    }
+    fn title(&self) -> &'static str {
+        "Cathode-Ray Tube"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_program(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+    fn animate(&self, input: &str, animation: &mut Animation) -> Result<()> {
+        animate(input, animation)
+    }
+    fn view(&self, input: &str) -> Result<View> {
+        view(input)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn parse_input_works() {
         assert_eq!(AddX(3), parse_line("addx 3").unwrap());
         assert_eq!(AddX(-5), parse_line("addx -5").unwrap());
         assert_eq!(Noop, parse_line("noop").unwrap());
+        assert_eq!(
+            "2:1: no such command `jmp 4`",
+            parse_program("noop\njmp 4").unwrap_err().to_string()
+        );
     }
 
     #[test]
@@ -136,8 +237,8 @@ mod test {
                 "noop
 addx 3
 addx -5"
-                    .to_string()
             )
+            .unwrap()
         );
     }
 
@@ -146,16 +247,60 @@ addx -5"
         let program = parse_program(
             "noop
 addx 3
-addx -5"
-                .to_string(),
-        );
+addx -5",
+        )
+        .unwrap();
 
         assert_eq!(0, execute_program(program));
     }
 
     #[test]
     fn calculation_works() {
-        let program = parse_program(read_file("input/day10-test.txt"));
+        let program = parse_program(&read_file("input/day10-test.txt").unwrap()).unwrap();
         assert_eq!(13140, execute_program(program));
     }
+
+    #[test]
+    fn drawing_works() {
+        let screen =
+            draw_screen(parse_program(&read_file("input/day10-test.txt").unwrap()).unwrap());
+        assert_eq!(
+            "##..##..##..##..##..##..##..##..##..##..",
+            screen.row(0).iter().collect::<String>()
+        );
+        assert_eq!(
+            "#######.......#######.......#######.....",
+            screen.row(5).iter().collect::<String>()
+        );
+    }
+
+    #[test]
+    fn reading_letters_works() {
+        let mut screen: Screen = Grid::new(40, 6, '.');
+        for (letter, (_, glyph)) in LETTERS.iter().take(8).enumerate() {
+            for (y, row) in glyph.iter().enumerate() {
+                for (x, pixel) in row.chars().enumerate() {
+                    screen[Point::new((letter * 5 + x) as i64, y as i64)] = pixel;
+                }
+            }
+        }
+        assert_eq!("ABCEFGHI", read_letters(&screen));
+
+        screen[Point::new(0, 1)] = '.';
+        assert_eq!("?BCEFGHI", read_letters(&screen));
+    }
+
+    #[test]
+    fn every_cycle_is_animated() {
+        let mut animation = Animation::sink();
+        animate(&read_file("input/day10-test.txt").unwrap(), &mut animation).unwrap();
+        assert_eq!(240, animation.frames());
+    }
+
+    #[test]
+    fn the_screen_is_viewed() {
+        let view = view(&read_file("input/day10-test.txt").unwrap()).unwrap();
+        assert_eq!((40, 6), (view.frame.grid.width(), view.frame.grid.height()));
+        assert_eq!("the screen shows ????????", view.frame.caption);
+    }
 }
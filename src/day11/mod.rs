@@ -1,7 +1,10 @@
+use crate::cycle::Detector;
 use crate::day11::Operation::{Add, Mul};
 use crate::day11::Parameter::{Constant, Old};
-use crate::read_file;
-use regex::Regex;
+use crate::error::{parse_number, Context};
+use crate::parse::{fields, numbers, separated, split_blocks};
+use crate::random::Random;
+use crate::{Answer, Error, Result, Solution};
 
 #[derive(Debug, PartialEq, Clone, Eq)]
 pub enum Parameter {
@@ -33,49 +36,87 @@ pub struct Monkey {
 
 type MonkeyHorde = Vec<Monkey>;
 
-pub fn parse_op(input: &str) -> Operation {
+fn parse_parameter(input: &str) -> Result<Parameter> {
+    match input {
+        "old" => Ok(Old),
+        _ => Ok(Constant(parse_number(input)?)),
+    }
+}
+
+pub fn parse_op(input: &str) -> Result<Operation> {
     let parts: Vec<&str> = input.split_whitespace().collect();
-    let left = match parts[0] {
-        "old" => Old,
-        _ => Constant(parts[0].parse().unwrap()),
-    };
-    let right = match parts[2] {
-        "old" => Old,
-        _ => Constant(parts[2].parse().unwrap()),
-    };
+    if parts.len() != 3 {
+        return Err(Error::parse(input, "expected an operation like `old * 19`"));
+    }
+    let left = parse_parameter(parts[0]).within(input, parts[0])?;
+    let right = parse_parameter(parts[2]).within(input, parts[2])?;
     match parts[1] {
-        "*" => Mul(left, right),
-        "+" => Add(left, right),
-        _ => {
-            panic!("Unknown operation");
-        }
+        "*" => Ok(Mul(left, right)),
+        "+" => Ok(Add(left, right)),
+        _ => Err(Error::parse(parts[1], "unknown operation").within(input, parts[1])),
     }
 }
 
-pub fn parse_monkey(input: &str) -> Monkey {
-    let regex = Regex::new(r"^Monkey (\d+):\n\s+Starting items: ([^\n]+)\n\s+Operation: new = ([^\n]+)\n\s+Test: divisible by ([^\n]+)\n\s+If true: throw to monkey ([^\n]+)\n\s+If false: throw to monkey ([^\n]+)$").unwrap();
+/// The value in `line` of a monkey description, which has to match `template`
+fn field<'a>(input: &'a str, line: &'a str, template: &str) -> Result<&'a str> {
+    let [value] = fields(line, template).within(input, line)?;
+    Ok(value)
+}
+
+fn number_field(input: &str, line: &str, template: &str) -> Result<usize> {
+    let [value] = numbers(line, template).within(input, line)?;
+    Ok(value)
+}
 
-    let cap = regex.captures(input).unwrap();
+pub fn parse_monkey(input: &str) -> Result<Monkey> {
+    let lines: Vec<&str> = input.lines().map(str::trim_start).collect();
+    let [id, items, op, divisor, true_target, false_target] = lines[..] else {
+        let first_line = input.lines().next().unwrap_or(input);
+        return Err(Error::parse(
+            first_line,
+            "expected a monkey description of six lines",
+        ));
+    };
+    let items = field(input, items, "Starting items: {}")?;
+    let op = field(input, op, "Operation: new = {}")?;
 
-    Monkey {
-        id: cap[1].parse().unwrap(),
-        items: cap[2].split(", ").map(|c| c.parse().unwrap()).collect(),
-        op: parse_op(&cap[3]),
+    Ok(Monkey {
+        id: number_field(input, id, "Monkey {}:")?,
+        items: separated(items, ", ", parse_number).within(input, items)?,
+        op: parse_op(op).within(input, op)?,
         test: Test {
-            divisor: cap[4].parse().unwrap(),
-            true_target: cap[5].parse().unwrap(),
-            false_target: cap[6].parse().unwrap(),
+            divisor: number_field(input, divisor, "Test: divisible by {}")?,
+            true_target: number_field(input, true_target, "If true: throw to monkey {}")?,
+            false_target: number_field(input, false_target, "If false: throw to monkey {}")?,
         },
         inspected: 0,
-    }
+    })
 }
 
-fn parse_monkeys(input: String) -> MonkeyHorde {
+fn parse_monkeys(input: &str) -> Result<MonkeyHorde> {
     let mut monkey_horde: MonkeyHorde = vec![];
-    for line in input.split("\n\n") {
-        monkey_horde.push(parse_monkey(line));
+    for block in split_blocks(input) {
+        let monkey = parse_monkey(block).within(input, block)?;
+        if monkey.id != monkey_horde.len() {
+            return Err(
+                Error::parse(block, "monkeys have to be numbered in order").within(input, block)
+            );
+        }
+        monkey_horde.push(monkey);
     }
-    monkey_horde
+    let count = monkey_horde.len();
+    if count < 2 {
+        return Err(Error::invalid("At least two monkeys are needed"));
+    }
+    if monkey_horde
+        .iter()
+        .any(|monkey| monkey.test.true_target >= count || monkey.test.false_target >= count)
+    {
+        return Err(Error::invalid(
+            "A monkey throws to a monkey which doesn't exist",
+        ));
+    }
+    Ok(monkey_horde)
 }
 
 pub fn run_turns(monkeys: &mut MonkeyHorde, count: usize, worry_mul: usize) {
@@ -90,11 +131,11 @@ pub fn run_turns(monkeys: &mut MonkeyHorde, count: usize, worry_mul: usize) {
             let false_target = monkey.test.false_target;
             let true_target = monkey.test.true_target;
             monkeys[monkey.id].items = vec![];
-            monkeys[monkey.id].inspected += monkey.items.len() as usize;
+            monkeys[monkey.id].inspected += monkey.items.len();
             let op = &monkey.op;
             for item in monkey.items {
                 let score = perform_op(item, op, kgv) / worry_mul;
-                if score % monkey.test.divisor > 0 {
+                if !score.is_multiple_of(monkey.test.divisor) {
                     monkeys[false_target].items.push(score);
                 } else {
                     monkeys[true_target].items.push(score);
@@ -104,8 +145,46 @@ pub fn run_turns(monkeys: &mut MonkeyHorde, count: usize, worry_mul: usize) {
     }
 }
 
+/// Follows a single item on its own, which works as long as worries aren't divided: it doesn't
+/// matter which other items a monkey holds. Returns how often each monkey inspects the item in
+/// `rounds` rounds, skipping ahead once the item is back at the same monkey with the same worry
+/// level at the start of a round.
+fn follow_item(monkeys: &MonkeyHorde, holder: usize, worry: usize, rounds: usize) -> Vec<usize> {
+    let kgv: usize = monkeys.iter().map(|monkey| monkey.test.divisor).product();
+    let mut detector = Detector::new();
+    let mut history = vec![vec![0; monkeys.len()]];
+    let (mut holder, mut worry) = (holder, worry % kgv);
+
+    for round in 0..rounds {
+        if let Some(cycle) = detector.record((holder, worry)) {
+            return (0..monkeys.len())
+                .map(|id| cycle.extrapolate(|round| history[round][id] as i64, rounds) as usize)
+                .collect();
+        }
+        let mut inspected = history[round].clone();
+        // monkeys take their turns in order, so the item moves on in the same round when it's
+        // thrown to a monkey further back
+        loop {
+            let monkey = &monkeys[holder];
+            inspected[holder] += 1;
+            worry = perform_op(worry, &monkey.op, kgv);
+            let target = match worry.is_multiple_of(monkey.test.divisor) {
+                true => monkey.test.true_target,
+                false => monkey.test.false_target,
+            };
+            let next_round = target < holder;
+            holder = target;
+            if next_round {
+                break;
+            }
+        }
+        history.push(inspected);
+    }
+    history.pop().unwrap_or_default()
+}
+
 pub fn monkey_business_level(monkeys: &mut MonkeyHorde) -> usize {
-    monkeys.sort_by(|a, b| a.inspected.cmp(&b.inspected));
+    monkeys.sort_by_key(|a| a.inspected);
 
     monkeys.pop().unwrap().inspected * monkeys.pop().unwrap().inspected
 }
@@ -137,31 +216,94 @@ fn perform_op(value: usize, op: &Operation, kgv: usize) -> usize {
     }
 }
 
-pub fn run() {
-    let input = read_file("input/day11.txt");
-    let mut monkeys = parse_monkeys(input);
+/// Two to eight monkeys holding `size` items between them. Every monkey tests for another prime,
+/// so the worry levels stay small enough to be squared.
+fn generate(size: usize, random: &mut Random) -> String {
+    let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    random.shuffle(&mut primes);
+    let count = random.range(2..=8) as usize;
+    // every monkey starts with at least one item
+    let mut items: Vec<Vec<String>> = (0..count)
+        .map(|_| vec![random.range(50..=99).to_string()])
+        .collect();
+    for _ in count..size {
+        items[random.index(count)].push(random.range(50..=99).to_string());
+    }
+    let squaring = random.index(count);
+
+    let monkeys: Vec<String> = (0..count)
+        .map(|id| {
+            let operation = match (id == squaring, random.chance(0.5)) {
+                (true, _) => "old * old".to_string(),
+                (false, true) => format!("old * {}", random.range(2..=19)),
+                (false, false) => format!("old + {}", random.range(1..=8)),
+            };
+            // a monkey never throws to itself
+            let true_target = (id + 1 + random.index(count - 1)) % count;
+            let false_target = (id + 1 + random.index(count - 1)) % count;
+            format!(
+                "Monkey {}:\n  Starting items: {}\n  Operation: new = {}\n  Test: divisible by {}\n    \
+                If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                id,
+                items[id].join(", "),
+                operation,
+                primes[id],
+                true_target,
+                false_target
+            )
+        })
+        .collect();
+    monkeys.join("\n")
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    let mut monkeys = parse_monkeys(input)?;
     run_turns(&mut monkeys, 20, 3);
+    Ok(monkey_business_level(&mut monkeys))
+}
 
-    println!(
-        "Level of monkey business: {}",
-        monkey_business_level(&mut monkeys)
-    );
+pub fn part2(input: &str) -> Result<usize> {
+    let mut monkeys = parse_monkeys(input)?;
+    let items: Vec<(usize, usize)> = monkeys
+        .iter()
+        .flat_map(|monkey| monkey.items.iter().map(|item| (monkey.id, *item)))
+        .collect();
+    for (holder, worry) in items {
+        let inspected = follow_item(&monkeys, holder, worry, 10_000);
+        for (monkey, count) in monkeys.iter_mut().zip(inspected) {
+            monkey.inspected += count;
+        }
+    }
+    Ok(monkey_business_level(&mut monkeys))
 }
 
-pub fn run2() {
-    let input = read_file("input/day11.txt");
-    let mut monkeys = parse_monkeys(input);
-    run_turns(&mut monkeys, 10_000, 1);
+pub struct Day11;
 
-    println!(
-        "Level of monkey business after 10000 rounds: {}",
-        monkey_business_level(&mut monkeys)
-    );
+impl Solution for Day11 {
+    fn day(&self) -> u32 {
+        11
+    }
+    fn title(&self) -> &'static str {
+        "Monkey in the Middle"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_monkeys(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn parse_monkey_works() {
@@ -185,13 +327,22 @@ mod test {
     If true: throw to monkey 2
     If false: throw to monkey 3"
             )
+            .unwrap()
         );
     }
 
+    #[test]
+    fn unknown_operation_is_reported() {
+        let input = read_file("input/day11-test.txt").unwrap();
+        let input = input.replace("old * 19", "old / 19");
+        let error = parse_monkeys(&input).unwrap_err();
+        assert_eq!("3:24: unknown operation `/`", error.to_string());
+    }
+
     #[test]
     fn parse_monkeys_work() {
-        let input = read_file("input/day11-test.txt");
-        let monkeys = parse_monkeys(input);
+        let input = read_file("input/day11-test.txt").unwrap();
+        let monkeys = parse_monkeys(&input).unwrap();
         assert_eq!(4, monkeys.len());
     }
 
@@ -205,8 +356,8 @@ mod test {
 
     #[test]
     fn run_turn_works() {
-        let input = read_file("input/day11-test.txt");
-        let mut monkeys = parse_monkeys(input);
+        let input = read_file("input/day11-test.txt").unwrap();
+        let mut monkeys = parse_monkeys(&input).unwrap();
         run_turns(&mut monkeys, 1, 3);
         assert_eq!(vec![20, 23, 27, 26], monkeys[0].items);
         assert_eq!(0, monkeys[2].items.len());
@@ -223,8 +374,8 @@ mod test {
 
     #[test]
     fn counting_works() {
-        let input = read_file("input/day11-test.txt");
-        let mut monkeys = parse_monkeys(input);
+        let input = read_file("input/day11-test.txt").unwrap();
+        let mut monkeys = parse_monkeys(&input).unwrap();
         run_turns(&mut monkeys, 20, 3);
         assert_eq!(101, monkeys[0].inspected);
         assert_eq!(95, monkeys[1].inspected);
@@ -233,8 +384,8 @@ mod test {
     }
     #[test]
     fn calc_monkey_business_level_works() {
-        let input = read_file("input/day11-test.txt");
-        let mut monkeys = parse_monkeys(input);
+        let input = read_file("input/day11-test.txt").unwrap();
+        let mut monkeys = parse_monkeys(&input).unwrap();
         run_turns(&mut monkeys, 20, 3);
 
         assert_eq!(10605, monkey_business_level(&mut monkeys));
@@ -242,10 +393,37 @@ mod test {
 
     #[test]
     fn calc_monkey_business_level_with_worry_mul_works() {
-        let input = read_file("input/day11-test.txt");
-        let mut monkeys = parse_monkeys(input);
+        let input = read_file("input/day11-test.txt").unwrap();
+        let mut monkeys = parse_monkeys(&input).unwrap();
         run_turns(&mut monkeys, 10_000, 1);
 
         assert_eq!(2713310158, monkey_business_level(&mut monkeys));
     }
+
+    #[test]
+    fn items_are_followed_on_their_own() {
+        let input = read_file("input/day11-test.txt").unwrap();
+        let monkeys = parse_monkeys(&input).unwrap();
+        let mut all_at_once = monkeys.clone();
+        run_turns(&mut all_at_once, 1000, 1);
+
+        let mut inspected = vec![0; monkeys.len()];
+        for monkey in &monkeys {
+            for item in &monkey.items {
+                let counts = follow_item(&monkeys, monkey.id, *item, 1000);
+                inspected
+                    .iter_mut()
+                    .zip(counts)
+                    .for_each(|(sum, count)| *sum += count);
+            }
+        }
+        assert_eq!(
+            all_at_once
+                .iter()
+                .map(|monkey| monkey.inspected)
+                .collect::<Vec<_>>(),
+            inspected
+        );
+        assert_eq!(2713310158, part2(&input).unwrap());
+    }
 }
@@ -1,141 +1,206 @@
-use crate::{read_file, Point};
-use std::fmt;
-use std::fmt::Debug;
-
-#[derive(PartialEq, Copy, Clone)]
-struct Field {
-    height: u32,
-    distance: u32,
-}
-impl Debug for Field {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[h:{}|D:{}]", self.height, self.distance)
-    }
-}
-impl Field {
-    pub fn new(height: u32) -> Field {
-        Field {
-            height,
-            distance: u32::MAX,
-        }
-    }
-}
+use crate::animation::{Frame, Palette, View};
+use crate::random::Random;
+use crate::search::{bfs, Distances};
+use crate::{Answer, Error, Grid, Point, Result, Solution};
+use ansi_term::Colour::{Red, Yellow};
+use ansi_term::Style;
 
 #[derive(Debug, PartialEq)]
 struct Game {
     height_map: HeightMap,
-    start: Point<usize>,
-    end: Point<usize>,
+    start: Point<i64>,
+    end: Point<i64>,
 }
 
-type HeightMap = Vec<Vec<Field>>;
-
-fn parse_input(input: String) -> Game {
-    let mut height_map: HeightMap = vec![];
-    let mut end: Point<usize> = Point { x: 0, y: 0 };
-    let mut start: Point<usize> = Point { x: 0, y: 0 };
-
-    for (y, line) in input.lines().enumerate() {
-        let mut row: Vec<Field> = vec![];
-
-        for (x, char) in line.chars().enumerate() {
-            match char {
-                'E' => {
-                    row.push(Field::new(0));
-                    end = Point { x, y };
-                }
-                'S' => {
-                    start = Point { x, y };
-                    row.push(Field::new(1));
-                }
-                'a'..='z' => {
-                    row.push(Field::new(char as u32 - 96));
-                }
-                _ => {
-                    row.push(Field::new(u32::MAX));
-                }
-            }
-        }
-        height_map.push(row);
-    }
-    height_map[end.y as usize][end.x as usize].height = height_map
-        .iter()
-        .map(|line| line.iter().map(|cell| cell.height).max().unwrap())
-        .max()
-        .unwrap();
+type HeightMap = Grid<u32>;
+
+fn parse_input(input: &str) -> Result<Game> {
+    let map = Grid::parse(input, "expected a height", |char| match char {
+        'S' | 'E' | 'a'..='z' => Some(char),
+        _ => None,
+    })?;
+    let start = map
+        .find(|char| *char == 'S')
+        .ok_or_else(|| Error::invalid("No start `S` found"))?;
+    let end = map
+        .find(|char| *char == 'E')
+        .ok_or_else(|| Error::invalid("No end `E` found"))?;
 
-    Game {
+    let mut height_map = map.map(|char| match char {
+        'S' => 1,
+        'E' => 0,
+        _ => *char as u32 - 96,
+    });
+    height_map[end] = height_map.iter().copied().max().unwrap_or(0);
+
+    Ok(Game {
         height_map,
         start,
         end,
-    }
+    })
 }
 
-fn walk(height_map: &mut HeightMap, p: Point<usize>, distance: u32) {
-    let field = height_map[p.y][p.x];
+/// The distances of all fields to the end, walking downhill from there. A step may go down at
+/// most one level, but up any number of levels.
+fn distances_to_end(game: &Game) -> Distances<Point<i64>> {
+    let map = &game.height_map;
+    bfs(game.end, |p| {
+        map.neighbours4(*p)
+            .filter(|neighbour| map[*neighbour] + 1 >= map[*p])
+            .collect::<Vec<_>>()
+    })
+}
 
-    if height_map[p.y][p.x].distance > distance {
-        height_map[p.y][p.x].distance = distance
-    } else {
-        return;
-    }
+fn reachable(distance: Option<u64>) -> Result<u64> {
+    distance.ok_or_else(|| Error::invalid("The end can't be reached"))
+}
+
+fn shortest_way_from_start(input: &str) -> Result<u64> {
+    let game = parse_input(input)?;
+
+    reachable(distances_to_end(&game).get(&game.start))
+}
+
+fn shortest_way_from_lowest(input: &str) -> Result<u64> {
+    let game = parse_input(input)?;
+    let distances = distances_to_end(&game);
 
-    // UP
-    if p.y > 0 {
-        let target = height_map[p.y - 1][p.x];
-        if target.height >= field.height - 1 && target.distance > distance + 1 {
-            walk(height_map, Point { y: p.y - 1, x: p.x }, distance + 1);
+    reachable(
+        game.height_map
+            .cells()
+            .filter(|(_, height)| **height == 1)
+            .filter_map(|(p, _)| distances.get(&p))
+            .min(),
+    )
+}
+
+/// The height map with the shortest way from `S` to `E` drawn as arrows, like in the puzzle
+fn view(input: &str) -> Result<View> {
+    let game = parse_input(input)?;
+    let mut map = Grid::parse(input, "expected a height", Some)?;
+    let way = distances_to_end(&game).path(&game.start);
+    let caption = match &way {
+        Some(way) => format!("the shortest way from S takes {} steps", way.len() - 1),
+        None => "the end can't be reached from S".to_string(),
+    };
+    // the search went downhill from the end, so its way leads back from the start
+    let mut way = way.unwrap_or_default();
+    way.reverse();
+    for step in way.windows(2) {
+        if step[0] != game.start {
+            map[step[0]] = match step[1] - step[0] {
+                Point { x: 1, y: 0 } => '>',
+                Point { x: -1, y: 0 } => '<',
+                Point { x: 0, y: 1 } => 'v',
+                _ => '^',
+            };
         }
     }
-    // DOWN
-    if p.y < height_map.len() - 1 {
-        let target = height_map[p.y + 1][p.x];
-        if target.height >= field.height - 1 && target.distance > distance + 1 {
-            walk(height_map, Point { y: p.y + 1, x: p.x }, distance + 1);
-        }
+    let palette = ('a'..='z')
+        .fold(Palette::new(), |palette, height| {
+            palette.with(height, Style::new().dimmed())
+        })
+        .with('S', Red.bold())
+        .with('E', Red.bold())
+        .with('>', Yellow.bold())
+        .with('<', Yellow.bold())
+        .with('v', Yellow.bold())
+        .with('^', Yellow.bold());
+    Ok(View::new(Frame::new(map).with_caption(&caption), palette))
+}
+
+/// A map `size` squares wide and half as high. The heights rise evenly along the top and the
+/// right edge from `S` to `E`, elsewhere there are dips and cliffs.
+fn generate(size: usize, random: &mut Random) -> String {
+    let (width, height) = (size.max(2), (size / 2).max(2));
+    let steps = (width + height - 2).max(25);
+    let rise = |x: usize, y: usize| ((x + y) * 25 / steps) as i64;
+    let top = rise(width - 1, height - 1);
+    (0..height)
+        .map(|y| {
+            let row: String = (0..width)
+                .map(|x| {
+                    let level = match (x, y) {
+                        (0, 0) => return 'S',
+                        _ if (x, y) == (width - 1, height - 1) => return 'E',
+                        _ if y == 0 || x == width - 1 => rise(x, y),
+                        _ if random.chance(0.1) => random.range(rise(x, y)..=top),
+                        _ => (rise(x, y) - random.range(0..=3)).max(0),
+                    };
+                    char::from(b'a' + level as u8)
+                })
+                .collect();
+            row + "\n"
+        })
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    shortest_way_from_start(input)
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    shortest_way_from_lowest(input)
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    fn day(&self) -> u32 {
+        12
     }
-    // LEFT
-    if p.x > 0 {
-        let target = height_map[p.y][p.x - 1];
-        if target.height >= field.height - 1 && target.distance > distance + 1 {
-            walk(height_map, Point { y: p.y, x: p.x - 1 }, distance + 1);
-        }
+    fn title(&self) -> &'static str {
+        "Hill Climbing Algorithm"
     }
-    //RIGHT
-    if p.x < height_map[0].len() - 1 {
-        let target = height_map[p.y][p.x + 1];
-        if target.height >= field.height - 1 && target.distance > distance + 1 {
-            walk(height_map, Point { y: p.y, x: p.x + 1 }, distance + 1);
-        }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+    fn view(&self, input: &str) -> Result<View> {
+        view(input)
     }
 }
 
-pub fn run() {
-    let mut game = parse_input(read_file("input/day12.txt"));
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read_file;
 
-    walk(&mut game.height_map, game.end, 0);
-    println!(
-        " The way is {} long",
-        game.height_map[game.start.y][game.start.x].distance
-    );
-}
-pub fn run2() {
-    let mut game = parse_input(read_file("input/day12.txt"));
-
-    walk(&mut game.height_map, game.end, 0);
-
-    let shortest = game
-        .height_map
-        .iter()
-        .map(|line| {
-            line.iter()
-                .filter(|cell| cell.height == 1)
-                .map(|cell| cell.distance)
-                .min()
-                .unwrap()
-        })
-        .min()
-        .unwrap();
+    #[test]
+    fn example_is_walked() {
+        let input = read_file("input/day12-test.txt").unwrap();
+        assert_eq!(31, part1(&input).unwrap());
+        assert_eq!(29, part2(&input).unwrap());
+    }
+
+    #[test]
+    fn cliffs_block_the_way() {
+        assert_eq!(
+            "The end can't be reached",
+            part1("SacE").unwrap_err().to_string()
+        );
+    }
 
-    println!(" The shortest way from height a is {} long", shortest);
+    #[test]
+    fn the_way_is_viewed() {
+        let walked = view(&read_file("input/day12-test.txt").unwrap()).unwrap();
+        let map = walked.frame.grid.to_string();
+        assert!(map.starts_with('S'));
+        assert_eq!(30, map.matches(['>', '<', 'v', '^']).count());
+        assert_eq!(
+            "the shortest way from S takes 31 steps",
+            walked.frame.caption
+        );
+
+        let blocked = view("SacE").unwrap();
+        assert_eq!("SacE", blocked.frame.grid.to_string());
+    }
 }
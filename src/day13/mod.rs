@@ -1,6 +1,8 @@
 use crate::day13::ListItem::{ItemList, Value};
 use crate::day13::ParseMode::{FindItems, FindRight};
-use crate::read_file;
+use crate::error::{parse_number, Context};
+use crate::random::Random;
+use crate::{Answer, Error, Result, Solution};
 use std::cmp::Ordering;
 
 #[derive(Debug, Eq)]
@@ -42,9 +44,12 @@ enum ParseMode {
     FindItems,
 }
 
-fn parse_list(input: &str) -> ListItem {
+fn parse_list(input: &str) -> Result<ListItem> {
     if input.is_empty() {
-        return ItemList(vec![]);
+        return Ok(ItemList(vec![]));
+    }
+    if !input.is_ascii() {
+        return Err(Error::parse(input, "unexpected character in packet"));
     }
     let mut cursor = 0;
     let mut capture_start = 0;
@@ -63,7 +68,8 @@ fn parse_list(input: &str) -> ListItem {
                 ']' => {
                     level -= 1;
                     if level == 0 {
-                        items.push(parse_list(&input[capture_start..cursor]));
+                        let list = &input[capture_start..cursor];
+                        items.push(parse_list(list).within(input, list)?);
                         parse_mode = FindItems;
                         capture_start = cursor + 1;
                     }
@@ -78,13 +84,8 @@ fn parse_list(input: &str) -> ListItem {
                 }
                 ',' | ']' => {
                     if cursor > capture_start {
-                        items.push(Value(
-                            stream[capture_start..cursor]
-                                .iter()
-                                .collect::<String>()
-                                .parse()
-                                .unwrap(),
-                        ));
+                        let value = &input[capture_start..cursor];
+                        items.push(Value(parse_number(value).within(input, value)?));
                     }
                     capture_start = cursor + 1;
                 }
@@ -94,61 +95,114 @@ fn parse_list(input: &str) -> ListItem {
 
         cursor += 1;
     }
+    if let FindRight = parse_mode {
+        let list = &input[capture_start - 1..];
+        return Err(Error::parse(list, "unclosed list").within(input, list));
+    }
     if cursor > capture_start {
-        items.push(Value(
-            stream[capture_start..cursor]
-                .iter()
-                .collect::<String>()
-                .parse()
-                .unwrap(),
-        ));
+        let value = &input[capture_start..cursor];
+        items.push(Value(parse_number(value).within(input, value)?));
     }
 
-    ItemList(items)
+    Ok(ItemList(items))
 }
 
-pub fn run() {
-    let mut count = 0;
-    let input = read_file("input/day13.txt");
-    let pairs = input.split("\n\n");
-    for (i, pair) in pairs.enumerate() {
-        let (left, right) = pair.split_once('\n').unwrap();
-        let left = parse_list(left);
-        let right = parse_list(right);
-
-        if left < right {
-            count += i + 1;
-        }
-    }
-    println!("Sum of indexes: {}", count);
+/// The pairs of packets, separated by blank lines
+fn parse_pairs(input: &str) -> Result<Vec<(ListItem, ListItem)>> {
+    input
+        .trim_end()
+        .split("\n\n")
+        .map(|pair| {
+            let (left, right) = pair.split_once('\n').ok_or_else(|| {
+                Error::parse(pair, "expected a pair of packets").within(input, pair)
+            })?;
+            let left = parse_list(left).within(input, left)?;
+            let right = parse_list(right).within(input, right)?;
+            Ok((left, right))
+        })
+        .collect()
 }
 
-pub fn run2() {
-    let input = read_file("input/day13.txt") + "\n\n[[2]]\n[[6]]";
-
-    let mut packet_list: Vec<ListItem> = vec![];
+fn sum_of_ordered_pairs(input: &str) -> Result<usize> {
+    let pairs = parse_pairs(input)?;
+    Ok((1..)
+        .zip(&pairs)
+        .filter(|(_, (left, right))| left < right)
+        .map(|(i, _)| i)
+        .sum())
+}
 
-    for line in input.lines() {
-        if !line.is_empty() {
-            packet_list.push(parse_list(line))
-        }
-    }
+fn decoder_key(input: &str) -> Result<usize> {
+    let divider = |value| ItemList(vec![ItemList(vec![ItemList(vec![Value(value)])])]);
+    let mut packet_list: Vec<ListItem> = parse_pairs(input)?
+        .into_iter()
+        .flat_map(|(left, right)| [left, right])
+        .chain([divider(2), divider(6)])
+        .collect();
     packet_list.sort();
 
-    let marker1 = packet_list
-        .iter()
-        .enumerate()
-        .find(|(_i, item)| ItemList(vec![ItemList(vec![Value(2)])]) == **item)
-        .unwrap();
-    let marker2 = packet_list
-        .iter()
-        .enumerate()
-        .find(|(_i, item)| ItemList(vec![ItemList(vec![Value(6)])]) == **item)
-        .unwrap();
-
-    println!("This is the Decoder key: {}", {
-        (marker1.0 + 1) * (marker2.0 + 1)
-    });
+    let position = |value| {
+        let divider = divider(value);
+        packet_list
+            .iter()
+            .position(|item| *item == divider)
+            .expect("the divider packets were added")
+    };
+
+    Ok((position(2) + 1) * (position(6) + 1))
+}
+
+/// `size` pairs of packets
+fn generate(size: usize, random: &mut Random) -> String {
+    let pairs: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            let left = packet(0, random);
+            format!("{}\n{}", left, packet(0, random))
+        })
+        .collect();
+    pairs.join("\n\n") + "\n"
+}
+
+/// A list of up to four numbers and lists, nested at most four levels deep
+fn packet(depth: usize, random: &mut Random) -> String {
+    let items: Vec<String> = (0..random.range(0..=4))
+        .map(|_| match depth < 4 && random.chance(0.3) {
+            true => packet(depth + 1, random),
+            false => random.range(0..=10).to_string(),
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    sum_of_ordered_pairs(input)
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    decoder_key(input)
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    fn day(&self) -> u32 {
+        13
+    }
+    fn title(&self) -> &'static str {
+        "Distress Signal"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_pairs(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
@@ -157,18 +211,21 @@ mod test {
 
     #[test]
     fn list_parsing() {
-        assert_eq!(ItemList(vec![ItemList(vec![])]), parse_list("[]"));
-        assert_eq!(ItemList(vec![ItemList(vec![Value(1)])]), parse_list("[1]"));
+        assert_eq!(ItemList(vec![ItemList(vec![])]), parse_list("[]").unwrap());
+        assert_eq!(
+            ItemList(vec![ItemList(vec![Value(1)])]),
+            parse_list("[1]").unwrap()
+        );
         assert_eq!(
             ItemList(vec![ItemList(vec![Value(1), Value(2)])]),
-            parse_list("[1,2]")
+            parse_list("[1,2]").unwrap()
         );
         assert_eq!(
             ItemList(vec![ItemList(vec![
                 Value(1),
                 ItemList(vec![Value(1), Value(2)])
             ])]),
-            parse_list("[1,[1,2]]")
+            parse_list("[1,[1,2]]").unwrap()
         );
         assert_eq!(
             ItemList(vec![ItemList(vec![
@@ -186,12 +243,20 @@ mod test {
                 Value(8),
                 Value(9),
             ])]),
-            parse_list("[1,[2,[3,[4,[5,6,0]]]],8,9]")
+            parse_list("[1,[2,[3,[4,[5,6,0]]]],8,9]").unwrap()
         );
     }
 
+    #[test]
+    fn broken_packets_are_reported() {
+        let error = sum_of_ordered_pairs("[1,2]\n[1,3]\n\n[1]\n[[1],x]").unwrap_err();
+        assert_eq!("5:6: expected a number `x`", error.to_string());
+        let error = parse_list("[1,[2,3]").unwrap_err();
+        assert_eq!("1:1: unclosed list `[1,[2,3]`", error.to_string());
+    }
+
     #[test]
     fn less_than() {
-        assert_eq!(true, parse_list("[1]") < parse_list("[2]"));
+        assert!(parse_list("[1]").unwrap() < parse_list("[2]").unwrap());
     }
 }
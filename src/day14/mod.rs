@@ -1,11 +1,12 @@
-use crate::read_file;
-use crate::Point;
-use itertools::Itertools;
-use png::Encoder;
+use crate::animation::{Animation, Frame, Palette, View};
+use crate::error::{parse_number, Context};
+use crate::random::Random;
+use crate::svg::Svg;
+use crate::{image, Answer, Error, Grid, Point, Result, Solution};
+use ansi_term::Colour::{White, Yellow};
+use ansi_term::Style;
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
-use std::fs::File;
-use std::io::BufWriter;
 use std::path::Path;
 
 type Points = Vec<Point<i32>>;
@@ -25,9 +26,6 @@ impl Rules {
     fn min_x(&self) -> Option<i32> {
         self.points.iter().map(|point| point.x).min()
     }
-    fn min_y(&self) -> Option<i32> {
-        self.points.iter().map(|point| point.y).min()
-    }
 }
 
 struct Ruleset {
@@ -48,117 +46,88 @@ impl Ruleset {
     fn min_x(&self) -> Option<i32> {
         self.items.iter().map(|rules| rules.min_x()).min()?
     }
-    fn min_y(&self) -> Option<i32> {
-        self.items.iter().map(|rules| rules.min_y()).min()?
-    }
 }
 
-struct Board {
-    map: Vec<Vec<char>>,
-    offset: Point<i32>,
+pub struct Board {
+    map: Grid<char>,
 }
 
 impl Board {
     pub fn set(&mut self, x: i32, y: i32, c: char) {
-        self.map[y as usize][(x - self.offset.x) as usize] = c;
+        self.map[Point::new(x as i64, y as i64)] = c;
     }
     pub fn get(&self, x: i32, y: i32) -> char {
-        let ox = (x - self.offset.x) as usize;
-        let oy = y as usize;
-        if oy < self.map.len() && ox < self.map[0].len() {
-            return self.map[oy][ox];
-        }
-        '.'
+        *self.map.get(Point::new(x as i64, y as i64)).unwrap_or(&'.')
     }
 
     pub fn is_outside(&self, x: i32, y: i32) -> bool {
-        let ox = (x - self.offset.x) as usize;
-        let oy = y as usize;
-
-        ox >= self.map[0].len() || oy >= self.map.len()
+        !self.map.contains(Point::new(x as i64, y as i64))
     }
 
     pub fn count_sand(&self) -> usize {
-        self.map
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|cell| match cell {
-                        'o' => 1,
-                        _ => 0,
-                    })
-                    .sum::<usize>()
-            })
-            .sum()
-    }
-
-    pub fn save_png(&self, filename: &str) {
-        let full_file = "output/".to_string() + filename;
-        let path = Path::new(&full_file);
-        let file = File::create(path).unwrap();
-        let w = &mut BufWriter::new(file);
-
-        let mut encoder = Encoder::new(w, self.map[0].len() as u32, self.map.len() as u32);
-        encoder.set_color(png::ColorType::Grayscale);
-        let mut writer = encoder.write_header().unwrap();
-
-        let data: Vec<u8> = self
-            .map
-            .iter()
-            .map(|row| row.iter().map(|cell| *cell as u8).collect::<Vec<u8>>())
-            .collect::<Vec<Vec<u8>>>()
-            .concat();
-
-        writer.write_image_data(&data).unwrap();
+        self.map.iter().filter(|cell| **cell == 'o').count()
     }
+
+    /// Writes the board as PNG, one square of `scale` pixels per cell
+    pub fn save_png(&self, path: &Path, scale: u32) -> Result<()> {
+        image::save_png(path, &self.map, &palette(), scale)
+    }
+}
+
+/// Rock, sand and air
+fn palette() -> Palette {
+    Palette::new()
+        .with('#', White.bold())
+        .with('o', Yellow.normal())
+        .with('.', Style::new().dimmed())
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.map
-                .iter()
-                .map(|row| row.iter().collect::<String>())
-                .join("\n")
-        )
+        write!(f, "{}", self.map)
     }
 }
 
-fn parse_input(input: String) -> Vec<Rules> {
+fn parse_point(coordinate: &str) -> Result<Point<i32>> {
+    let (x, y) = coordinate
+        .split_once(',')
+        .ok_or_else(|| Error::parse(coordinate, "expected a point like `498,4`"))?;
+    let point = Point {
+        x: parse_number(x).within(coordinate, x)?,
+        y: parse_number(y).within(coordinate, y)?,
+    };
+    if point.y < 0 {
+        return Err(Error::parse(y, "rock can't be above the sand source").within(coordinate, y));
+    }
+    Ok(point)
+}
+
+fn parse_input(input: &str) -> Result<Vec<Rules>> {
     let mut ruleset: Vec<Rules> = vec![];
 
     for line in input.lines() {
         let rules: Rules = Rules {
             points: line
                 .split(" -> ")
-                .map(|coordinate| {
-                    let (x, y) = coordinate.split_once(',').unwrap();
-                    Point {
-                        x: x.parse().unwrap(),
-                        y: y.parse().unwrap(),
-                    }
-                })
-                .collect::<Points>(),
+                .map(|coordinate| parse_point(coordinate).within(input, coordinate))
+                .collect::<Result<Points>>()?,
         };
         ruleset.push(rules);
     }
 
-    ruleset
+    Ok(ruleset)
 }
 
 fn create_board(ruleset: Ruleset) -> Option<Board> {
     let x = ruleset.min_x()?;
-    let y = ruleset.min_y()?;
     let x1 = ruleset.max_x()?;
     let y1 = ruleset.max_y()?;
     let width = x1 - x + 1;
     let height = y1 + 1;
 
+    // the sand falls from y = 0, so the board starts there instead of at the highest rock
     let mut board = Board {
-        map: vec![vec!['.'; width as usize]; height as usize],
-        offset: Point { x, y },
+        map: Grid::new(width as usize, height as usize, '.').with_origin(Point::new(x as i64, 0)),
     };
 
     for rules in ruleset.items {
@@ -207,6 +176,15 @@ fn paint_horizontal_line(board: &mut Board, x1: i32, x2: i32, y: i32) {
 }
 
 fn run_simulation(board: &mut Board) {
+    pour_sand(board, |_, _| Ok(())).expect("pouring alone doesn't fail");
+}
+
+/// Lets sand fall from 500,0 until it flows out of the board or blocks the source. `observe`
+/// sees the board after every grain which came to rest, along with that grain.
+fn pour_sand(
+    board: &mut Board,
+    mut observe: impl FnMut(&Board, Point<i32>) -> Result<()>,
+) -> Result<()> {
     let mut cx = 500;
     let mut cy = 0;
 
@@ -219,6 +197,7 @@ fn run_simulation(board: &mut Board) {
             cx += 1;
         } else {
             board.set(cx, cy - 1, 'o');
+            observe(board, Point::new(cx, cy - 1))?;
             if cx == 500 && cy == 1 {
                 break;
             }
@@ -230,41 +209,144 @@ fn run_simulation(board: &mut Board) {
             break;
         }
     }
+    Ok(())
 }
 
-pub fn run() {
-    let board = create_board(Ruleset::new(parse_input(read_file("input/day14.txt"))));
+fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let mut board = create_board(Ruleset::new(parse_input(input)?)).ok_or_else(no_rocks)?;
+    animation.set_palette(palette());
+    let mut grains = 0;
+    pour_sand(&mut board, |board, grain| {
+        grains += 1;
+        let frame = Frame::new(board.map.clone())
+            .with_focus(grain.into())
+            .with_caption(&format!("{} grains of sand came to rest", grains));
+        animation.show(&frame)
+    })
+}
 
-    let mut board = board.unwrap();
+/// The sand at rest once it flows past the rocks, starting at the source
+fn view(input: &str) -> Result<View> {
+    let board = fill_with_sand(input)?;
+    let caption = format!("{} grains of sand came to rest", board.count_sand());
+    let frame = Frame::new(board.map)
+        .with_focus(Point::new(500, 0))
+        .with_caption(&caption);
+    Ok(View::new(frame, palette()))
+}
 
-    run_simulation(&mut board);
+/// The rock paths and the source of the sand
+fn draw(input: &str) -> Result<Svg> {
+    let mut svg = Svg::new("Regolith Reservoir");
+    for rules in parse_input(input)? {
+        svg.polyline(rules.points, "#555");
+    }
+    if svg.is_empty() {
+        return Err(no_rocks());
+    }
+    svg.marker(Point::new(500, 0), "+", "#c90");
+    Ok(svg)
+}
 
-    println!("{} units sand are collected!", board.count_sand());
-    println!("I painted a nice picture for you (output/day14-a.png)");
+fn no_rocks() -> Error {
+    Error::invalid("No rock formations found")
+}
+
+pub fn fill_with_sand(input: &str) -> Result<Board> {
+    let mut board = create_board(Ruleset::new(parse_input(input)?)).ok_or_else(no_rocks)?;
+
+    run_simulation(&mut board);
 
-    board.save_png("day14-a.png");
+    Ok(board)
 }
 
-pub fn run2() {
-    let mut ruleset = Ruleset::new(parse_input(read_file("input/day14.txt")));
-    let y = ruleset.max_y().unwrap();
+pub fn fill_with_sand_on_floor(input: &str) -> Result<Board> {
+    let mut ruleset = Ruleset::new(parse_input(input)?);
+    let y = ruleset.max_y().ok_or_else(no_rocks)?;
     ruleset.items.push(Rules {
         points: vec![
             Point::new(500 - y - 2, y + 2),
             Point::new(500 + y + 2, y + 2),
         ],
     });
-    let mut board = create_board(ruleset).unwrap();
+    let mut board = create_board(ruleset).ok_or_else(no_rocks)?;
     run_simulation(&mut board);
 
-    println!("{} units of sand come to rest", board.count_sand());
-    println!("This pic is even nicer (output/day14-b.png)");
-    board.save_png("day14-b.png");
+    Ok(board)
+}
+
+/// `size` rock paths below the source of the sand
+fn generate(size: usize, random: &mut Random) -> String {
+    let depth = 10 + size as i32 / 4;
+    (0..size.max(1))
+        .map(|_| {
+            let mut point = Point::new(
+                random.range(470..=530) as i32,
+                random.range(2..=depth as i64) as i32,
+            );
+            let mut path = vec![point];
+            let horizontal = random.chance(0.5);
+            for segment in 0..random.range(1..=4) {
+                let length = random.range(-6..=6) as i32;
+                point = match (segment % 2 == 0) == horizontal {
+                    true => Point::new(point.x + length, point.y),
+                    false => Point::new(point.x, (point.y + length).max(2)),
+                };
+                path.push(point);
+            }
+            let points: Vec<String> = path
+                .iter()
+                .map(|point| format!("{},{}", point.x, point.y))
+                .collect();
+            points.join(" -> ") + "\n"
+        })
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    Ok(fill_with_sand(input)?.count_sand())
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    Ok(fill_with_sand_on_floor(input)?.count_sand())
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    fn day(&self) -> u32 {
+        14
+    }
+    fn title(&self) -> &'static str {
+        "Regolith Reservoir"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+    fn animate(&self, input: &str, animation: &mut Animation) -> Result<()> {
+        animate(input, animation)
+    }
+    fn draw(&self, input: &str) -> Result<Svg> {
+        draw(input)
+    }
+    fn view(&self, input: &str) -> Result<View> {
+        view(input)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::read_file;
 
     #[test]
     fn parses_lines() {
@@ -272,7 +354,7 @@ mod test {
             vec![Rules {
                 points: vec![Point::new(1, 1), Point::new(2, 2)]
             }],
-            parse_input("1,1 -> 2,2".to_string())
+            parse_input("1,1 -> 2,2").unwrap()
         );
         assert_eq!(
             vec![
@@ -288,7 +370,11 @@ mod test {
                     ]
                 },
             ],
-            parse_input(read_file("input/day14-test.txt"))
+            parse_input(&read_file("input/day14-test.txt").unwrap()).unwrap()
+        );
+        assert_eq!(
+            "1:8: expected a number `x`",
+            parse_input("1,1 -> x,2").unwrap_err().to_string()
         );
     }
 
@@ -305,7 +391,6 @@ mod test {
         assert_eq!(Some(503), rules.max_x());
         assert_eq!(Some(494), rules.min_x());
         assert_eq!(Some(9), rules.max_y());
-        assert_eq!(Some(4), rules.min_y());
 
         let ruleset = Ruleset {
             items: vec![
@@ -325,12 +410,13 @@ mod test {
         assert_eq!(Some(503), ruleset.max_x());
         assert_eq!(Some(494), ruleset.min_x());
         assert_eq!(Some(9), ruleset.max_y());
-        assert_eq!(Some(4), ruleset.min_y());
     }
 
     #[test]
     fn painting_board_works() {
-        let board = create_board(Ruleset::new(parse_input(read_file("input/day14-test.txt"))));
+        let board = create_board(Ruleset::new(
+            parse_input(&read_file("input/day14-test.txt").unwrap()).unwrap(),
+        ));
 
         let board = board.unwrap();
 
@@ -352,7 +438,9 @@ mod test {
 
     #[test]
     fn filling_with_sand_works() {
-        let board = create_board(Ruleset::new(parse_input(read_file("input/day14-test.txt"))));
+        let board = create_board(Ruleset::new(
+            parse_input(&read_file("input/day14-test.txt").unwrap()).unwrap(),
+        ));
 
         let mut board = board.unwrap();
 
@@ -376,7 +464,9 @@ mod test {
 
     #[test]
     fn counting_sand_works() {
-        let board = create_board(Ruleset::new(parse_input(read_file("input/day14-test.txt"))));
+        let board = create_board(Ruleset::new(
+            parse_input(&read_file("input/day14-test.txt").unwrap()).unwrap(),
+        ));
 
         let mut board = board.unwrap();
 
@@ -384,12 +474,15 @@ mod test {
 
         assert_eq!(24, board.count_sand());
 
-        board.save_png("day14-test-a.png");
+        board
+            .save_png(Path::new("output/day14-test-a.png"), 1)
+            .unwrap();
     }
 
     #[test]
     fn part2_works() {
-        let mut ruleset = Ruleset::new(parse_input(read_file("input/day14-test.txt")));
+        let mut ruleset =
+            Ruleset::new(parse_input(&read_file("input/day14-test.txt").unwrap()).unwrap());
         let y = ruleset.max_y().unwrap();
         ruleset.items.push(Rules {
             points: vec![
@@ -404,6 +497,33 @@ mod test {
 
         assert_eq!(93, board.count_sand());
 
-        board.save_png("day14-test-b.png");
+        board
+            .save_png(Path::new("output/day14-test-b.png"), 1)
+            .unwrap();
+    }
+
+    #[test]
+    fn every_grain_is_animated() {
+        let mut animation = Animation::sink();
+        animate(&read_file("input/day14-test.txt").unwrap(), &mut animation).unwrap();
+        assert_eq!(24, animation.frames());
+    }
+
+    #[test]
+    fn rock_paths_are_drawn() {
+        let svg = draw(&read_file("input/day14-test.txt").unwrap()).unwrap();
+        let text = svg.to_string();
+        assert!(text.contains(r#"<polyline points="498,4 498,6 496,6""#));
+        assert!(text.contains(r#"<polyline points="503,4 502,4 502,9 494,9""#));
+        assert!(text.contains(">+</text>"));
+        assert!(draw("").is_err());
+    }
+
+    #[test]
+    fn sand_at_rest_is_viewed() {
+        let view = view(&read_file("input/day14-test.txt").unwrap()).unwrap();
+        assert_eq!(Point::new(494, 0), view.frame.grid.origin());
+        assert_eq!(Some('o'), view.frame.grid.get(Point::new(500, 2)).copied());
+        assert_eq!("24 grains of sand came to rest", view.frame.caption);
     }
 }
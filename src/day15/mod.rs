@@ -1,150 +1,192 @@
-use crate::read_file;
-use regex::Regex;
-use std::cmp::{max, min};
+use crate::parse::{lines, numbers};
+use crate::random::Random;
+use crate::svg::Svg;
+use crate::{Answer, Error, IntervalSet, Point, Result, Solution};
+use itertools::Itertools;
 
 #[derive(Debug, PartialEq)]
 struct Pair {
-    sensor: (i64, i64),
-    beacon: (i64, i64),
+    sensor: Point<i64>,
+    beacon: Point<i64>,
 }
 
 impl Pair {
     fn manhattan(&self) -> i64 {
-        (self.beacon.1 - self.sensor.1).abs() + (self.beacon.0 - self.sensor.0).abs()
+        self.sensor.manhattan(&self.beacon)
     }
     pub fn visible_at_line(&self, line: i64) -> Option<(i64, i64)> {
-        let width_at_line = self.manhattan() - (line - self.sensor.1).abs();
-        if width_at_line < 1 {
+        let width_at_line = self.manhattan() - (line - self.sensor.y()).abs();
+        if width_at_line < 0 {
             return None;
         }
-        Some((self.sensor.0 - width_at_line, self.sensor.0 + width_at_line))
+        Some((
+            self.sensor.x() - width_at_line,
+            self.sensor.x() + width_at_line,
+        ))
     }
 }
 
-pub fn interval_union(mut intervals: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
-    let mut interval_union: Vec<(i64, i64)> = vec![];
-
-    intervals.sort_by(|a, b| a.0.cmp(&b.0));
-
-    for t in intervals.iter() {
-        if interval_union.is_empty() {
-            interval_union.push(t.to_owned());
-        }
-        let mut found = false;
-        for ut in &mut interval_union {
-            if t.0 >= ut.0 - 1 && t.0 <= ut.1 + 1 {
-                ut.1 = max(t.1, ut.1);
-                found = true;
-                break;
-            } else if t.1 <= ut.1 + 1 && t.1 >= ut.0 + 1 {
-                ut.0 = min(t.0, ut.0);
-                found = true;
-                break;
-            }
-        }
-        if !found {
-            interval_union.push(t.to_owned());
-        }
-    }
-    interval_union
-}
-
-fn intervals_at_line(pairs: &[Pair], line: i64) -> Vec<(i64, i64)> {
+/// The x coordinates which are seen by any sensor at `line`
+fn intervals_at_line(pairs: &[Pair], line: i64) -> IntervalSet<i64> {
     pairs
         .iter()
         .filter_map(|pair| pair.visible_at_line(line))
         .collect()
 }
 
-fn count_intervals(interval: Vec<(i64, i64)>) -> i64 {
-    interval.iter().map(|i| i.1 - i.0 + 1).sum()
+fn parse_line(line: &str) -> Result<Pair> {
+    let [sensor_x, sensor_y, beacon_x, beacon_y] = numbers(
+        line,
+        "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+    )?;
+    Ok(Pair {
+        sensor: Point::new(sensor_x, sensor_y),
+        beacon: Point::new(beacon_x, beacon_y),
+    })
 }
 
-fn count_intervals_with_bounds(interval: Vec<(i64, i64)>, left: i64, right: i64) -> i64 {
-    let clipped = clip_intervals(interval, left, right);
-    count_intervals(clipped)
+fn parse_input(input: &str) -> Result<Vec<Pair>> {
+    lines(input, parse_line)
 }
 
-fn clip_intervals(interval: Vec<(i64, i64)>, left: i64, right: i64) -> Vec<(i64, i64)> {
-    let clipped = interval
+fn impossible_positions(pairs: &[Pair], line: i64) -> i64 {
+    let beacons_at_line = pairs
         .iter()
-        .filter(|i| i.0 <= right && i.1 >= left)
-        .map(|i| (max(i.0, left), min(i.1, right)))
-        .collect::<Vec<(i64, i64)>>();
-    clipped
+        .filter(|pair| pair.beacon.y() == line)
+        .map(|pair| pair.beacon)
+        .unique()
+        .count() as i64;
+
+    intervals_at_line(pairs, line).len() - beacons_at_line
 }
 
-fn parse_line(line: &str) -> Pair {
-    let regex = Regex::new(
-        r"Sensor at x=([\d-]+), y=([\d-]+): closest beacon is at x=([\d-]+), y=([\d-]+)",
-    )
-    .unwrap();
-    let cap = regex.captures(line).unwrap();
-
-    Pair {
-        sensor: (
-            cap[1].parse::<i64>().unwrap(),
-            cap[2].parse::<i64>().unwrap(),
-        ),
-        beacon: (
-            cap[3].parse::<i64>().unwrap(),
-            cap[4].parse::<i64>().unwrap(),
-        ),
-    }
+/// Finds the only position between 0 and `range` no sensor sees
+fn tuning_frequency(pairs: &[Pair], range: i64) -> Option<i64> {
+    let area = IntervalSet::interval(0, range);
+    (0..=range).find_map(|y| {
+        let (x, _) = area
+            .difference(&intervals_at_line(pairs, y))
+            .intervals()
+            .next()?;
+        Some(y + range * x)
+    })
 }
 
-fn parse_input(input: &str) -> Vec<Pair> {
-    let mut pairs: Vec<Pair> = vec![];
-    for line in input.lines() {
-        pairs.push(parse_line(line));
+/// The area each sensor sees as diamond, with the sensors and their beacons
+fn draw(input: &str) -> Result<Svg> {
+    let pairs = parse_input(input)?;
+    let mut svg = Svg::new("Beacon Exclusion Zone");
+    for pair in &pairs {
+        let (sensor, distance) = (pair.sensor, pair.manhattan());
+        let corners = [
+            Point::new(sensor.x, sensor.y - distance),
+            Point::new(sensor.x + distance, sensor.y),
+            Point::new(sensor.x, sensor.y + distance),
+            Point::new(sensor.x - distance, sensor.y),
+        ];
+        svg.polygon(corners, "#357", "#9ab");
     }
-    pairs
+    for pair in &pairs {
+        svg.marker(pair.sensor, "S", "#357");
+    }
+    for beacon in pairs.iter().map(|pair| pair.beacon).unique() {
+        svg.marker(beacon, "B", "#c22");
+    }
+    Ok(svg)
 }
 
-pub fn run() {
-    let pairs = parse_input(&read_file("input/day15.txt"));
+/// `size` sensors around a distress beacon somewhere in the area of part 2. The beacon of every
+/// sensor is just one step closer than the distress beacon, so it stays hidden. Whether the
+/// sensors see all the rest depends on how many there are.
+fn generate(size: usize, random: &mut Random) -> String {
+    let area = 4_000_000;
+    let distress = Point::new(random.range(0..=area), random.range(0..=area));
+    (0..size.max(1))
+        .map(|_| {
+            let sensor = loop {
+                let sensor = Point::new(
+                    random.range(-area / 20..=area * 21 / 20),
+                    random.range(-area / 20..=area * 21 / 20),
+                );
+                if sensor.manhattan(&distress) > 1 {
+                    break sensor;
+                }
+            };
+            let reach = sensor.manhattan(&distress) - 1;
+            let dx = random.range(-reach..=reach);
+            let dy = match random.chance(0.5) {
+                true => reach - dx.abs(),
+                false => dx.abs() - reach,
+            };
+            format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+                sensor.x,
+                sensor.y,
+                sensor.x + dx,
+                sensor.y + dy
+            )
+        })
+        .collect()
+}
 
-    let intervals = intervals_at_line(&pairs, 2_000_000);
+pub fn part1(input: &str) -> Result<i64> {
+    Ok(impossible_positions(&parse_input(input)?, 2_000_000))
+}
 
-    println!(
-        "{} Positions cannot hold a beacon",
-        count_intervals(interval_union(intervals))
-    );
+pub fn part2(input: &str) -> Result<i64> {
+    let pairs = parse_input(input)?;
+    if pairs.is_empty() {
+        return Err(Error::invalid("No sensors found"));
+    }
+    tuning_frequency(&pairs, 4_000_000)
+        .ok_or_else(|| Error::invalid("Every position is seen by a sensor"))
 }
 
-pub fn run2() {
-    let pairs = parse_input(&read_file("input/day15.txt"));
-    let range = 4_000_000;
-    let mut min_count = i64::MAX;
-    let mut y = 0;
-    for i in 0..range {
-        let intervals = intervals_at_line(&pairs, i);
-        let vec = interval_union(intervals);
-        let count = count_intervals_with_bounds(vec, 0, range);
-        if count < min_count {
-            min_count = count;
-            y = i;
-        }
+pub struct Day15;
+
+impl Solution for Day15 {
+    fn day(&self) -> u32 {
+        15
+    }
+    fn title(&self) -> &'static str {
+        "Beacon Exclusion Zone"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+    fn draw(&self, input: &str) -> Result<Svg> {
+        draw(input)
     }
-    let intervals = interval_union(intervals_at_line(&pairs, y));
-    println!(
-        "This is your frequency: {}",
-        y + range * (intervals[0].1 + 1)
-    );
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::read_file;
 
     #[test]
     pub fn parses_line() {
         assert_eq!(
             Pair {
-                sensor: (1, 2),
-                beacon: (-3, 4)
+                sensor: Point::new(1, 2),
+                beacon: Point::new(-3, 4)
             },
-            parse_line("Sensor at x=1, y=2: closest beacon is at x=-3, y=4")
+            parse_line("Sensor at x=1, y=2: closest beacon is at x=-3, y=4").unwrap()
+        );
+        assert_eq!(
+            "1:13: expected a number `2-`",
+            parse_line("Sensor at x=2-, y=2: closest beacon is at x=-3, y=4")
+                .unwrap_err()
+                .to_string()
         );
     }
 
@@ -153,53 +195,76 @@ mod test {
         assert_eq!(
             vec![
                 Pair {
-                    sensor: (2, 18),
-                    beacon: (-2, 15)
+                    sensor: Point::new(2, 18),
+                    beacon: Point::new(-2, 15)
                 },
                 Pair {
-                    sensor: (9, 16),
-                    beacon: (10, 16)
+                    sensor: Point::new(9, 16),
+                    beacon: Point::new(10, 16)
                 }
             ],
             parse_input(
                 "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16"
             )
+            .unwrap()
         )
     }
 
     #[test]
     pub fn computes_interval() {
-        let pair = parse_line("Sensor at x=8, y=7: closest beacon is at x=2, y=10");
+        let pair = parse_line("Sensor at x=8, y=7: closest beacon is at x=2, y=10").unwrap();
         assert_eq!((2, 14), pair.visible_at_line(10).unwrap());
     }
 
     #[test]
     pub fn computes_intervals() {
-        let pairs = parse_input(&read_file("input/day15-test.txt"));
+        let pairs = parse_input(&read_file("input/day15-test.txt").unwrap()).unwrap();
 
         let intervals = intervals_at_line(&pairs, 11);
 
-        assert_eq!(vec![(-3, 13), (15, 25)], interval_union(intervals));
+        assert_eq!(
+            vec![(-3, 13), (15, 25)],
+            intervals.intervals().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![(14, 14)], intervals.gaps().collect::<Vec<_>>());
     }
 
     #[test]
     pub fn computes_impossible() {
-        let pairs = parse_input(&read_file("input/day15-test.txt"));
+        let pairs = parse_input(&read_file("input/day15-test.txt").unwrap()).unwrap();
 
-        let intervals = intervals_at_line(&pairs, 10);
+        assert_eq!(26, impossible_positions(&pairs, 10));
+    }
 
-        assert_eq!(26, count_intervals(interval_union(intervals)));
+    #[test]
+    pub fn sensors_just_reaching_the_line_count() {
+        // the sensor reaches a single cell of line 2, on line 1 its beacon takes one of three
+        let pairs = parse_input("Sensor at x=0, y=0: closest beacon is at x=1, y=1\n").unwrap();
+        assert_eq!(1, impossible_positions(&pairs, 2));
+        assert_eq!(2, impossible_positions(&pairs, 1));
     }
 
     #[test]
     pub fn scan_lines() {
-        let pairs = parse_input(&read_file("input/day15-test.txt"));
+        let pairs = parse_input(&read_file("input/day15-test.txt").unwrap()).unwrap();
         for i in 0..21 {
-            let intervals = intervals_at_line(&pairs, i);
-            let vec = interval_union(intervals);
-            println!("{:?}", vec);
-            println!("{}", count_intervals_with_bounds(vec, 0, 20));
+            let seen = intervals_at_line(&pairs, i).clip(0, 20).len();
+            assert_eq!(if i == 11 { 20 } else { 21 }, seen);
         }
+        // the beacon is at x = 14, y = 11
+        assert_eq!(Some(11 + 20 * 14), tuning_frequency(&pairs, 20));
+    }
+
+    #[test]
+    pub fn sensors_are_drawn() {
+        let text = draw(&read_file("input/day15-test.txt").unwrap())
+            .unwrap()
+            .to_string();
+        assert_eq!(14, text.matches("<polygon").count());
+        assert_eq!(14, text.matches(">S</text>").count());
+        assert_eq!(6, text.matches(">B</text>").count());
+        // the sensor at 8,7 sees up to 9 steps away
+        assert!(text.contains(r#"<polygon points="8,-2 17,7 8,16 -1,7""#));
     }
 }
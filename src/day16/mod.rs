@@ -1,10 +1,10 @@
-use crate::day16::Action::{Valve, Walk};
-use crate::read_file;
-use regex::{Captures, Regex};
-use std::collections::HashMap;
+use crate::error::Context;
+use crate::parse::{separated, Scanner};
+use crate::random::Random;
+use crate::search::dijkstra;
+use crate::{Answer, Error, Result, Solution};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
-use std::fs::File;
-use std::io::{Error, Write};
 
 #[derive(PartialEq, Copy, Clone)]
 pub struct Edge {
@@ -12,16 +12,10 @@ pub struct Edge {
     weight: u32,
 }
 
-pub enum Action {
-    Valve(NodeId),
-    Walk(NodeId, NodeId, u32),
-}
-
 #[derive(PartialEq)]
 pub struct Node {
     id: NodeId,
     flow: u32,
-    valve_open: bool,
     neighbours: Vec<Edge>,
 }
 
@@ -41,7 +35,6 @@ impl Node {
         Node {
             id,
             flow,
-            valve_open: flow < 1,
             neighbours,
         }
     }
@@ -73,19 +66,37 @@ impl Debug for Edge {
     }
 }
 
-fn parse_input(input: &str) -> HashMap<NodeId, Node> {
+fn parse_input(input: &str) -> Result<HashMap<NodeId, Node>> {
     let mut nodes: HashMap<NodeId, Node> = HashMap::new();
     let mut node_ids: Vec<NodeId> = vec![];
 
     for line in input.lines() {
-        let node = parse_line(line);
+        let node = parse_line(line).within(input, line)?;
         node_ids.push(node.id);
         nodes.insert(node.id, node);
     }
 
+    if !nodes.contains_key(&make_id("AA")) {
+        return Err(Error::invalid("No valve AA to start from"));
+    }
+    for node in nodes.values() {
+        if let Some(edge) = node
+            .neighbours
+            .iter()
+            .find(|edge| !nodes.contains_key(&edge.to))
+        {
+            return Err(Error::invalid(&format!(
+                "Valve {} leads to unknown valve {}",
+                make_node_string(node.id),
+                make_node_string(edge.to)
+            )));
+        }
+    }
+
     for id in node_ids {
         let node = nodes.get(&id).unwrap();
-        if node.flow == 0 && id != make_id("AA") {
+        // only plain corridors can be shortened into a single edge
+        if node.flow == 0 && id != make_id("AA") && node.neighbours.len() == 2 {
             let x = node.neighbours[0].to;
             let y = node.neighbours[1].to;
             let weight = node.neighbours[0].weight + node.neighbours[1].weight;
@@ -99,170 +110,180 @@ fn parse_input(input: &str) -> HashMap<NodeId, Node> {
         }
     }
 
-    nodes
+    Ok(nodes)
 }
 
-pub fn parse_line(input: &str) -> Node {
-    let regex =
-        Regex::new(r"Valve ([A-Z][A-Z]) has flow rate=(\d+); tunnels? leads? to valves? (.*)$")
-            .unwrap();
+/// A valve is named by two capital letters
+fn parse_valve(name: &str) -> Result<NodeId> {
+    match name.len() == 2 && name.chars().all(|c| c.is_ascii_uppercase()) {
+        true => Ok(make_id(name)),
+        false => Err(Error::parse(name, "expected a valve name like `AA`")),
+    }
+}
 
-    let cap: Captures = regex.captures(input).unwrap();
+pub fn parse_line(input: &str) -> Result<Node> {
+    let mut scanner = Scanner::new(input);
+    scanner.literal("Valve ")?;
+    let name = scanner.word()?;
+    let id = parse_valve(name).within(input, name)?;
+    scanner.literal(" has flow rate=")?;
+    let flow = scanner.number()?;
+    scanner.literal("; ")?;
+    scanner.one_of(&["tunnels lead to valves ", "tunnel leads to valve "])?;
+    let tunnels = scanner.take_rest();
+    let neighbours = separated(tunnels, ", ", |name| {
+        Ok(Edge {
+            to: parse_valve(name)?,
+            weight: 1,
+        })
+    })
+    .within(input, tunnels)?;
+
+    Ok(Node::new(id, flow, neighbours))
+}
 
-    Node::new(
-        make_id(&cap[1]),
-        cap[2].parse().unwrap(),
-        cap[3]
-            .split(", ")
-            .map(|c| Edge {
-                to: make_id(c),
-                weight: 1,
-            })
-            .collect(),
-    )
+/// The minutes it takes to walk from the start and from every valve worth opening to all the
+/// other valves worth opening
+fn travel_times(nodes: &HashMap<NodeId, Node>) -> HashMap<NodeId, HashMap<NodeId, u32>> {
+    let start = make_id("AA");
+    nodes
+        .values()
+        .filter(|node| node.flow > 0 || node.id == start)
+        .map(|node| {
+            let distances = dijkstra(node.id, |id| {
+                nodes[id]
+                    .neighbours
+                    .iter()
+                    .map(|edge| (edge.to, edge.weight as u64))
+                    .collect::<Vec<_>>()
+            });
+            let times = nodes
+                .values()
+                .filter(|other| other.flow > 0 && other.id != node.id)
+                .filter_map(|other| Some((other.id, distances.get(&other.id)? as u32)))
+                .collect();
+            (node.id, times)
+        })
+        .collect()
 }
 
-fn walk(
-    nodes: &mut HashMap<NodeId, Node>,
-    current_node: NodeId,
-    previous_node: NodeId,
+/// The most pressure which can be released in the remaining `steps` minutes, walking from
+/// `current` to one closed valve after the other
+fn release(
+    nodes: &HashMap<NodeId, Node>,
+    times: &HashMap<NodeId, HashMap<NodeId, u32>>,
+    current: NodeId,
     steps: u32,
-    open_valves: u32,
-) -> (u32, Vec<Action>) {
-    if steps < 1 {
-        return (0, vec![]);
-    }
-
-    if open_valves as usize == nodes.len() {
-        println!("All valves open!");
-        return (0, vec![]);
+    opened: &mut HashSet<NodeId>,
+) -> u32 {
+    let mut max_score = 0;
+    for (&valve, &time) in &times[&current] {
+        // walking there and opening the valve only pays off if there's time left afterwards
+        let left = match steps.checked_sub(time + 1) {
+            Some(left) if left > 0 && !opened.contains(&valve) => left,
+            _ => continue,
+        };
+        opened.insert(valve);
+        let score = nodes[&valve].flow * left + release(nodes, times, valve, left, opened);
+        opened.remove(&valve);
+        max_score = max_score.max(score);
     }
+    max_score
+}
 
-    let mut max_score: u32 = 0;
-    let mut best_steps: Vec<Action> = vec![];
+fn make_node_string(p0: NodeId) -> String {
+    [char::from_u32(p0 / 256), char::from_u32(p0 % 256)]
+        .into_iter()
+        .flatten()
+        .collect::<String>()
+}
 
-    if !nodes.get(&current_node).unwrap().valve_open {
-        nodes
-            .entry(current_node)
-            .and_modify(|node| node.valve_open = true);
-        let node = nodes.get(&current_node).unwrap();
-        let score = node.flow * (steps - 1);
-        let neighbours = node.neighbours.clone();
-        for neighbour in neighbours {
-            // makes only sense if we can walk AND open the valve AND there's still time after
-            if steps > 1 + neighbour.weight {
-                let (score, done_steps) = walk(
-                    nodes,
-                    neighbour.to,
-                    0,
-                    steps - 1 - neighbour.weight,
-                    open_valves + 1,
-                );
-                if score > max_score {
-                    best_steps = done_steps;
-                    best_steps.push(Walk(current_node, neighbour.to, neighbour.weight));
-                    best_steps.push(Valve(current_node));
-                    max_score = score;
-                }
-            }
+/// `size` valves connected by tunnels, about a third of them worth opening
+fn generate(size: usize, random: &mut Random) -> String {
+    let mut names: Vec<String> = ('A'..='Z')
+        .flat_map(|a| ('A'..='Z').map(move |b| format!("{}{}", a, b)))
+        .filter(|name| name != "AA")
+        .collect();
+    random.shuffle(&mut names);
+    names.truncate(size.clamp(2, 26 * 26) - 1);
+    names.insert(0, "AA".to_string());
+
+    let count = names.len();
+    let mut tunnels: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); count];
+    let mut connect = |a: usize, b: usize| {
+        if a != b {
+            tunnels[a].insert(b);
+            tunnels[b].insert(a);
         }
-        nodes
-            .entry(current_node)
-            .and_modify(|node| node.valve_open = false);
-        max_score += score;
+    };
+    // a tree reaches every valve, the other tunnels make loops
+    for valve in 1..count {
+        connect(valve, random.index(valve));
     }
-
-    let neighbours = nodes.get(&current_node).unwrap().neighbours.clone();
-
-    for neighbour in neighbours {
-        if neighbour.to != previous_node {
-            // makes only sense if we can walk AND open the valve AND there's still time after
-            if steps > 1 + neighbour.weight {
-                let (score, done_steps) = walk(
-                    nodes,
-                    neighbour.to,
-                    current_node,
-                    steps - neighbour.weight,
-                    open_valves,
-                );
-                if score > max_score {
-                    best_steps = done_steps;
-                    best_steps.push(Walk(current_node, neighbour.to, neighbour.weight));
-                    max_score = score;
-                }
-            }
-        }
+    for _ in 0..count / 3 {
+        connect(random.index(count), random.index(count));
     }
 
-    (max_score, best_steps)
+    let mut valves: Vec<String> = (0..count)
+        .map(|valve| {
+            let flow = match valve > 0 && random.chance(0.3) {
+                true => random.range(1..=25),
+                false => 0,
+            };
+            let others: Vec<&str> = tunnels[valve]
+                .iter()
+                .map(|other| names[*other].as_str())
+                .collect();
+            let tunnels = match others.len() {
+                1 => "tunnel leads to valve",
+                _ => "tunnels lead to valves",
+            };
+            format!(
+                "Valve {} has flow rate={}; {} {}\n",
+                names[valve],
+                flow,
+                tunnels,
+                others.join(", ")
+            )
+        })
+        .collect();
+    random.shuffle(&mut valves);
+    valves.concat()
 }
 
-pub fn run() {
-    println!("You must be kidding, how can this be solved? ... Wait ...");
-    let mut nodes = parse_input(&read_file("input/day16.txt"));
-
-    //    output_dotfile(&mut nodes);
-    let (score, steps) = walk(&mut nodes, make_id("AA"), 0, 30, 0);
-    println!("Here, take that score: {}\n", score);
-    for s in steps {
-        match s {
-            Valve(node_id) => {
-                println!("Open valve: {}", make_node_string(node_id))
-            }
-            Walk(from_id, node_id, weight) => {
-                println!(
-                    "Walk from {} to {} (Costs: {})",
-                    make_node_string(from_id),
-                    make_node_string(node_id),
-                    weight
-                )
-            }
-        }
-    }
-
-    generate_dot_file(&nodes).expect("Couldnt write file");
+pub fn part1(input: &str) -> Result<u32> {
+    let nodes = parse_input(input)?;
+    let times = travel_times(&nodes);
+    Ok(release(
+        &nodes,
+        &times,
+        make_id("AA"),
+        30,
+        &mut HashSet::new(),
+    ))
 }
 
-fn generate_dot_file(nodes: &HashMap<NodeId, Node>) -> Result<(), Error> {
-    let path = "output/day16.dot";
-
-    let mut output = File::create(path)?;
+pub struct Day16;
 
-    writeln!(output, "digraph {}", "{")?;
-
-    for (id, node) in nodes {
-        writeln!(
-            output,
-            "{} [label =\"{} {}\"]",
-            make_node_string(*id),
-            make_node_string(*id),
-            node.flow
-        )?;
-
-        for neighbour in &node.neighbours {
-            writeln!(
-                output,
-                "{} -> {} [label = {}]",
-                make_node_string(*id),
-                make_node_string(neighbour.to),
-                neighbour.weight
-            )?;
-        }
+impl Solution for Day16 {
+    fn day(&self) -> u32 {
+        16
+    }
+    fn title(&self) -> &'static str {
+        "Proboscidea Volcanium"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
     }
-    writeln!(output, "{}", "}")?;
-
-    Ok(())
-}
-
-fn make_node_string(p0: NodeId) -> String {
-    [char::from_u32(p0 / 256), char::from_u32(p0 % 256)]
-        .into_iter()
-        .flatten()
-        .collect::<String>()
-}
-
-pub fn run2() {
-    println!("Sorry, won't let animals work!")
 }
 
 #[cfg(test)]
@@ -273,12 +294,19 @@ mod test {
     #[test]
     fn parses_line() {
         //assert_eq!(Node { id: "AA".to_string(), flow: 0, neighbours: vec!["DD".to_string(), "II".to_string(), "BB".to_string()] }, parse_line("Valve AA has flow rate=0; tunnels lead to valves DD, II, BB"))
+        let error =
+            parse_input("Valve AA has flow rate=0; tunnels lead to valves BB\nValve BB has flow=1")
+                .unwrap_err();
+        assert_eq!("2:18: expected `has flow rate=` `=1`", error.to_string());
     }
 
     #[test]
     fn parses_input() {
-        let mut nodes = parse_input(&read_file("input/day16-test.txt"));
+        let nodes = parse_input(&read_file("input/day16-test.txt").unwrap()).unwrap();
+        let times = travel_times(&nodes);
+        assert_eq!(Some(&2), times[&make_id("AA")].get(&make_id("JJ")));
 
-        let score = walk(&mut nodes, make_id("AA"), 0, 30, 0);
+        let score = release(&nodes, &times, make_id("AA"), 30, &mut HashSet::new());
+        assert_eq!(1651, score);
     }
 }
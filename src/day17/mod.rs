@@ -1,5 +1,17 @@
+use crate::animation::{Animation, Frame, Palette, View};
+use crate::random::Random;
+use crate::{cycle, Answer, Error, Grid, Point, Result, Solution};
+use ansi_term::Colour::Red;
+use ansi_term::Style;
 use std::fmt::{Display, Formatter};
 
+/// A row with all seven cells taken
+const FULL_ROW: u8 = 0b1111111;
+
+/// How deep the [surface](Board::surface) is followed down. The rocks of the puzzle input get
+/// down about 90 rows, only a column which stays empty goes further.
+const SURFACE_ROWS: usize = 256;
+
 struct Board {
     rows: Vec<u8>,
     base_height: usize,
@@ -33,10 +45,8 @@ impl Board {
         false
     }
     pub fn settle(&mut self, piece: Piece, row: usize) {
-        let mut cutoff: u8 = 0;
         for (i, piece_line) in piece.rows.iter().enumerate() {
-            self.rows[row - i] = self.rows[row - i] | piece_line;
-            cutoff = cutoff | self.rows[row - i];
+            self.rows[row - i] |= piece_line;
         }
 
         while self.rows[self.rows.len() - 1] == 0 {
@@ -47,19 +57,57 @@ impl Board {
             self.base_height += 9000;
         }
     }
+
+    /// The free cells a falling rock can get to from above, row by row from the top down to the
+    /// last row it reaches, but at most [`SURFACE_ROWS`] deep. Rocks only move sideways and down,
+    /// so unless a shaft goes deeper than that, this is all of the board that matters for the
+    /// rocks still to come.
+    pub fn surface(&self) -> Vec<u8> {
+        let mut surface = vec![];
+        let mut reachable = FULL_ROW;
+        for row in self.rows.iter().rev().take(SURFACE_ROWS) {
+            let free = !row & FULL_ROW;
+            reachable &= free;
+            loop {
+                let spread = (reachable | reachable << 1 | reachable >> 1) & free;
+                if spread == reachable {
+                    break;
+                }
+                reachable = spread;
+            }
+            if reachable == 0 {
+                break;
+            }
+            surface.push(reachable);
+        }
+        surface
+    }
+}
+
+impl Board {
+    /// The settled rocks as `#`, the highest row first
+    pub fn to_grid(&self) -> Grid<char> {
+        let rows = self
+            .rows
+            .iter()
+            .rev()
+            .map(|row| {
+                (0..7)
+                    .rev()
+                    .map(|bit| match row & (1 << bit) {
+                        0 => '.',
+                        _ => '#',
+                    })
+                    .collect()
+            })
+            .collect();
+        Grid::from_rows(rows).expect("all rows are 7 wide")
+    }
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.rows
-                .iter()
-                .map(|row| format!("{:b}", row + 128))
-                .collect::<Vec<String>>()
-                .join("\n")
-        )
+        write!(f, "{}", self.to_grid())
     }
 }
 
@@ -105,30 +153,66 @@ impl Piece {
     }
 }
 
-pub fn get_move(move_number: usize) -> char {
-    //let pattern = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
-    let pattern = ">><<<<>><>>>><><<>>>><<<>>>><<<<>>><>>>><<<<>>>><<><><<<><<><<<<><<<<>>>><<>>><<<>><<>>><<><<<>>>><>>><<><>><<>>><<<<><>><<>>><<<<><<<<><>><<<<>>><<<<>>>><<<>><><<<<>>><>><<<>><><<<>>><<<<>>><>>>><<<><<>>>><><<<<>>>><<<>>>><>>>><>>>><><<><<>>>><<<>>><<<>>>><><<<><<<<>><>>><<<<><<<><>><><><<><<><<<<>>>><<<<>>><>>><<<><<<<>>><<<<>>>><<<<>>><>>>><<<><<<<>>><<>><>><>><><>>>><<>>><>>>><<<<>><<<>>>><<>><<<>>>><><<<<><>><>>>><>><<<<>>>><<<>>>><>><>>><><<<<>><><<<><<<<>>><<><<<>>><<<<><<>>><<>><<<<><<>>>><<><>>><<><<<>>><<<<><<<<>><>>><<<>>><<<<>>>><<><<>><<<>><<>>>><<<>>>><<>><<>><<><<<<>><<<<>>><<><<<<>>><>><<>><<<<><<<><<<>>>><<<<>><>>>><<<<>><<>>><<>>>><<<<><<<><<>>><>>><<<>>>><>><<><><<<<>>>><<<<>>>><<<<><<<>>><<>><>>>><>>>><<<<>><<<<><<><>><<<<>><<<>>>><<>>>><<<<><<<>>><<<<>>><<>>><<<>>><<><<><<<>>><><<>>><<<>>>><>>>><<<><<<<><<>><><<>>>><><<<<>>><<><<<<><<<<><<<<>>>><<>><<<><<<>><<>>><<>><<<<><<><<<>>><<>><<<><>><<><<<<><<<<>><<>><<><>>><>>><<<>><<<>>>><>>><>><<><<>><><<<<><<<<>>><<<<>>><<<<><<<>>><<<>>>><<<>><<>><><<><<<<><>>>><>>>><<<<><>>><<<>><<<>>>><<<<>><<<<>><<<<>>>><>><<>>>><<<>>><<><<<>>>><<<><<><<<>>>><<<>>>><<>><<<<>>>><><<<>>><>>>><<<>>>><<>><<<<>>>><><<<>><<<>>><<>>><>>>><<>>><><<<<><>>>><><<<<>>>><<<<>><<<>>>><<<>><<<>><<<>>>><><<<<>>><<>>>><<>><><<<>>><<<>><<<>>>><>>>><<>>><<<<>>><<<>><><<<>>><<<<>>>><<>><<>>>><<<<>>><<<><>>><<<>>>><<<<>><<<<><<<<><<<<>>><<<>>><<<>>>><><>>>><>>><<>>><><>>><<>>><>><><<<>>>><<<>><<<<>>><<>>>><<>>><<<>><<<<>><>>><<<>>>><<<>>><<<>>><><><<<>>><<<>><<<>><<<<>><>>><<><>>><<>>><<>>><<<><>>><<<<>>><<<>>><<<>>><>>><<>><<>>>><><<<<>><>>>><<<<><<><<<<>>><>><>>>><<>>>><<>>>><<<>><>>><><<<<>>><><<>><<<>><<<<><<<><<<<><<<>><<>>>><<<<>>><>>><<<<><<<>><<<>>><<>>>><<<<>>>><<>>><><><<<<>>>><>>>><<<<>>><><<<><<<>>><>>><<<<><<<<><>>>><>>>><<><<<>>>><<>>><>>><<<<>>><<<<><<>>>><<<>>><<<<>>><>><<<><>>>><<>>><<<>><<<<>>>><<<>>><<<<><<<<><<>><>>><>><<<<><>>><>>>><<>><>>><<><<<><<>><<<>><>>><<<<>>>><>>>><<<<>>><<<><<<<>>>><<<<>>><<<<>>>><>>><<<>><<>>>><<<<>>>><<<<><<>>>><<<>><>>><<>><><<<<>><<<<>>><<<><<>>><>><<<><>><<>>><>>>><<>>><>>><>><<<><<>>>><<<<><<>><<<<><<<><<<<>>><<<<>>><<<>><<<<>>><<<<>>>><<><<>>><<<<><<>><<<<><<><<<<>>>><<<><<<<>><<<<><>>>><<><<<<>><>>>><><<<>>>><<<>><<<<>>>><<><<<<>><<>><<<<><<<><<>>><<>><>><<><<><>><<<<>>><<>>><>>><<<><<<<><<>>>><<>>><<>><>>>><><<<<><<>><>>><><<>>><<>>>><<<<>>><<<<>>>><<<>><<<<><<<>>><<<>><<<<><<<><><<>>>><<<>>>><<<>>>><<<>>><<>>>><<>>>><<<<>>><<>>><<<>><<>><<>>>><<<>>>><<<<><<>><>>><<<<>>>><<<><<>><>>>><>>>><>>>><<>><<<><<><>><<<>><<<<>><><<>><<>><<>>><><<<>><>><<><<<<><>>><>><<<>>>><<<>>><>>>><<>><><<>><<>>>><<<<><>>>><<>><<>>><<<>>>><<<<>><<>>><<<<>>>><>>><<<>><<<<>>>><<<<>>><<<>><<>>>><<<><<<<>><<<<>><<><<<>>><<>><<<><<<>>>><<<<>>>><<<<>><<<<>><><<>><<<<>>>><<<<>><<>>>><<>><>>>><<<><>><<><>><<>><><<><>>><<<>>>><>>><<>><>><>>>><<<<><<>><<<<><>>>><<>>><<>>><<><>>>><>><<<><<<><>>>><><<><<<<><<><<<<>>>><><<<<>>>><<<>><<>><<<<>>>><<<<>>>><<<>>>><>>><<<>>>><<<>><<<<>>>><<<>><<>>><<<<>>><<><<<<>>>><>><<<>><<>>>><<<<>>>><<>>><<<<>><><<<<>>><<<>>>><<>>>><><>>>><<>>><>>><<<>>><<<>><<<>><<<<><><<><<<<>><>>><>>><><>>><<<>>><<>><<><<<>>>><<<>>><>><><<>>>><<<><>><<<<>><<<><>>><<><<<<>>>><<>>>><<<<>><<<<>><<<<>>>><<<<>>><<<<><<<>><<>><<<<>><<><<<<>><<<<>>><<<<><<<<>>>><<><<<><<<<>>>><<>>><<<<>>>><<<<><<>>><>>><<<><<<<>><<<>>><>>>><><>>>><><>>><<<>>>><<<><>>>><<<>><<<><<>><<><><<<<><<>>><<>><<><<<>>>><<<>>><>>><>><<<<>>><<><<<<>><>>><<<>>>><<>><>>><<<>>><<<>><<<<>>>><>>><>>>><>>><<<>>><<<>>>><<<>><<<>><<<><><>>>><<<<>>>><<<<><>>><><<<<>>><<<<>>><<><><><<>><<><<<<>>><>>>><<>>><<>>>><<<>>><<<>><<<<><>>><>><<<<><<>><<<<><<<<>>>><<<>>><>>>><<<<>>>><<<<><<<<>>>><<<<>>>><<<>><<<<>>>><<>><<<>>>><<<>>>><<<<>><<<><>>>><<><>>>><<<<><>>><><<<><>>>><<>><<<<>><>><<<<>>>><<<<>><<>>><><<<><<<<>>>><>>>><<>><<<><<>>><<>><>><<>><<><<<<>><>><<<<>>>><<<<>><<<<>>><<><<>><>>><<<<>>>><<>>>><<><<>>>><<>><<>>>><<<>><<<<>>>><>>>><<<<><<<><><<<><<<<>><<<><<>><<<<>><<<<>>><<<><<<>><<>><<<>>><<><<>><<>>><<<>>>><>>><<<>>>><<>>><<<<>>>><><>>><<<<>>>><>>>><<<>>><<>>>><<<>><>><>><<<>>><<<>>>><<<>>><<<>>>><<>>>><<<><>><<>>><<<<>>>><><<<<><<>><>><>>><>>>><<>>><<<<>>><<<<><<<>>>><<<<>>>><<<<>>><>><>><<<<>>><<<<>>>><<>>>><<<<><>><<>>>><>>><<>><<<>>><<<>><<<><<<<>>>><<<<>><<>><<>>>><<<<>>>><>><<>>><<<<><<<><><<<>>><<>>><<<>>><<<>><<>>><>><<<>>>><<>>>><<<<>>>><<>><>>>><<<<><>>><<<<>>>><<>>>><<<<>>><<<<>>><<<<>><<>>><<>>>><<>>><<<><<<>>><<>>>><<<<><<>><<<<>>>><>>>><>><><<><<><<<<>><<<><<>>><>>><>>><<<<><<<<><<<<>>>><<>><>>><>>><<<><>>>><>>><>>><<<>><<<><<<>><<<<>>>><<<>>>><<<>><>><<>><<<>>>><<<<>>>><<<>>><><>><<><<<>><<<<>>><<>><<>><<<<>>><<<>>>><<>>>><<<<>>><<<>>><<<><>>>><<<<>><>><<<<>>>><<>>><<<>><><<<><<<<><>>>><>><><>><<<>>>><<<>>><<>>>><><<<<><<<>><<<<>>><<<><<><<<>><>>><><<<>>>><<<<>>>><<<>><>><<<>><<<>><<><<<<>>>><<>>><<<>>><>>>><<<>><<<<>>><<><>><<<<><<<<>><<<>><><>><<<><<<><><<<<>>>><<<><<<<><<<>>>><<>><<><><>>>><><>>><<>>><<<>><<<><<<<>>>><<<><<>><<<<>>><>><<><<>>>><<<>>><<<><<<>>><><<<<>>>><<><>><>>><<>>><<>>>><<>>><<>>>><>><><<<<>><>>><<<>>><<<>><<>>><<<<>><>>><<>>>><<<>>><>>><<<>><<<><<<>>><<><<<>>>><<>><<<<>>><<<>>><<<>>>><>><<<>>><<<<>>>><<><>>>><>>>><<<<>><<<>>>><>>>><<<>><<<<>><<><<>>>><<<<>>><<<<>>><<>><<<<>><<>>><<>>>><>><<>>><<>>>><>>>><>>><<<><<>><<<<>>>><<<>><<<<>>>><<<>><<<><<<>>><<<>><>>><<<<>>>><>>>><<<>><<<<>>><>>>><>><<><><><<<<>>>><<<<><>>><<>><<<>><<<>><<><<>><>><<<><<<>><<<>>><<<>><<>>><<<>>><<<<><>>><>>>><><<>>>><>>>><<<<>>><>>><<<<>>><>>>><>>>><<<<>>>><<>>>><<<>>><<>><<>>><<>>>><<>><>>>><<<>><<<><<><<<>><<<<>>><<>>>><<<<><>><<<>>>><<<>>>><<>><<>>>><<><>><>>><<<<>><<<>>>><<<>>>><<<<>>>><>>>><>>><<<<>>>><<<<><<>>>><<<<>>><<<<>><<>><>>><<<>><<<><<<<>>><><>><>>>><><<><<<<>>>><><<<>><<>>><<<<>>><<<<><>>><<>><>><<<><><>><<>>><<><><<><>><<>>>><<<<><<>><<<><<<><<>>><<<<><>><>>><<<><<<>><<><<<>><<<<>>>><<<>>><<>>>><><>><>>>><>>><>>>><>>><<<<>>>><<<>>><<<<>>>><>><<<<><<>><>><<>>><<<<>>>><<<>>><<<>><<<>><<>><<<<><>>>><>>>><<>>><<<><>><>><<<<>>><>>>><>><<>>>><<<<>><>>><<>>><<<<>>>><<><<<<>>>><<><<>><><>>>><>>><>>>><>>><<><>>><<>>><<<>>><<<<>>>><>>><<>><>><>>>><<>><<<>>><<<>>>><>><<>>><<>>><<><<>>>><<<<>>>><>><<<<>>>><<<<>>>><<>><<>>>><<<<>>>><<<<>><>>><>>>><<<>><>>>><<<><<>>>><<>>><<>>>><<><<<><<<<><<<>>><<>>><<<>>>><<><<<><><<>><<<<><<<<>>><<><<<<>><<<<>>>><<<>>>><<<>><<<<>>><>>><<<<>>><<<>>><<<<>><<<<>>><<>>><<<>><<<<>>><>>><<<>><<<<><<<<>>><<><<<<>>>><<<>>>><<>>><<><<<>>>><>>>><<>>>><>><<>><<>>>><<<<>>><<<>>>><<><<<><>><<<<>>><><>><<>>><<<>><<<>>><<<<><<<>>><<<<>>><<<<>><<><<<<>>>><<<><>><<>><>>>><>><<>><><<<>><<<>><<<<>>>><<<>>><<><<>>>><<>>>><<>>><<>><<<<>>>><<<<>><<>>>><<<>>>><<<<>>><<><<<<>>>><<<><>>><<<>><><>>>><><<<>>>><<<<>><<>><<<>><<><<><<<<>><><<<<>>><<><<>><<<>>>><<<>>><<>>><<<<>><<><<<<>>><<<<><><<<>>><<<><<<<>>><<<<>>><<<<>>><<<<>>><<>>><<<<>>><>><<<>><<<<>>>><><>>>><<><<><<<<>><><<<>>><><<<><<<>>>><<>><<<<>>><<<<><<<>>>><<>>>><<<<>><<<>>>><>>><<<>>>><<<>>>><<<>>><>>>><<<<>><>><<>><><<<<><<<>>>><<<>>><>>>><>>>><<<><<<>>><<>>>><>>><><<<<>><<>>><<<>><<>>><<<<><<<<>>>><<><<<><>>>><<<>>><<>>>><<<<>>>><<<<>>>><>><<<<>>><>>>><<>>>><<<><<>><<<>>><<<><<>>><<<<>>><<<<><<>>>><<>><<<>>><<<<>><<><<<<>>>><<><<>>><<<>>>><<<><<>><<<>>>><<><<>>>><>><<<<>>>><<>><>><<>>><<<><<<<><<<<>>><>><><>>><<<>>><<>><><<>><<<>>><<<>><<<<>>><<<<><<<><>><>>><<<<>>>><<<<>><<<<>><>>>><>>>><>>>><<<<>><>>>><<>><<<>>><<>><><>>>><<><<<<>><<>>><<>>><<>>><<<<>>>><<<<>><<<>>><<><<<<>><<<<>><<><>>>><<<>>>><<<><<>><<<<>>><<<<><>>>><<<<>>>><<>>>><<>><>>><>>><<><<<<>><<<<>><><<>><>><>>><<><<<>>>><<<>>><<<<>>>><<><<><>>><<<<><><<>>>><<<<>><<<<>><<>><<>>>><<>>>><<>>><<<>>>><<>>>><<<<>><>><>><<>>>><<<>>>><<<<>><<<>>><<>>><<<<>>>><>>>><<>>><<>>>><<<<><<>>><<<<>><<<>>>><><<<>>>><<<>>><<<<>>><<<<><<><<><<<<>>><<<>><>>><>><<<<>>><<<<>>><<><<<<>>><>>><<<>>><<>>>><<<>><<>>>><<><<<>><<<>>><<<<>>>><<<<>>>><>>><<>>><<<><<<>>>><<>>>><<><<<><>>><<>><<><<<<>>><>><>>><<<><><<><<<>>><<<><><<>>><<><<>><>>>><>><<<>>><<<>>>><<<>><<<<>>><>>>><<<<>><<<<><<<<><<<><>><<<<><<>>><<<<>>>><<<>>><<<>>>><>>>><<<<><<<<><<<<><<>><><<<<>>>><<<<>><<<>>>><<<>><<<>>>><<<>>>><<<><<>>><>><<<>>><<>><<<>>><>><<<>>><<<<>><>>>><>>>><><>>>><<<>>>><<<<><<><<><>><>>>><><<>><<<>>><<<<><>><<>><>>>><><<<>>>><<<>>>><<>>>><>>><>>>><<<>>><>><<<<><<<<>>>><<<<>><<<>>><<>>><<<<>>><<<>><>>><<><<<><<>>><<>><<>>><<<<>>><<<>>>><<<<><<<<>>>><<<>>>><<<<>>>><<><>><<<><><<>>>><><<<>>><<>>><<>>>><<<<>>><<<>>><<<<>>>><<>>><<<>><>>>><<>><<<><<><<<>><<<<>>><><>>>><><<><><<>>>><<>>>><>><><>>>><<<><<<<>>>><<<>><<<>><<<>>><<<>><<<>>><<<><<<>>>><<<<><<>><<<>>><<<<>>>><<<<>><<<<>>>><>>><<><<>>><><<<>><<><<<<>>><<<<>>><<<<>><<<>>><<<<><>><<>>><<<<>>><<<>><<<>><<<>>><><<<>><<>>><>><>><<>>><<<>>>><<<>>>><<>>><>><>><<>>><<>>>><<>>><><<<>><<>>><<>><<>><<<<>>>><>>>><<>><<<><<>><<<<>>>><<<<>><<>><>><<<>><<<<><<><<>>>><<>>>><<<>><<<<>>><<<<><<<>><<<>><>>>><>>>><<><>>>><<<>>><<<<>>><<<>><<<<>>><<<><<<>>><>>><<<<>><<>>>><<>><<<>><<<>>>><<<<>><<>>><<<<>><<>>>><<><<<>>><<<<>><>>>><<<<>>>><>>>><>>><<<>>><<<>>><>><>>>><<<<>>><<<<>>><<>>><>>><><<<>><<<<>>>><>>>><<<>><>>>><<>>>><<<><<>>><<<<>>>><<><>>><<<<>><<<>>><>>><<>>><<<><>>><<><<<>>>><<<<>><>>>><<><<>><>>><<>><>>>><<<<>>>><<<<>>><<>><<><><<<<>>>><<>>><<<>>>><<<>>><<<<>><<<<>><<>>>><<<<><<>>><<<><<<>><<<<>>><<<<>><<<>>><<><<>><<<<>>><<<<>>><<><>>><<<><<<<>>>><<<<>>>><>><>>><<<>>>><<<><<>>><<>><<<<>>><<<<><<<><<<>>><<>><>><>>>><>>>><><<<><><<<<><>>>><<>>><>>>><<>>><<<<>>>><<>>><>>>><<<>><><<>>>><<>>><<<>>><<<><<<>><<<>>><<<>>><<><<>><<<>>><<<<>>><<>>><>>><<<<>>><<>>>><<<><<<<>>><>><<<<>><>>><<>><>>>><<><<<<>>><<<<>><<>>><<<>>>><<<<>>><<<>><>>>><<<<>>><>>>><<>><>>><><>>><>>>><<<<>><<<><><<>><>><<<><<>>><<>>><<>><<<>>><<<<>><>><<<>><<<<>>>><<>>><>><<<<>>><<<><<>><>>>><<><<>>><<<>><><<<>>>><<<<><<<<>>>><<<>>><<<><>>><<<>>><<<<>><<<><<>>>><<<<>>>><<>><><<>>><<<<>>><>><<<<>>><>>>><>>><<<<>>><<><<<>>>><<<><<<>>><<<<>>><<<>>>><<<>><>><<<<>>>><<<<>><<<><>><<<><<>>>><<<>><<<<>>><<<<>>>><>><<<>>><<>>><<><<>><<<<>><<<<>><>><<<>>>><<<>>><<<>>><<>>>><<>>>><<>><<>><<>><<>><<>><<><>><>>><<<>>><<><<<<>>>><><<<>><<<<>>>><>><>>>><<><<<><>>";
-    pattern.chars().nth(move_number % pattern.len()).unwrap()
+pub fn get_move(pattern: &[char], move_number: usize) -> char {
+    pattern[move_number % pattern.len()]
+}
+
+fn parse_pattern(input: &str) -> Result<Vec<char>> {
+    let pattern = input.trim();
+    if let Some(index) = pattern.find(|c| c != '<' && c != '>') {
+        let text = &pattern[index..pattern.len().min(index + 1)];
+        return Err(Error::parse(text, "expected `<` or `>`").within(input, text));
+    }
+    if pattern.is_empty() {
+        return Err(Error::invalid("The jet pattern is empty"));
+    }
+    Ok(pattern.chars().collect())
+}
+
+/// The board together with the position in the rock and jet patterns
+struct Chamber {
+    board: Board,
+    pattern: Vec<char>,
+    pieces: Vec<Piece>,
+    current_block_type: usize,
+    current_move: usize,
 }
 
-pub fn run() {
-    let mut board = Board::new();
-    let pieces: Vec<Piece> = vec![
-        Piece::new(vec![0b0011110]),
-        Piece::new(vec![0b0001000, 0b0011100, 0b0001000]),
-        Piece::new(vec![0b0000100, 0b0000100, 0b0011100]),
-        Piece::new(vec![0b0010000, 0b0010000, 0b0010000, 0b0010000]),
-        Piece::new(vec![0b0011000, 0b0011000]),
-    ];
+impl Chamber {
+    fn new(pattern: Vec<char>) -> Chamber {
+        Chamber {
+            board: Board::new(),
+            pattern,
+            pieces: vec![
+                Piece::new(vec![0b0011110]),
+                Piece::new(vec![0b0001000, 0b0011100, 0b0001000]),
+                Piece::new(vec![0b0000100, 0b0000100, 0b0011100]),
+                Piece::new(vec![0b0010000, 0b0010000, 0b0010000, 0b0010000]),
+                Piece::new(vec![0b0011000, 0b0011000]),
+            ],
+            current_block_type: 0,
+            current_move: 0,
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.board.rows.len() + self.board.base_height
+    }
 
-    let mut current_block_type: usize = 0;
-    let mut current_move = 0;
-    let mut block_count = 0;
+    /// Everything which decides how the next rocks fall
+    fn fingerprint(&self) -> (usize, usize, Vec<u8>) {
+        (
+            self.current_block_type,
+            self.current_move,
+            self.board.surface(),
+        )
+    }
 
-    // loop
-    loop {
+    /// Lets the next rock fall until it settles
+    fn drop_rock(&mut self) {
+        let board = &mut self.board;
         // new rock
-        let mut rock = Piece::copy(&pieces[current_block_type]);
+        let mut rock = Piece::copy(&self.pieces[self.current_block_type]);
         // board: add 3+height rock lines
         board.add_rows(rock.rows.len() + 3);
         // set rock-y to board-height
@@ -136,23 +220,19 @@ pub fn run() {
         // loop
         loop {
             // shift left/right
-            let movetype = get_move(current_move);
-            current_move += 1;
+            let movetype = get_move(&self.pattern, self.current_move);
+            self.current_move = (self.current_move + 1) % self.pattern.len();
             match movetype {
-                '>' => {
-                    if rock.can_shift_right() {
-                        rock.shift_right();
-                        if board.overlapping(&rock, current_position) {
-                            rock.shift_left();
-                        }
+                '>' if rock.can_shift_right() => {
+                    rock.shift_right();
+                    if board.overlapping(&rock, current_position) {
+                        rock.shift_left();
                     }
                 }
-                '<' => {
-                    if rock.can_shift_left() {
-                        rock.shift_left();
-                        if board.overlapping(&rock, current_position) {
-                            rock.shift_right();
-                        }
+                '<' if rock.can_shift_left() => {
+                    rock.shift_left();
+                    if board.overlapping(&rock, current_position) {
+                        rock.shift_right();
                     }
                 }
                 _ => {}
@@ -165,86 +245,161 @@ pub fn run() {
                 // fall down: decrease rock-y
                 current_position -= 1;
             } else {
-                block_count += 1;
                 board.settle(rock, current_position);
-                //println!("{}\n", board);
                 break;
             }
         }
 
-        current_block_type = (current_block_type + 1) % pieces.len();
-        if block_count == 2022 {
-            break;
-        }
+        self.current_block_type = (self.current_block_type + 1) % self.pieces.len();
     }
-    println!("{}", board.rows.len() + board.base_height);
 }
 
-pub fn run2() {
-    let mut board = Board::new();
-    let pieces: Vec<Piece> = vec![
-        Piece::new(vec![0b0011110]),
-        Piece::new(vec![0b0001000, 0b0011100, 0b0001000]),
-        Piece::new(vec![0b0000100, 0b0000100, 0b0011100]),
-        Piece::new(vec![0b0010000, 0b0010000, 0b0010000, 0b0010000]),
-        Piece::new(vec![0b0011000, 0b0011000]),
-    ];
+fn tower_height(input: &str, rock_count: usize) -> Result<usize> {
+    let mut chamber = Chamber::new(parse_pattern(input)?);
+    let initial = (chamber.fingerprint(), 0);
 
-    let mut current_block_type: usize = 0;
-    let mut current_move = 0;
-    let mut block_count: usize = 0;
+    let height = cycle::extrapolate(rock_count, initial, || {
+        chamber.drop_rock();
+        (chamber.fingerprint(), chamber.height() as i64)
+    });
+    Ok(height as usize)
+}
 
-    // loop
-    loop {
-        // new rock
-        let mut rock = Piece::copy(&pieces[current_block_type]);
-        // board: add 3+height rock lines
-        board.add_rows(rock.rows.len() + 3);
-        // set rock-y to board-height
-        let mut current_position = board.rows.len() - 1;
-        // loop
-        loop {
-            // shift left/right
-            let movetype = get_move(current_move);
-            current_move += 1;
-            match movetype {
-                '>' => {
-                    if rock.can_shift_right() {
-                        rock.shift_right();
-                        if board.overlapping(&rock, current_position) {
-                            rock.shift_left();
-                        }
-                    }
-                }
-                '<' => {
-                    if rock.can_shift_left() {
-                        rock.shift_left();
-                        if board.overlapping(&rock, current_position) {
-                            rock.shift_right();
-                        }
-                    }
-                }
-                _ => {}
-            }
-            // if can fall down
-            if current_position < rock.rows.len() - 1
-                || (current_position > rock.rows.len() - 1
-                    && !board.overlapping(&rock, current_position - 1))
-            {
-                // fall down: decrease rock-y
-                current_position -= 1;
-            } else {
-                block_count += 1;
-                board.settle(rock, current_position);
-                //println!("{}\n", board);
-                break;
-            }
-        }
+/// Rock and air
+fn palette() -> Palette {
+    Palette::new()
+        .with('#', Red.normal())
+        .with('.', Style::new().dimmed())
+}
 
-        current_block_type = (current_block_type + 1) % pieces.len();
-        if block_count >= 1_000_000 {
-            break;
-        }
+/// Shows the top of the tower after every rock of part 1
+fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let mut chamber = Chamber::new(parse_pattern(input)?);
+    animation.set_palette(palette());
+    for rock in 1..=2022 {
+        chamber.drop_rock();
+        let caption = format!("rock {}, the tower is {} high", rock, chamber.height());
+        let frame = Frame::new(chamber.board.to_grid())
+            .with_focus(Point::new(3, 0))
+            .with_caption(&caption);
+        animation.show(&frame)?;
+    }
+    Ok(())
+}
+
+/// The whole tower of part 1, from its top down to the floor
+fn view(input: &str) -> Result<View> {
+    let mut chamber = Chamber::new(parse_pattern(input)?);
+    for _ in 0..2022 {
+        chamber.drop_rock();
+    }
+    let caption = format!("after 2022 rocks the tower is {} high", chamber.height());
+    let frame = Frame::new(chamber.board.to_grid())
+        .with_focus(Point::new(3, 0))
+        .with_caption(&caption);
+    Ok(View::new(frame, palette()))
+}
+
+/// A pattern of `size` jets
+fn generate(size: usize, random: &mut Random) -> String {
+    let jets: String = (0..size.max(1))
+        .map(|_| *random.pick(&['<', '>']))
+        .collect();
+    jets + "\n"
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    tower_height(input, 2022)
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    tower_height(input, 1_000_000_000_000)
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    fn day(&self) -> u32 {
+        17
+    }
+    fn title(&self) -> &'static str {
+        "Pyroclastic Flow"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_pattern(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+    fn animate(&self, input: &str, animation: &mut Animation) -> Result<()> {
+        animate(input, animation)
+    }
+    fn view(&self, input: &str) -> Result<View> {
+        view(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read_file;
+
+    #[test]
+    fn board_is_drawn() {
+        let mut board = Board::new();
+        board.add_rows(2);
+        board.settle(Piece::new(vec![0b0011110]), 0);
+        board.add_rows(1);
+        board.settle(Piece::new(vec![0b0001000]), 1);
+        assert_eq!("...#...\n..####.", board.to_string());
+    }
+
+    #[test]
+    fn surface_is_what_rocks_can_reach() {
+        let board = |rows: Vec<u8>| Board {
+            rows,
+            base_height: 0,
+        };
+        assert_eq!(vec![0b0001111], board(vec![0b1110000]).surface());
+        // the cells left of the gap are covered
+        assert_eq!(vec![0b0000001], board(vec![0b0000001, 0b1111110]).surface());
+        // below an overhang the rock can still move sideways
+        assert_eq!(
+            vec![0b0000011, 0b1111111],
+            board(vec![0b0000000, 0b1111100]).surface()
+        );
+    }
+
+    #[test]
+    fn towers_are_extrapolated() {
+        let input = read_file("input/day17-test.txt").unwrap();
+        assert_eq!(3068, tower_height(&input, 2022).unwrap());
+        assert_eq!(
+            1514285714288,
+            tower_height(&input, 1_000_000_000_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn every_rock_is_animated() {
+        let mut animation = Animation::sink();
+        animate(&read_file("input/day17-test.txt").unwrap(), &mut animation).unwrap();
+        assert_eq!(2022, animation.frames());
+    }
+
+    #[test]
+    fn the_tower_is_viewed() {
+        let view = view(&read_file("input/day17-test.txt").unwrap()).unwrap();
+        assert_eq!(3068, view.frame.grid.height());
+        assert_eq!(
+            "after 2022 rocks the tower is 3068 high",
+            view.frame.caption
+        );
     }
-    println!("{}", board.rows.len() + board.base_height);
 }
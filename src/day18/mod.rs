@@ -1,228 +1,203 @@
-use crate::read_file;
-use std::fmt;
-use std::fmt::Debug;
-
-#[derive(Debug)]
-struct Voxel {
-    x: i32,
-    y: i32,
-    z: i32,
+use crate::animation::{Frame, Palette, View};
+use crate::error::{parse_number, Context};
+use crate::random::Random;
+use crate::search::{bfs, Distances};
+use crate::{Answer, Error, Grid, Point, Point3, Result, Solution};
+use ansi_term::Colour::{Cyan, Red};
+use ansi_term::Style;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+type Voxel = Point3<i32>;
+
+fn parse_voxel(line: &str) -> Result<Voxel> {
+    let parts: Vec<&str> = line.split(',').collect();
+    if parts.len() != 3 {
+        return Err(Error::parse(line, "expected a voxel like `2,2,2`"));
+    }
+    let coordinate = |part: &str| parse_number(part).within(line, part);
+
+    Ok(Voxel::new(
+        coordinate(parts[0])?,
+        coordinate(parts[1])?,
+        coordinate(parts[2])?,
+    ))
 }
 
-struct Space {
-    width: i32,
-    height: i32,
-    depth: i32,
-    voxels: Vec<SpaceVoxel>,
-    x: i32,
-    y: i32,
-    z: i32,
-}
-
-#[derive(Clone)]
-struct SpaceVoxel {
-    visited: bool,
-    is_outside: bool,
-}
+fn parse_input(input: &str) -> Result<Vec<Voxel>> {
+    let mut voxels = vec![];
 
-impl Debug for SpaceVoxel {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.visited)
+    for line in input.lines() {
+        voxels.push(parse_voxel(line).within(input, line)?);
     }
+
+    Ok(voxels)
 }
 
-impl Debug for Space {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut space = vec![];
-        space.push(format!(
-            "Width: {}/Height: {}/Depth: {}",
-            self.width, self.height, self.depth
-        ));
-        for z in 0..self.depth {
-            for y in 0..self.height {
-                let mut row = vec![];
-                for x in 0..self.width {
-                    row.push(
-                        match self
-                            .get_visited(x + self.x, y + self.y, z + self.z)
-                            .unwrap()
-                        {
-                            true => '#',
-                            false => '.',
-                        },
-                    );
-                }
-                space.push(row.into_iter().collect::<String>())
+fn count_hidden_faces(voxels: &[Voxel]) -> usize {
+    let mut hidden_faces = 0;
+    for i in 0..voxels.len() {
+        for j in i + 1..voxels.len() {
+            if voxels[i].manhattan(&voxels[j]) == 1 {
+                hidden_faces += 1;
             }
-            space.push("".to_string());
         }
-
-        write!(f, "{}", space.join("\n"))
     }
+    hidden_faces
 }
 
-impl SpaceVoxel {
-    pub fn new() -> SpaceVoxel {
-        SpaceVoxel {
-            visited: false,
-            is_outside: false,
-        }
-    }
+fn surface_area(input: &str) -> Result<usize> {
+    let voxels = parse_input(input)?;
+
+    let hidden_faces = count_hidden_faces(&voxels);
+    Ok(6 * voxels.len() - 2 * hidden_faces)
 }
 
-impl Space {
-    pub fn new(x: i32, y: i32, z: i32, width: i32, height: i32, depth: i32) -> Space {
-        Space {
-            width,
-            height,
-            depth,
-            x,
-            y,
-            z,
-            voxels: vec![SpaceVoxel::new(); (width * height * depth) as usize],
-        }
-    }
+/// The box around the droplet, with one voxel of space on every side so steam can flow around it
+struct Bounds {
+    xs: RangeInclusive<i32>,
+    ys: RangeInclusive<i32>,
+    zs: RangeInclusive<i32>,
+}
 
-    pub fn set_visited(&mut self, x: i32, y: i32, z: i32, visited: bool) {
-        if self.coordinates_valid(x, y, z) {
-            let index = self.get_vec_index(x, y, z);
-            self.voxels[index].visited = visited;
+impl Bounds {
+    fn new(voxels: &[Voxel]) -> Bounds {
+        let bounds = |coordinate: fn(&Voxel) -> i32| {
+            let min = voxels.iter().map(coordinate).min().unwrap_or(0) - 1;
+            let max = voxels.iter().map(coordinate).max().unwrap_or(0) + 1;
+            min..=max
+        };
+        Bounds {
+            xs: bounds(Voxel::x),
+            ys: bounds(Voxel::y),
+            zs: bounds(Voxel::z),
         }
     }
-    pub fn set_is_outside(&mut self, x: i32, y: i32, z: i32, is_outside: bool) {
-        if self.coordinates_valid(x, y, z) {
-            let index = self.get_vec_index(x, y, z);
-            self.voxels[index].is_outside = is_outside;
-        }
-    }
-    pub fn get_visited(&self, x: i32, y: i32, z: i32) -> Option<bool> {
-        if self.coordinates_valid(x, y, z) {
-            return Some(self.voxels[self.get_vec_index(x, y, z)].visited);
-        }
-        None
-    }
-
-    fn get_vec_index(&self, x: i32, y: i32, z: i32) -> usize {
-        (((x - self.x) * self.height + y - self.y) * self.depth + (z - self.z)) as usize
-    }
 
-    pub fn coordinates_valid(&self, x: i32, y: i32, z: i32) -> bool {
-        x >= self.x
-            && x < self.x + self.width
-            && y >= self.y
-            && y < self.y + self.height
-            && z >= self.z
-            && z < self.z + self.depth
+    fn contains(&self, voxel: &Voxel) -> bool {
+        self.xs.contains(&voxel.x) && self.ys.contains(&voxel.y) && self.zs.contains(&voxel.z)
     }
 }
 
-impl Voxel {
-    pub fn new(x: i32, y: i32, z: i32) -> Voxel {
-        Voxel { x, y, z }
-    }
-
-    pub fn manhatten(&self, other: &Voxel) -> i32 {
-        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
-    }
+/// Fills the box around the droplet with steam, starting in one of its corners
+fn steam(lava: &HashSet<Voxel>, bounds: &Bounds) -> Distances<Voxel> {
+    let start = Voxel::new(*bounds.xs.start(), *bounds.ys.start(), *bounds.zs.start());
+    bfs(start, |voxel| {
+        voxel
+            .neighbours6()
+            .filter(|neighbour| bounds.contains(neighbour) && !lava.contains(neighbour))
+            .collect::<Vec<_>>()
+    })
 }
 
-fn parse_input(input: String) -> Vec<Voxel> {
-    let mut voxels = vec![];
-
-    for line in input.lines() {
-        let mut parts = line.split(",");
-        let x = parts.next().unwrap().parse().unwrap();
-        let y = parts.next().unwrap().parse().unwrap();
-        let z = parts.next().unwrap().parse().unwrap();
-        voxels.push(Voxel::new(x, y, z));
-    }
-
-    voxels
+/// Counts the faces which can be reached from outside, by filling the box around the droplet with
+/// steam and counting where the steam touches lava
+fn exterior_surface_area(input: &str) -> Result<usize> {
+    let voxels = parse_input(input)?;
+    if voxels.is_empty() {
+        return Ok(0);
+    }
+    let lava: HashSet<Voxel> = voxels.iter().copied().collect();
+    let steam = steam(&lava, &Bounds::new(&voxels));
+
+    Ok(voxels
+        .iter()
+        .flat_map(|voxel| voxel.neighbours6())
+        .filter(|neighbour| steam.contains(neighbour))
+        .count())
 }
 
-fn count_hidden_faces(voxels: &Vec<Voxel>) -> usize {
-    let mut hidden_faces = 0;
-    for i in 0..voxels.len() {
-        for j in i + 1..voxels.len() {
-            if voxels[i].manhatten(&voxels[j]) == 1 {
-                hidden_faces += 1;
+/// The droplet cut into slices along z, laid out from left to right: lava as `#`, the air pockets
+/// inside as `o` and the steam around as `.`
+fn view(input: &str) -> Result<View> {
+    let voxels = parse_input(input)?;
+    if voxels.is_empty() {
+        return Err(Error::invalid("There are no cubes to view"));
+    }
+    let lava: HashSet<Voxel> = voxels.iter().copied().collect();
+    let bounds = Bounds::new(&voxels);
+    let steam = steam(&lava, &bounds);
+
+    // the space around the droplet is left out
+    let inner = |range: &RangeInclusive<i32>| range.start() + 1..=range.end() - 1;
+    let (xs, ys, zs) = (inner(&bounds.xs), inner(&bounds.ys), inner(&bounds.zs));
+    let width = xs.clone().count();
+    let slices = zs.clone().count();
+    let mut grid = Grid::new(slices * (width + 1) - 1, ys.clone().count(), ' ');
+    for (slice, z) in zs.clone().enumerate() {
+        for (column, x) in xs.clone().enumerate() {
+            for (row, y) in ys.clone().enumerate() {
+                let voxel = Voxel::new(x, y, z);
+                let cell = match (lava.contains(&voxel), steam.contains(&voxel)) {
+                    (true, _) => '#',
+                    (false, true) => '.',
+                    (false, false) => 'o',
+                };
+                grid[Point::new((slice * (width + 1) + column) as i64, row as i64)] = cell;
             }
         }
     }
-    hidden_faces
-}
-
-pub fn run() {
-    let voxels = parse_input(read_file("input/day18.txt"));
-
-    let hidden_faces = count_hidden_faces(&voxels);
-    println!(
-        "It looks like the lava surface has {} units ",
-        6 * voxels.len() - 2 * hidden_faces
+    let caption = format!(
+        "z = {} to {} from left to right, # lava, o air pockets, . steam",
+        zs.start(),
+        zs.end()
     );
+    let palette = Palette::new()
+        .with('#', Red.normal())
+        .with('o', Cyan.bold())
+        .with('.', Style::new().dimmed());
+    Ok(View::new(Frame::new(grid).with_caption(&caption), palette))
 }
 
-pub fn run2() {
-    let mut voxels = parse_input(read_file("input/day18.txt"));
+/// `size` cubes filling two thirds of a box, so there are pockets of air between them
+fn generate(size: usize, random: &mut Random) -> String {
+    let side = ((size as f64 * 1.5).cbrt().ceil() as i64).max(1);
+    let mut cubes = HashSet::new();
+    let mut lines = String::new();
+    while cubes.len() < size {
+        let cube = Voxel::new(
+            random.range(0..=side - 1) as i32,
+            random.range(0..=side - 1) as i32,
+            random.range(0..=side - 1) as i32,
+        );
+        if cubes.insert(cube) {
+            lines += &format!("{},{},{}\n", cube.x, cube.y, cube.z);
+        }
+    }
+    lines
+}
 
-    let min_x = voxels.iter().map(|voxel| voxel.x).min().unwrap() - 1;
-    let max_x = voxels.iter().map(|voxel| voxel.x).max().unwrap() + 1;
-    let width = max_x - min_x + 1;
+pub fn part1(input: &str) -> Result<usize> {
+    surface_area(input)
+}
 
-    let min_y = voxels.iter().map(|voxel| voxel.y).min().unwrap() - 1;
-    let max_y = voxels.iter().map(|voxel| voxel.y).max().unwrap() + 1;
-    let height = max_y - min_y + 1;
+pub fn part2(input: &str) -> Result<usize> {
+    exterior_surface_area(input)
+}
 
-    let min_z = voxels.iter().map(|voxel| voxel.z).min().unwrap() - 1;
-    let max_z = voxels.iter().map(|voxel| voxel.z).max().unwrap() + 1;
-    let depth = max_z - min_z + 1;
+pub struct Day18;
 
-    let mut space = Space::new(min_x, min_y, min_z, width, height, depth);
-    for voxel in &voxels {
-        space.set_visited(voxel.x, voxel.y, voxel.z, true);
+impl Solution for Day18 {
+    fn day(&self) -> u32 {
+        18
     }
-    //println!("{:?}", space);
-    floodfill(&mut space, min_x, min_y, min_z);
-    //println!("{:?}", space);
-
-    for x in min_x..=max_x {
-        for y in min_y..=max_y {
-            for z in min_z..=max_z {
-                if !space.get_visited(x, y, z).unwrap() {
-                    voxels.push(Voxel { x, y, z });
-                }
-            }
-        }
+    fn title(&self) -> &'static str {
+        "Boiling Boulders"
     }
-    let hidden_faces = count_hidden_faces(&voxels);
-    println!(
-        "An this is the size of the exterior area: {} units",
-        6 * voxels.len() - 2 * hidden_faces
-    );
-}
-
-fn floodfill(space: &mut Space, x: i32, y: i32, z: i32) {
-    if !space.coordinates_valid(x, y, z) {
-        return;
-    }
-    space.set_is_outside(x, y, z, true);
-    space.set_visited(x, y, z, true);
-    for i in [
-        (0, 1, 0),
-        (0, -1, 0),
-        (-1, 0, 0),
-        (1, 0, 0),
-        (0, 0, 1),
-        (0, 0, -1),
-    ] {
-        match space.get_visited(x + i.0, y + i.1, z + i.2) {
-            None => {}
-            Some(visited) => {
-                if !visited {
-                    floodfill(space, x + i.0, y + i.1, z + i.2);
-                }
-            }
-        }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+    fn view(&self, input: &str) -> Result<View> {
+        view(input)
     }
 }
 
@@ -230,12 +205,42 @@ fn floodfill(space: &mut Space, x: i32, y: i32, z: i32) {
 mod test {
     use super::*;
 
+    /// A 3x3x3 cube with a hole in the middle
+    fn hollow_cube() -> String {
+        (0..27)
+            .filter(|i| *i != 13)
+            .map(|i| format!("{},{},{}", i % 3, i / 3 % 3, i / 9))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn air_pockets_are_ignored() {
+        let input = hollow_cube();
+        assert_eq!(60, surface_area(&input).unwrap());
+        assert_eq!(54, exterior_surface_area(&input).unwrap());
+    }
+
+    #[test]
+    fn broken_voxel_is_reported() {
+        let error = parse_input("1,1,1\n2,1").unwrap_err();
+        assert_eq!(
+            "2:1: expected a voxel like `2,2,2` `2,1`",
+            error.to_string()
+        );
+    }
+
     #[test]
-    fn test_floodfill() {
-        let mut space = Space::new(0, 0, 0, 3, 2, 1);
-        space.set_visited(1, 0, 0, true);
-        space.set_visited(1, 1, 0, true);
-        floodfill(&mut space, 0, 0, 0);
-        println!("{:?}", space);
+    fn slices_are_viewed() {
+        let sliced = view(&hollow_cube()).unwrap();
+        assert_eq!(
+            "### ### ###\n### #o# ###\n### ### ###",
+            sliced.frame.grid.to_string()
+        );
+        assert_eq!(
+            "z = 0 to 2 from left to right, # lava, o air pockets, . steam",
+            sliced.frame.caption
+        );
+        assert!(view("").is_err());
     }
 }
@@ -1,10 +1,11 @@
-use crate::read_file;
+use crate::parse::{lines, numbers};
+use crate::random::Random;
+use crate::{Answer, Result, Solution};
 use rayon::prelude::*;
-use regex::{Captures, Regex};
-use std::cmp::{max};
+use std::cmp::max;
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug,Clone)]
+#[derive(Debug, Clone)]
 struct GameState {
     blueprint_id: u32,
 
@@ -39,8 +40,14 @@ impl Display for GameState {
         write!(
             f,
             "[{},{},{},{} / {},{},{},{}]",
-            self.ores, self.clays, self.obsidians, self.geodes,
-            self.ore_robots, self.clay_robots, self.obsidian_robots, self.geode_robots
+            self.ores,
+            self.clays,
+            self.obsidians,
+            self.geodes,
+            self.ore_robots,
+            self.clay_robots,
+            self.obsidian_robots,
+            self.geode_robots
         )
     }
 }
@@ -74,7 +81,10 @@ impl GameState {
             obsidians: 0,
             geodes: 0,
 
-            max_ore_robots: max(max(ore_cost, max(clay_cost,obsidian_ore_cost)), geode_ore_cost),
+            max_ore_robots: max(
+                max(ore_cost, max(clay_cost, obsidian_ore_cost)),
+                geode_ore_cost,
+            ),
             max_clay_robots: obsidian_clay_cost,
             max_obsidian_robots: geode_obsidian_cost,
         }
@@ -100,7 +110,11 @@ impl GameState {
         }
         Some(max(
             self.time_to(self.geode_ore_cost, self.ores, self.ore_robots)?,
-            self.time_to(self.geode_obsidian_cost, self.obsidians, self.obsidian_robots)?,
+            self.time_to(
+                self.geode_obsidian_cost,
+                self.obsidians,
+                self.obsidian_robots,
+            )?,
         ))
     }
     pub fn time_to_obsidian(&self) -> Option<u32> {
@@ -125,171 +139,198 @@ impl GameState {
         }
         let missing = need - have;
         let mut time = missing / multi;
-        if missing % multi > 0 {
+        if !missing.is_multiple_of(multi) {
             time += 1;
         }
         Some(time)
     }
 }
 
-fn parse_input(input: String) -> Vec<GameState> {
-    let mut game_state = vec![];
-
-    for line in input.lines() {
-        game_state.push(parse_line(line));
-    }
-
-    game_state
+fn parse_input(input: &str) -> Result<Vec<GameState>> {
+    lines(input, parse_line)
 }
-fn parse_line(line: &str) -> GameState {
-    let regex = Regex::new(r"Blueprint (\d+): Each ore robot costs (\d+) ore. Each clay robot costs (\d+) ore. Each obsidian robot costs (\d+) ore and (\d+) clay. Each geode robot costs (\d+) ore and (\d+) obsidian.").unwrap();
-
-    let cap: Captures = regex.captures(line).unwrap();
 
-    GameState::new(
-        cap[1].parse().unwrap(),
-        cap[2].parse().unwrap(),
-        cap[3].parse().unwrap(),
-        cap[4].parse().unwrap(),
-        cap[5].parse().unwrap(),
-        cap[6].parse().unwrap(),
-        cap[7].parse().unwrap(),
-    )
+fn parse_line(line: &str) -> Result<GameState> {
+    let [id, ore_ore, clay_ore, obsidian_ore, obsidian_clay, geode_ore, geode_obsidian] = numbers(
+        line,
+        "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+        Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.",
+    )?;
+    Ok(GameState::new(
+        id,
+        ore_ore,
+        clay_ore,
+        obsidian_ore,
+        obsidian_clay,
+        geode_ore,
+        geode_obsidian,
+    ))
 }
 
-fn run_simulation(
-    game_state: &mut GameState,
-    steps: u32,
-) -> u32 {
+fn run_simulation(game_state: &mut GameState, steps: u32) -> u32 {
     let mut max_score: u32 = 0;
 
     // 1. do nothing
-    max_score = max(max_score, steps  * game_state.geode_robots + game_state.geodes);
-
+    max_score = max(
+        max_score,
+        steps * game_state.geode_robots + game_state.geodes,
+    );
 
     // 2. try building geode
-    match game_state.time_to_geode() {
-        None => {}
-        Some(time) => {
-            if time < steps {
-                game_state.collect_materials(time + 1);
-                game_state.ores -= game_state.geode_ore_cost;
-                game_state.obsidians -= game_state.geode_obsidian_cost;
-                game_state.geode_robots += 1;
-                let score= run_simulation(game_state, steps - time - 1);
-                if score > max_score {
-                    max_score = score;
-
-                }
-                game_state.geode_robots -= 1;
-                game_state.ores += game_state.geode_ore_cost;
-                game_state.obsidians += game_state.geode_obsidian_cost;
-                game_state.uncollect_materials(time + 1);
+    if let Some(time) = game_state.time_to_geode() {
+        if time < steps {
+            game_state.collect_materials(time + 1);
+            game_state.ores -= game_state.geode_ore_cost;
+            game_state.obsidians -= game_state.geode_obsidian_cost;
+            game_state.geode_robots += 1;
+            let score = run_simulation(game_state, steps - time - 1);
+            if score > max_score {
+                max_score = score;
             }
+            game_state.geode_robots -= 1;
+            game_state.ores += game_state.geode_ore_cost;
+            game_state.obsidians += game_state.geode_obsidian_cost;
+            game_state.uncollect_materials(time + 1);
         }
     }
-    match game_state.time_to_obsidian() {
-        None => {}
-        Some(time) => {
-            if time < steps && game_state.obsidian_robots < game_state.max_obsidian_robots {
-                game_state.collect_materials(time + 1);
-                game_state.ores -= game_state.obsidian_ore_cost;
-                game_state.clays -= game_state.obsidian_clay_cost;
-                game_state.obsidian_robots += 1;
-                let score= run_simulation(game_state, steps - time - 1);
-                if score > max_score {
-                    max_score = score;
-
-                }
-                game_state.obsidian_robots -= 1;
-                game_state.ores += game_state.obsidian_ore_cost;
-                game_state.clays += game_state.obsidian_clay_cost;
-                game_state.uncollect_materials(time + 1);
+    if let Some(time) = game_state.time_to_obsidian() {
+        if time < steps && game_state.obsidian_robots < game_state.max_obsidian_robots {
+            game_state.collect_materials(time + 1);
+            game_state.ores -= game_state.obsidian_ore_cost;
+            game_state.clays -= game_state.obsidian_clay_cost;
+            game_state.obsidian_robots += 1;
+            let score = run_simulation(game_state, steps - time - 1);
+            if score > max_score {
+                max_score = score;
             }
+            game_state.obsidian_robots -= 1;
+            game_state.ores += game_state.obsidian_ore_cost;
+            game_state.clays += game_state.obsidian_clay_cost;
+            game_state.uncollect_materials(time + 1);
         }
     }
-    match game_state.time_to_clay() {
-        None => {}
-        Some(time) => {
-            if time < steps && game_state.clay_robots < game_state.max_clay_robots {
-                game_state.collect_materials(time + 1);
-                game_state.ores -= game_state.clay_cost;
-                game_state.clay_robots += 1;
-                let score= run_simulation(game_state, steps - time - 1);
-                if score > max_score {
-                    max_score = score;
-
-                }
-                game_state.clay_robots -= 1;
-                game_state.ores += game_state.clay_cost;
-                game_state.uncollect_materials(time + 1);
+    if let Some(time) = game_state.time_to_clay() {
+        if time < steps && game_state.clay_robots < game_state.max_clay_robots {
+            game_state.collect_materials(time + 1);
+            game_state.ores -= game_state.clay_cost;
+            game_state.clay_robots += 1;
+            let score = run_simulation(game_state, steps - time - 1);
+            if score > max_score {
+                max_score = score;
             }
+            game_state.clay_robots -= 1;
+            game_state.ores += game_state.clay_cost;
+            game_state.uncollect_materials(time + 1);
         }
     }
-    match game_state.time_to_ore() {
-        None => {}
-        Some(time) => {
-            if time < steps && game_state.ore_robots < game_state.max_ore_robots {
-                game_state.collect_materials(time + 1);
-                game_state.ores -= game_state.ore_cost;
-                game_state.ore_robots += 1;
-                let score= run_simulation(game_state, steps - time - 1);
-                if score > max_score {
-                    max_score = score;
-
-                }
-                game_state.ore_robots -= 1;
-                game_state.ores += game_state.ore_cost;
-                game_state.uncollect_materials(time + 1);
+    if let Some(time) = game_state.time_to_ore() {
+        if time < steps && game_state.ore_robots < game_state.max_ore_robots {
+            game_state.collect_materials(time + 1);
+            game_state.ores -= game_state.ore_cost;
+            game_state.ore_robots += 1;
+            let score = run_simulation(game_state, steps - time - 1);
+            if score > max_score {
+                max_score = score;
             }
+            game_state.ore_robots -= 1;
+            game_state.ores += game_state.ore_cost;
+            game_state.uncollect_materials(time + 1);
         }
     }
 
-
     max_score
 }
 
-pub fn run() {
-    let game_states = parse_input(read_file("input/day19.txt"));
-    let final_score = game_states
+fn quality_level_sum(input: &str) -> Result<u32> {
+    let game_states = parse_input(input)?;
+    Ok(game_states
         .par_iter()
         .map(|game_state| {
             let mut gs = game_state.clone();
             let score = run_simulation(&mut gs, 24);
             score * game_state.blueprint_id
         })
-        .sum::<u32>();
-
-    println!("Final score: {}", final_score);
+        .sum::<u32>())
 }
 
-pub fn run2() {
-    let game_states = parse_input(read_file("input/day19.txt"));
-    let final_score = game_states[0..3]
+fn geode_product(input: &str) -> Result<u32> {
+    let game_states = parse_input(input)?;
+    let count = game_states.len().min(3);
+    Ok(game_states[0..count]
         .par_iter()
         .map(|game_state| {
             let mut gs = game_state.clone();
-            let score = run_simulation(&mut gs, 32);
-            score
+
+            run_simulation(&mut gs, 32)
+        })
+        .product::<u32>())
+}
+
+/// `size` blueprints
+fn generate(size: usize, random: &mut Random) -> String {
+    (1..=size.max(1))
+        .map(|id| {
+            format!(
+                "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+                Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.\n",
+                id,
+                random.range(2..=4),
+                random.range(2..=4),
+                random.range(2..=4),
+                random.range(5..=20),
+                random.range(2..=4),
+                random.range(7..=20)
+            )
         })
-        .product::<u32>();
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<u32> {
+    quality_level_sum(input)
+}
+
+pub fn part2(input: &str) -> Result<u32> {
+    geode_product(input)
+}
+
+pub struct Day19;
 
-    println!("Final score: {}", final_score);
+impl Solution for Day19 {
+    fn day(&self) -> u32 {
+        19
+    }
+    fn title(&self) -> &'static str {
+        "Not Enough Minerals"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::day19::Material::Obsidian;
     use crate::read_file;
 
     #[test]
     fn parses_input() {
-        let blueprints: Vec<Blueprint> = parse_input(read_file("input/day19-test.txt"));
-
-        assert_eq!(vec![Ore(4)], blueprints[0].ore_robot);
-        assert_eq!(vec![Ore(2)], blueprints[0].clay_robot);
-        assert_eq!(vec![Ore(3), Clay(14)], blueprints[0].obsidian_robot);
-        assert_eq!(vec![Ore(2), Obsidian(7)], blueprints[0].geode_robot);
+        let blueprints: Vec<GameState> =
+            parse_input(&read_file("input/day19-test.txt").unwrap()).unwrap();
+
+        assert_eq!(4, blueprints[0].ore_cost);
+        assert_eq!(2, blueprints[0].clay_cost);
+        assert_eq!(3, blueprints[0].obsidian_ore_cost);
+        assert_eq!(14, blueprints[0].obsidian_clay_cost);
+        assert_eq!(2, blueprints[0].geode_ore_cost);
+        assert_eq!(7, blueprints[0].geode_obsidian_cost);
     }
 }
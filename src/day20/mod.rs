@@ -1,5 +1,7 @@
+use crate::error::{parse_number, Context};
+use crate::random::Random;
+use crate::{Answer, Error, Result, Solution};
 use std::fmt::{Debug, Formatter};
-use crate::read_file;
 
 struct VisitNumber {
     number: i64,
@@ -13,22 +15,34 @@ impl Debug for VisitNumber {
     }
 }
 
-fn parse_input(input: &str) -> Vec<VisitNumber> {
-    let input = read_file(input);
+fn parse_input(input: &str) -> Result<Vec<VisitNumber>> {
     let mut counter: usize = 0;
 
-    input.lines().map(|c| {
-        counter += 1;
-        VisitNumber {
-            number: c.parse().unwrap(),
-            visited: false,
-            number_in_line: counter,
-        }
-    }).collect::<Vec<VisitNumber>>()
+    let numbers = input
+        .lines()
+        .map(|c| {
+            counter += 1;
+            Ok(VisitNumber {
+                number: parse_number(c).within(input, c)?,
+                visited: false,
+                number_in_line: counter,
+            })
+        })
+        .collect::<Result<Vec<VisitNumber>>>()?;
+
+    if numbers.len() < 2 {
+        return Err(Error::invalid("At least two numbers are needed to mix"));
+    }
+    if !numbers.iter().any(|c| c.number == 0) {
+        return Err(Error::invalid(
+            "The file has no 0 to find the grove coordinates",
+        ));
+    }
+    Ok(numbers)
 }
 
-pub fn run() {
-    let mut numbers = parse_input("input/day20.txt");
+fn mix(input: &str) -> Result<i64> {
+    let mut numbers = parse_input(input)?;
     let mut cursor: i64 = 0;
     let len = (numbers.len() - 1) as i64;
     let mut to_go = len + 1;
@@ -44,7 +58,7 @@ pub fn run() {
             if index < 0 {
                 index = (len + index % len) % len;
             } else if index >= len {
-                index = index % len;
+                index %= len;
             }
             number.visited = true;
             to_go -= 1;
@@ -63,54 +77,93 @@ pub fn run() {
         }
     }
 
-    let zero_position = numbers.iter().position(|c| c.number == 0).unwrap();
-    println!("Result: {}",
-             numbers[(zero_position + 1000) % numbers.len()].number +
-                 numbers[(zero_position + 2000) % numbers.len()].number +
-                 numbers[(zero_position + 3000) % numbers.len()].number
-    );
+    Ok(grove_coordinates(&numbers))
 }
 
-pub fn run2() {
-    let mut numbers = parse_input("input/day20.txt");
+/// Moves every number once, in the order of the file
+fn mix_round(numbers: &mut Vec<VisitNumber>) {
     let len: i64 = (numbers.len() - 1) as i64;
+    for i in 1..=numbers.len() {
+        let cursor = numbers.iter().position(|c| c.number_in_line == i).unwrap();
+
+        if numbers[cursor].number != 0 {
+            let number = numbers.remove(cursor);
+            let mut index: i64 = cursor as i64 + number.number;
+            if index < 0 {
+                index = (len + index % len) % len;
+            } else if index >= len {
+                index %= len;
+            }
+            if index == len {
+                numbers.push(number);
+            } else {
+                numbers.insert(index as usize, number);
+            }
+        }
+    }
+}
+
+fn decrypt(input: &str) -> Result<i64> {
+    let mut numbers = parse_input(input)?;
     for number in numbers.iter_mut() {
         number.number *= 811589153;
-        //number.number = number.number % len;
     }
-    println!("{:?}", numbers);
     for _ in 0..10 {
-        for number in numbers.iter_mut() {
-            number.visited = false;
-        }
-        for i in 1..=numbers.len() {
-            let cursor = numbers.iter().position(|c| c.number_in_line == i).unwrap();
-
-            numbers[cursor].visited = true;
-
-            if numbers[cursor].number != 0 {
-                let number = numbers.remove(cursor);
-                let mut index: i64 = cursor as i64 + number.number;
-                if index < 0 {
-                    index = (len + index % len) % len;
-                } else if index >= len {
-                    index = index % len;
-                }
-                if index == len {
-                    numbers.push(number);
-                } else {
-                    numbers.insert(index as usize, number);
-                }
-            }
-        }
-        println!("{:?}", numbers);
+        mix_round(&mut numbers);
     }
-    println!("{:?}", numbers);
 
+    Ok(grove_coordinates(&numbers))
+}
+
+fn grove_coordinates(numbers: &[VisitNumber]) -> i64 {
     let zero_position = numbers.iter().position(|c| c.number == 0).unwrap();
-    println!("Result: {}",
-             numbers[(zero_position + 1000) % numbers.len()].number +
-                 numbers[(zero_position + 2000) % numbers.len()].number +
-                 numbers[(zero_position + 3000) % numbers.len()].number
-    );
+    numbers[(zero_position + 1000) % numbers.len()].number
+        + numbers[(zero_position + 2000) % numbers.len()].number
+        + numbers[(zero_position + 3000) % numbers.len()].number
+}
+
+/// `size` numbers to mix, exactly one of them 0
+fn generate(size: usize, random: &mut Random) -> String {
+    let mut numbers: Vec<i64> = (1..size.max(2))
+        .map(|_| match random.range(-10_000..=9_999) {
+            0 => 10_000,
+            number => number,
+        })
+        .collect();
+    numbers.insert(random.index(numbers.len() + 1), 0);
+    numbers
+        .iter()
+        .map(|number| format!("{}\n", number))
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<i64> {
+    mix(input)
+}
+
+pub fn part2(input: &str) -> Result<i64> {
+    decrypt(input)
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    fn day(&self) -> u32 {
+        20
+    }
+    fn title(&self) -> &'static str {
+        "Grove Positioning System"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
 }
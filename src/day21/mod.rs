@@ -1,8 +1,8 @@
-use std::collections::HashMap;
-use std::process::exit;
-use regex::Regex;
 use crate::day21::Operand::{Value, Variable};
-use crate::read_file;
+use crate::parse::{lines, Scanner};
+use crate::random::Random;
+use crate::{Answer, Error, Result, Solution};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 enum Operator {
@@ -20,7 +20,6 @@ enum Operand {
     Variable(String),
 }
 
-
 #[derive(Debug, Clone)]
 struct Monkey {
     id: String,
@@ -30,171 +29,170 @@ struct Monkey {
     remove: bool,
 }
 
+fn parse_operand(text: &str) -> Operand {
+    match text.parse() {
+        Ok(value) => Value(value),
+        Err(_) => Variable(text.to_string()),
+    }
+}
 
-fn parse_input(input: &str) -> Vec<Monkey> {
-    let input = read_file(input);
-    let mut monkeys = vec![];
-
-    let regex = Regex::new(r"(\w+): ((\d+)|(\w+) (.) (\w+))").unwrap();
-
-    for line in input.lines() {
-        let cap = regex.captures(line).unwrap();
-        match cap.get(6) {
-            None => {
-                monkeys.push(Monkey {
-                    id: cap[1].to_string(),
-                    left: Value(cap[2].parse().unwrap()),
-                    right: Value(0),
-                    operator: Operator::Plus,
-                    remove: false,
-                });
-            }
-            Some(_) => {
-                monkeys.push(Monkey {
-                    id: cap[1].to_string(),
-                    left: match cap[4].parse() {
-                        Err(_) => Variable(cap[4].to_string()),
-                        Ok(v) => Operand::Value(v)
-                    },
-                    right: (match cap[6].parse() {
-                        Err(_) => Variable(cap[6].to_string()),
-                        Ok(v) => Operand::Value(v)
-                    }),
-                    operator: match &cap[5] {
-                        "+" => Operator::Plus,
-                        "-" => Operator::Minus,
-                        "*" => Operator::Multiply,
-                        "/" => Operator::Divide,
-                        _ => {
-                            println!("Unknown Operaotr: {}", &cap[5]);
-                            exit(1);
-                        }
-                    },
-                    remove: false,
-                });
-            }
-        }
+fn parse_line(line: &str) -> Result<Monkey> {
+    let mut scanner = Scanner::new(line);
+    let id = scanner.word()?.to_string();
+    scanner.literal(": ")?;
+    if scanner.rest().starts_with(|c: char| c.is_ascii_digit()) {
+        let value = scanner.number()?;
+        scanner.end()?;
+        return Ok(Monkey {
+            id,
+            left: Value(value),
+            right: Value(0),
+            operator: Operator::Plus,
+            remove: false,
+        });
     }
 
-    monkeys
+    let left = parse_operand(scanner.word()?);
+    scanner.literal(" ")?;
+    let operator = scanner.until(" ")?;
+    let operator = match operator {
+        "+" => Operator::Plus,
+        "-" => Operator::Minus,
+        "*" => Operator::Multiply,
+        "/" => Operator::Divide,
+        _ => return Err(scanner.error(operator, "unknown operator")),
+    };
+    scanner.literal(" ")?;
+    let right = parse_operand(scanner.word()?);
+    scanner.end()?;
+    Ok(Monkey {
+        id,
+        left,
+        right,
+        operator,
+        remove: false,
+    })
 }
 
+fn parse_input(input: &str) -> Result<Vec<Monkey>> {
+    lines(input, parse_line)
+}
 
+fn position(monkeys: &[Monkey], id: &str) -> Result<usize> {
+    monkeys
+        .iter()
+        .position(|monkey| monkey.id == id)
+        .ok_or_else(|| Error::invalid(&format!("No monkey named {}", id)))
+}
 
-pub fn run() {
-    let mut monkeys = parse_input("input/day21.txt");
+fn root_value(input: &str) -> Result<i64> {
+    let mut monkeys = parse_input(input)?;
     let mut value_map: HashMap<String, i64> = HashMap::new();
 
+    position(&monkeys, "root")?;
     loop {
+        let count = monkeys.len();
         monkeys.retain(|monkey| !monkey.remove);
+        if count == monkeys.len() && !value_map.is_empty() {
+            return Err(Error::invalid("The value of root can't be computed"));
+        }
 
         for monkey in &mut monkeys {
             match &monkey.left {
-                Variable(id) => match value_map.get(id) {
-                    Some(v) => {
+                Variable(id) => {
+                    if let Some(v) = value_map.get(id) {
                         monkey.left = Operand::Value(*v);
                     }
-                    _ => {}
-                },
+                }
                 Operand::Value(_) => {}
             }
             match &monkey.right {
-                Variable(id) => match value_map.get(id) {
-                    Some(v) => {
+                Variable(id) => {
+                    if let Some(v) = value_map.get(id) {
                         monkey.right = Operand::Value(*v);
                     }
-                    _ => {}
-                },
+                }
                 Operand::Value(_) => {}
             }
-            match (&monkey.left, &monkey.right) {
-                (Operand::Value(a), Operand::Value(b)) => {
-                    monkey.remove = true;
-                    match &monkey.operator {
-                        Operator::Plus => {
-                            value_map.insert(monkey.id.clone(), a + b);
-                        }
-                        Operator::Minus => {
-                            value_map.insert(monkey.id.clone(), a - b);
-                        }
-                        Operator::Multiply => {
-                            value_map.insert(monkey.id.clone(), a * b);
-                        }
-                        Operator::Divide => {
-                            value_map.insert(monkey.id.clone(), a / b);
-                        }
-                        _ => {}
+            if let (Operand::Value(a), Operand::Value(b)) = (&monkey.left, &monkey.right) {
+                monkey.remove = true;
+                match &monkey.operator {
+                    Operator::Plus => {
+                        value_map.insert(monkey.id.clone(), a + b);
+                    }
+                    Operator::Minus => {
+                        value_map.insert(monkey.id.clone(), a - b);
                     }
+                    Operator::Multiply => {
+                        value_map.insert(monkey.id.clone(), a * b);
+                    }
+                    Operator::Divide => {
+                        value_map.insert(monkey.id.clone(), a / b);
+                    }
+                    _ => {}
                 }
-                (_, _) => {}
             }
         }
 
         match value_map.get("root") {
             None => {}
             Some(v) => {
-                println!("Root Value is: {}", v);
-                break;
+                return Ok(*v);
             }
         };
     }
 }
 
-pub fn run2() {
-    let mut monkeys = parse_input("input/day21.txt");
+fn humn_value(input: &str) -> Result<i64> {
+    let mut monkeys = parse_input(input)?;
     let mut value_map: HashMap<String, i64> = HashMap::new();
 
-    let root_position = monkeys.iter().position(|monkey| monkey.id == "root".to_string()).unwrap();
+    let root_position = position(&monkeys, "root")?;
     monkeys[root_position].operator = Operator::Equal;
-    let humn_position = monkeys.iter().position(|monkey| monkey.id == "humn".to_string()).unwrap();
+    let humn_position = position(&monkeys, "humn")?;
     monkeys[humn_position].operator = Operator::Player;
 
     loop {
         for monkey in &mut monkeys {
             match &monkey.left {
-                Variable(id) => match value_map.get(id) {
-                    Some(v) => {
+                Variable(id) => {
+                    if let Some(v) = value_map.get(id) {
                         monkey.left = Operand::Value(*v);
                     }
-                    _ => {}
-                },
+                }
                 Operand::Value(_) => {}
             }
             match &monkey.right {
-                Variable(id) => match value_map.get(id) {
-                    Some(v) => {
+                Variable(id) => {
+                    if let Some(v) = value_map.get(id) {
                         monkey.right = Operand::Value(*v);
                     }
-                    _ => {}
-                },
+                }
                 Operand::Value(_) => {}
             }
-            match (&monkey.left, &monkey.right) {
-                (Operand::Value(a), Operand::Value(b)) => {
-                    match &monkey.operator {
-                        Operator::Plus => {
-                            monkey.remove = true;
-                            value_map.insert(monkey.id.clone(), a + b);
-                        }
-                        Operator::Minus => {
-                            monkey.remove = true;
-                            value_map.insert(monkey.id.clone(), a - b);
-                        }
-                        Operator::Multiply => {
-                            monkey.remove = true;
-                            value_map.insert(monkey.id.clone(), a * b);
-                        }
-                        Operator::Divide => {
-                            monkey.remove = true;
-                            value_map.insert(monkey.id.clone(), a / b);
-                        }
-                        Operator::Equal => {
-                            break;
-                        }
-                        Operator::Player => {}
+            if let (Operand::Value(a), Operand::Value(b)) = (&monkey.left, &monkey.right) {
+                match &monkey.operator {
+                    Operator::Plus => {
+                        monkey.remove = true;
+                        value_map.insert(monkey.id.clone(), a + b);
+                    }
+                    Operator::Minus => {
+                        monkey.remove = true;
+                        value_map.insert(monkey.id.clone(), a - b);
+                    }
+                    Operator::Multiply => {
+                        monkey.remove = true;
+                        value_map.insert(monkey.id.clone(), a * b);
+                    }
+                    Operator::Divide => {
+                        monkey.remove = true;
+                        value_map.insert(monkey.id.clone(), a / b);
                     }
+                    Operator::Equal => {
+                        break;
+                    }
+                    Operator::Player => {}
                 }
-                (_, _) => {}
             }
         }
 
@@ -205,7 +203,7 @@ pub fn run2() {
         }
     }
 
-    let current_position = monkeys.iter().position(|monkey| monkey.id == "root".to_string()).unwrap();
+    let current_position = position(&monkeys, "root")?;
     let mut target;
     let mut next_pos;
     match &monkeys[current_position].left {
@@ -235,11 +233,10 @@ pub fn run2() {
     }
 
     loop {
-        if next_pos == "humn".to_string() {
-            println!("You have to yell: {}",target);
-            exit(1);
+        if next_pos == "humn" {
+            return Ok(target);
         }
-        let current_position = monkeys.iter().position(|monkey| monkey.id == next_pos).unwrap();
+        let current_position = position(&monkeys, &next_pos)?;
 
         let monkey: &Monkey = &monkeys[current_position];
         match &monkey.left {
@@ -271,29 +268,168 @@ pub fn run2() {
             Variable(v) => {
                 next_pos = (*v).clone();
                 match &monkey.right {
-                    Value(v) => {
-                        match &monkey.operator {
-                            Operator::Plus => {
-                                target -= v;
-                            }
-                            Operator::Minus => {
-                                target += v;
-                            }
-                            Operator::Multiply => {
-                                target /= v;
-                            }
-                            Operator::Divide => {
-                                target *= v;
-                            }
-                            _ => {}
+                    Value(v) => match &monkey.operator {
+                        Operator::Plus => {
+                            target -= v;
                         }
-                    }
+                        Operator::Minus => {
+                            target += v;
+                        }
+                        Operator::Multiply => {
+                            target /= v;
+                        }
+                        Operator::Divide => {
+                            target *= v;
+                        }
+                        _ => {}
+                    },
                     Variable(_) => {
                         unreachable!();
                     }
                 }
-
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// `size` monkeys. Both sides of `root` start out equal, so `humn` already yells the number part 2
+/// is looking for, and every division works out.
+fn generate(size: usize, random: &mut Random) -> String {
+    let mut names = HashSet::from(["root".to_string(), "humn".to_string()]);
+    let mut jobs = vec![];
+    let others = size.max(3) - 1;
+    let left_count = random.range(1..=others as i64 - 1) as usize;
+    let value = random.range(1..=1000);
+    let left = yell(value, left_count, true, &mut names, &mut jobs, random);
+    let right = yell(
+        value,
+        others - left_count,
+        false,
+        &mut names,
+        &mut jobs,
+        random,
+    );
+    jobs.push(format!("root: {} + {}", left, right));
+    random.shuffle(&mut jobs);
+    jobs.join("\n") + "\n"
+}
+
+/// Adds the jobs of about `count` monkeys which work out `value` together and returns the name
+/// of the monkey which yells it. With `human` set, one of them is `humn`.
+fn yell(
+    value: i64,
+    count: usize,
+    human: bool,
+    names: &mut HashSet<String>,
+    jobs: &mut Vec<String>,
+    random: &mut Random,
+) -> String {
+    if count < 3 && human {
+        jobs.push(format!("humn: {}", value));
+        return "humn".to_string();
+    }
+    let name = loop {
+        let name: String = (0..4)
+            .map(|_| char::from(b'a' + random.range(0..=25) as u8))
+            .collect();
+        if names.insert(name.clone()) {
+            break name;
+        }
+    };
+    if count < 3 {
+        jobs.push(format!("{}: {}", name, value));
+        return name;
+    }
+
+    let divisors: Vec<i64> = (2..=12).filter(|divisor| value % divisor == 0).collect();
+    let mut choices = vec!['-'];
+    if value > 1 {
+        choices.push('+');
+    }
+    if !divisors.is_empty() {
+        choices.push('*');
+    }
+    if value <= 1_000_000_000 {
+        choices.push('/');
+    }
+    let (left, operator, right) = match random.pick(&choices) {
+        '+' => {
+            let left = random.range(1..=value - 1);
+            (left, '+', value - left)
+        }
+        '-' => {
+            let right = random.range(1..=1000);
+            (value + right, '-', right)
+        }
+        '*' => {
+            let divisor = *random.pick(&divisors);
+            (divisor, '*', value / divisor)
+        }
+        _ => {
+            let divisor = random.range(2..=5);
+            (value * divisor, '/', divisor)
+        }
+    };
+    let left_count = random.range(1..=count as i64 - 2) as usize;
+    let human_left = human && random.chance(0.5);
+    let left = yell(left, left_count, human_left, names, jobs, random);
+    let right_human = human && !human_left;
+    let right = yell(
+        right,
+        count - 1 - left_count,
+        right_human,
+        names,
+        jobs,
+        random,
+    );
+    jobs.push(format!("{}: {} {} {}", name, left, operator, right));
+    name
+}
+
+pub fn part1(input: &str) -> Result<i64> {
+    root_value(input)
+}
+
+pub fn part2(input: &str) -> Result<i64> {
+    humn_value(input)
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    fn day(&self) -> u32 {
+        21
+    }
+    fn title(&self) -> &'static str {
+        "Monkey Math"
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input).map(Answer::from)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input).map(Answer::from)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn generated_monkeys_already_agree() {
+        for seed in 0..20 {
+            let input = generate(50, &mut Random::new(seed));
+            let humn = input
+                .lines()
+                .find_map(|line| line.strip_prefix("humn: "))
+                .unwrap();
+            assert_eq!(humn.parse::<i64>().unwrap(), part2(&input).unwrap());
+        }
+    }
+}
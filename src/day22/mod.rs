@@ -1,87 +1,119 @@
-use std::collections::HashMap;
 use crate::day22::TileType::{Floor, Void, Wall};
-use crate::read_file;
+use crate::random::Random;
+use crate::{Answer, Error, Grid, Result, Solution};
 
-#[derive(PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 enum TileType {
     Void,
     Wall,
     Floor,
 }
 
-struct Tile {
-    tile_type: TileType,
-    left: usize,
-    top: usize,
-    right: usize,
-    bottom: usize,
+/// The map, lines shorter than the widest one are filled up with [`Void`]
+type Board = Grid<TileType>;
+
+fn parse_tile(character: char) -> Option<TileType> {
+    match character {
+        '.' => Some(Floor),
+        '#' => Some(Wall),
+        ' ' => Some(Void),
+        _ => None,
+    }
 }
 
-impl Tile {
-    pub fn new(tile_type: TileType) -> Tile {
-        Tile {
-            tile_type,
-            left: 0,
-            top: 0,
-            right: 0,
-            bottom: 0,
+fn parse_board(input: &str) -> Result<Board> {
+    let (map, _commands) = input
+        .split_once("\n\n")
+        .ok_or_else(|| Error::invalid("Expected a map and a path separated by a blank line"))?;
+
+    let width = map
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut rows = vec![];
+    for line in map.lines() {
+        let mut row = vec![Void; width];
+        for (x, (index, character)) in line.char_indices().enumerate() {
+            row[x] = parse_tile(character).ok_or_else(|| {
+                let text = &line[index..index + character.len_utf8()];
+                Error::parse(text, "unknown tile").within(input, text)
+            })?;
         }
+        rows.push(row);
     }
+    Grid::from_rows(rows)
 }
 
-struct Board {
-    tiles: HashMap<usize, Tile>,
-    width: usize,
-    height: usize,
+/// The six faces of a cube with `size` tiles along each edge, unfolded like the puzzle input,
+/// and a path of `size` moves
+fn generate(size: usize, random: &mut Random) -> String {
+    let edge = size.max(1);
+    // the columns of the faces in each row of the unfolded cube
+    let layout: [&[usize]; 4] = [&[1, 2], &[1], &[0, 1], &[0]];
+    let mut map = String::new();
+    for faces in layout {
+        for _ in 0..edge {
+            for column in 0..=faces[faces.len() - 1] {
+                match faces.contains(&column) {
+                    true => map.extend((0..edge).map(|_| match random.chance(0.1) {
+                        true => '#',
+                        false => '.',
+                    })),
+                    false => map.push_str(&" ".repeat(edge)),
+                }
+            }
+            map.push('\n');
+        }
+    }
+    let mut path = random.range(1..=50).to_string();
+    for _ in 1..edge {
+        path.push(*random.pick(&['L', 'R']));
+        path += &random.range(1..=50).to_string();
+    }
+    format!("{}\n{}\n", map, path)
 }
 
-impl Board {
-    pub fn new() -> Board {
-        Board {
-            tiles: HashMap::new(),
-            width: 0,
-            height: 0,
-        }
+pub struct Day22;
+
+impl Solution for Day22 {
+    fn day(&self) -> u32 {
+        22
     }
-    pub fn get_index(&self, x: usize, y: usize) -> usize {
-        self.height * (x + self.width) % self.width + (y + self.height) % self.height
+    fn title(&self) -> &'static str {
+        "Monkey Map"
     }
-    pub fn get_tile(&self, x: usize, y: usize) -> Tile {
-        self.tiles[&self.get_index(x, y)]
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_board(input).map(|_| ())
     }
-    pub fn set_tile_from_char(&mut self, x: usize, y: usize, character: char) {
-        match character {
-            '.' => self.tiles.insert(self.get_index(x, y), Tile::new(Floor)),
-            '#' => self.tiles.insert(self.get_index(x, y), Tile::new(Wall)),
-            ' ' => self.tiles.insert(self.get_index(x, y), Tile::new(Void)),
-            _ => {
-                panic!("unknown char in map");
-            }
-        };
+    fn part1(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
     }
-    pub fn get_pos(&self, id: usize) -> (usize, usize) {
-        let x = id % self.height;
-        let y = id / self.height;
-        (x, y)
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+    fn generate(&self, size: usize, random: &mut Random) -> Result<String> {
+        Ok(generate(size, random))
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{read_file, Point};
 
-pub fn run() {
-    let input = read_file("input/day22.txt");
-    let (map, _commands) = input.split_once("\n\n").unwrap();
-
-    let mut board: Board = Board::new();
-    board.height = map.lines().count();
-    for (y, line) in map.lines().enumerate() {
-        board.width = line.chars().count();
-        for (x, character) in line.chars().enumerate() {
-            board.set_tile_from_char(x, y, character);
-        }
-    }
-    for (_id, tile) in board.tiles.iter_mut() {
-        if tile.tile_type == Floor {
+    #[test]
+    fn board_is_parsed() {
+        let board = parse_board(&read_file("input/day22-test.txt").unwrap()).unwrap();
+        assert_eq!((16, 12), (board.width(), board.height()));
+        assert_eq!(Some(&Void), board.get(Point::new(0, 0)));
+        assert_eq!(Some(&Floor), board.get(Point::new(8, 0)));
+        assert_eq!(Some(&Wall), board.get(Point::new(11, 0)));
+        assert_eq!(Some(&Void), board.get(Point::new(15, 0)));
 
-        }
+        assert_eq!(
+            "2:2: unknown tile `x`",
+            parse_board(" .#\n.x\n\n10R5").unwrap_err().to_string()
+        );
     }
-}
\ No newline at end of file
+}
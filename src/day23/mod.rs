@@ -1 +1,18 @@
+use crate::{Answer, Result, Solution};
 
+pub struct Day23;
+
+impl Solution for Day23 {
+    fn day(&self) -> u32 {
+        23
+    }
+    fn title(&self) -> &'static str {
+        "Unstable Diffusion"
+    }
+    fn part1(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+}
@@ -1 +1,18 @@
+use crate::{Answer, Result, Solution};
 
+pub struct Day24;
+
+impl Solution for Day24 {
+    fn day(&self) -> u32 {
+        24
+    }
+    fn title(&self) -> &'static str {
+        "Blizzard Basin"
+    }
+    fn part1(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+}
@@ -1 +1,18 @@
+use crate::{Answer, Result, Solution};
 
+pub struct Day25;
+
+impl Solution for Day25 {
+    fn day(&self) -> u32 {
+        25
+    }
+    fn title(&self) -> &'static str {
+        "Full of Hot Air"
+    }
+    fn part1(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+}
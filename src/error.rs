@@ -0,0 +1,209 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input file couldn't be read
+    Io { file: String, source: io::Error },
+    /// A piece of the input couldn't be parsed, `line` and `column` start at 1
+    Parse {
+        file: Option<String>,
+        line: usize,
+        column: usize,
+        text: String,
+        message: String,
+    },
+    /// The input parsed fine but doesn't describe a solvable puzzle
+    Invalid {
+        file: Option<String>,
+        message: String,
+    },
+    /// Talking to the puzzle website failed, or it refused
+    Http { url: String, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// A parse error for `text`, positioned at its very start. Use [`Error::within`] to move
+    /// it to the place `text` has in the surrounding input.
+    pub fn parse(text: &str, message: &str) -> Error {
+        Error::Parse {
+            file: None,
+            line: 1,
+            column: 1,
+            text: text.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn invalid(message: &str) -> Error {
+        Error::Invalid {
+            file: None,
+            message: message.to_string(),
+        }
+    }
+
+    /// Relocates a parse error found in `fragment` to its position in `input`.
+    /// `fragment` has to be a slice of `input`, otherwise the error is left untouched.
+    pub fn within(self, input: &str, fragment: &str) -> Error {
+        match self {
+            Error::Parse {
+                file,
+                line,
+                column,
+                text,
+                message,
+            } => {
+                let (fragment_line, fragment_column) = position(input, fragment).unwrap_or((1, 1));
+                Error::Parse {
+                    file,
+                    line: fragment_line + line - 1,
+                    column: if line == 1 {
+                        fragment_column + column - 1
+                    } else {
+                        column
+                    },
+                    text,
+                    message,
+                }
+            }
+            error => error,
+        }
+    }
+
+    pub fn in_file(self, filename: &str) -> Error {
+        match self {
+            Error::Parse {
+                line,
+                column,
+                text,
+                message,
+                ..
+            } => Error::Parse {
+                file: Some(filename.to_string()),
+                line,
+                column,
+                text,
+                message,
+            },
+            Error::Invalid { message, .. } => Error::Invalid {
+                file: Some(filename.to_string()),
+                message,
+            },
+            error => error,
+        }
+    }
+}
+
+/// Line and column (both starting at 1) of `fragment` inside of `input`
+fn position(input: &str, fragment: &str) -> Option<(usize, usize)> {
+    let start = input.as_ptr() as usize;
+    let offset = (fragment.as_ptr() as usize).checked_sub(start)?;
+    if offset + fragment.len() > input.len() {
+        return None;
+    }
+    let before = &input[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    Some((
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    ))
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { file, source } => write!(f, "Couldn't read file {}: {}", file, source),
+            Error::Parse {
+                file,
+                line,
+                column,
+                text,
+                message,
+            } => {
+                if let Some(file) = file {
+                    write!(f, "{}:", file)?;
+                }
+                write!(f, "{}:{}: {} `{}`", line, column, message, text)
+            }
+            Error::Invalid { file, message } => {
+                if let Some(file) = file {
+                    write!(f, "{}: ", file)?;
+                }
+                write!(f, "{}", message)
+            }
+            Error::Http { url, message } => write!(f, "Request to {} failed: {}", url, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+pub trait Context<T> {
+    /// See [`Error::within`]
+    fn within(self, input: &str, fragment: &str) -> Result<T>;
+}
+
+impl<T> Context<T> for Result<T> {
+    fn within(self, input: &str, fragment: &str) -> Result<T> {
+        self.map_err(|error| error.within(input, fragment))
+    }
+}
+
+/// Parses a whole token like `42` or `-7`
+pub fn parse_number<T: FromStr>(text: &str) -> Result<T> {
+    text.parse()
+        .map_err(|_| Error::parse(text, "expected a number"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn errors_are_relocated() {
+        let input = "1,2\n3,x4\n5,6";
+        let line = input.lines().nth(1).unwrap();
+        let token = &line[2..];
+        let error = parse_number::<i32>(token)
+            .within(line, token)
+            .within(input, line)
+            .unwrap_err();
+
+        assert_eq!("2:3: expected a number `x4`", error.to_string());
+        assert_eq!(
+            "input/day04.txt:2:3: expected a number `x4`",
+            error.in_file("input/day04.txt").to_string()
+        );
+    }
+
+    #[test]
+    fn errors_below_the_first_line_keep_their_column() {
+        let input = "header\nblock\n  line\n  bad";
+        let block = &input[7..];
+        let error = Error::parse("bad", "oops").within(block, &block[15..]);
+        let error = error.within(input, block);
+
+        match error {
+            Error::Parse { line, column, .. } => assert_eq!((4, 3), (line, column)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn foreign_fragments_are_ignored() {
+        let error = Error::parse("x", "oops").within("abc", "x");
+
+        assert_eq!("1:1: oops `x`", error.to_string());
+    }
+}
@@ -0,0 +1,457 @@
+//! An interactive prompt to explore the days in the terminal: pick a day and one of its inputs, run
+//! the parts with their timings and scroll through the grids some days end with.
+
+use crate::animation::{Animation, Settings, View, CLEAR_SCREEN, HOME};
+use crate::bench::{format_duration, timed};
+use crate::{
+    input_variants, read_file, resolve_input, solution, solutions, Answer, Error, Point, Result,
+    Solution,
+};
+use ansi_term::Colour::{Red, Yellow};
+use ansi_term::Style;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::Duration;
+
+const HELP: &str = "\
+days          list the days and their inputs
+day N         pick a day, it starts with the real input
+input NAME    pick an input: `real`, an example like `test` or the path of a file
+run [PART]    run both parts or only one, with timings
+view          scroll through the grid the day ends with (days 8, 10, 12, 14, 17 and 18)
+help          show this
+quit          leave, as does Ctrl-D";
+
+const VIEW_HELP: &str = "h j k l or arrows scroll, H J K L a whole screen, q goes back";
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Days,
+    Day(u32),
+    Input(String),
+    Run(Option<u32>),
+    View,
+    Help,
+    Quit,
+}
+
+fn parse_command(line: &str) -> Result<Command> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let number = |word: &str| {
+        word.parse::<u32>()
+            .map_err(|_| Error::invalid(&format!("`{}` is not a number", word)))
+    };
+    match words.as_slice() {
+        ["days" | "ls"] => Ok(Command::Days),
+        ["day", day] => Ok(Command::Day(number(day)?)),
+        // picking a day is the most common command, so the number alone does it, too
+        [day] if day.chars().all(|c| c.is_ascii_digit()) => Ok(Command::Day(number(day)?)),
+        ["input", name] => Ok(Command::Input(name.to_string())),
+        ["run"] => Ok(Command::Run(None)),
+        ["run", part] => Ok(Command::Run(Some(number(part)?))),
+        ["view"] => Ok(Command::View),
+        ["help" | "?"] => Ok(Command::Help),
+        ["quit" | "exit" | "q"] => Ok(Command::Quit),
+        _ => Err(Error::invalid(&format!(
+            "Unknown command `{}`, try `help`",
+            line.trim()
+        ))),
+    }
+}
+
+/// Which input of the picked day is used
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Choice {
+    Real,
+    Variant(String),
+    File(String),
+}
+
+impl Choice {
+    fn new(name: &str) -> Choice {
+        match name {
+            "real" => Choice::Real,
+            _ if name.contains(['/', '.']) => Choice::File(name.to_string()),
+            _ => Choice::Variant(name.to_string()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Choice::Real => "real",
+            Choice::Variant(name) | Choice::File(name) => name,
+        }
+    }
+}
+
+/// Reads commands line by line and answers them, until `quit` or the end of the input
+pub struct Explorer {
+    input: Box<dyn BufRead>,
+    out: Box<dyn Write>,
+    /// The size of grid views and whether they and the answers are coloured
+    settings: Settings,
+    input_dir: Option<String>,
+    day: Option<(&'static dyn Solution, Choice)>,
+}
+
+impl Explorer {
+    pub fn new(
+        input: Box<dyn BufRead>,
+        out: Box<dyn Write>,
+        settings: Settings,
+        input_dir: Option<&str>,
+    ) -> Explorer {
+        Explorer {
+            input,
+            out,
+            settings,
+            input_dir: input_dir.map(str::to_string),
+            day: None,
+        }
+    }
+
+    /// An explorer on the terminal
+    pub fn stdio(settings: Settings, input_dir: Option<&str>) -> Explorer {
+        Explorer::new(
+            Box::new(io::stdin().lock()),
+            Box::new(io::stdout()),
+            settings,
+            input_dir,
+        )
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.line(&format!(
+            "{} days to explore, `help` shows the commands",
+            solutions().len()
+        ))?;
+        loop {
+            let prompt = match &self.day {
+                Some((solution, choice)) => format!("day{:02}/{}> ", solution.day(), choice.name()),
+                None => "> ".to_string(),
+            };
+            let Some(line) = self.read_line(&prompt)? else {
+                // Ctrl-D, the prompt still needs its line break
+                return self.line("");
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let executed = match parse_command(&line) {
+                Ok(Command::Quit) => return Ok(()),
+                Ok(command) => self.execute(command),
+                Err(error) => Err(error),
+            };
+            // mistakes are shown, the explorer keeps going
+            if let Err(error) = executed {
+                let message = self.paint(Red.bold(), &error.to_string());
+                self.line(&message)?;
+            }
+        }
+    }
+
+    fn execute(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Days => self.list_days(),
+            Command::Day(day) => self.pick_day(day),
+            Command::Input(name) => self.pick_input(&name),
+            Command::Run(part) => self.run_parts(part),
+            Command::View => self.view(),
+            Command::Help => self.line(HELP),
+            Command::Quit => Ok(()),
+        }
+    }
+
+    /// The names of the inputs of a day which exist
+    fn inputs(&self, day: u32) -> Vec<String> {
+        let real = resolve_input(day, None, None, self.input_dir.as_deref()).to_string();
+        let real = Path::new(&real).is_file().then(|| "real".to_string());
+        real.into_iter()
+            .chain(input_variants(day, self.input_dir.as_deref()))
+            .collect()
+    }
+
+    fn list_days(&mut self) -> Result<()> {
+        for solution in solutions() {
+            let line = format!(
+                "{:>2}  {:<32} {}",
+                solution.day(),
+                solution.title(),
+                self.inputs(solution.day()).join(" ")
+            );
+            self.line(&line)?;
+        }
+        Ok(())
+    }
+
+    fn pick_day(&mut self, day: u32) -> Result<()> {
+        let solution =
+            solution(day).ok_or_else(|| Error::invalid(&format!("There is no day {}", day)))?;
+        self.day = Some((solution, Choice::Real));
+        let line = format!(
+            "Day {:02} - {}, inputs: {}",
+            day,
+            self.paint(Yellow.bold(), solution.title()),
+            self.inputs(day).join(" ")
+        );
+        self.line(&line)
+    }
+
+    fn picked(&self) -> Result<(&'static dyn Solution, Choice)> {
+        self.day
+            .clone()
+            .ok_or_else(|| Error::invalid("Pick a day first, e.g. `day 14`"))
+    }
+
+    /// The file the picked input is read from
+    fn filename(&self, solution: &dyn Solution, choice: &Choice) -> String {
+        let input_dir = self.input_dir.as_deref();
+        let source = match choice {
+            Choice::Real => resolve_input(solution.day(), None, None, input_dir),
+            Choice::Variant(variant) => {
+                resolve_input(solution.day(), None, Some(variant), input_dir)
+            }
+            Choice::File(path) => resolve_input(solution.day(), Some(path), None, input_dir),
+        };
+        source.to_string()
+    }
+
+    fn pick_input(&mut self, name: &str) -> Result<()> {
+        let (solution, _) = self.picked()?;
+        let choice = Choice::new(name);
+        let filename = self.filename(solution, &choice);
+        if !Path::new(&filename).is_file() {
+            return Err(Error::invalid(&format!("There is no input {}", filename)));
+        }
+        self.day = Some((solution, choice));
+        self.line(&format!("Reading {}", filename))
+    }
+
+    /// Reads the picked input
+    fn load(&self) -> Result<(&'static dyn Solution, String)> {
+        let (solution, choice) = self.picked()?;
+        let filename = self.filename(solution, &choice);
+        let input = read_file(&filename)?;
+        Ok((solution, input))
+    }
+
+    fn time(&self, duration: Duration) -> String {
+        self.paint(
+            Style::new().dimmed(),
+            &format!("({})", format_duration(duration)),
+        )
+    }
+
+    fn run_parts(&mut self, part: Option<u32>) -> Result<()> {
+        let (solution, input) = self.load()?;
+        let (parsed, parse_time) = timed(|| solution.parse(&input));
+        parsed?;
+        self.line(&format!("Parse:  {}", self.time(parse_time)))?;
+        let parts = match part {
+            Some(part) => vec![part],
+            None => vec![1, 2],
+        };
+        for part in parts {
+            let (answer, duration) = timed(|| solution.solve(part, &input));
+            let line = match answer {
+                Ok(Answer::Unsolved) => format!("Part {}: {}", part, Answer::Unsolved),
+                Ok(answer) => format!(
+                    "Part {}: {} {}",
+                    part,
+                    self.paint(Yellow.bold(), &answer.to_string()),
+                    self.time(duration)
+                ),
+                Err(error) => self.paint(Red.bold(), &error.to_string()),
+            };
+            self.line(&line)?;
+        }
+        Ok(())
+    }
+
+    /// Shows the grid of the picked day and scrolls it until `q`
+    fn view(&mut self) -> Result<()> {
+        let (solution, input) = self.load()?;
+        let View { mut frame, palette } = solution.view(&input)?;
+        let mut animation = Animation::new(Box::new(io::sink()), self.settings);
+        animation.set_palette(palette);
+        let mut text = animation.render(&frame);
+        // the focus only decides where the view starts, from then on it's scrolled freely
+        frame.focus = None;
+        let (width, height) = (self.settings.width as i64, self.settings.height as i64);
+        loop {
+            let top_left = animation.viewport().top_left().unwrap_or(Point::ORIGIN);
+            let status = format!(
+                "{} of {}x{}, {}",
+                top_left,
+                frame.grid.width(),
+                frame.grid.height(),
+                VIEW_HELP
+            );
+            let screen = match self.settings.colour {
+                true => format!("{}{}{}", CLEAR_SCREEN, HOME, text),
+                false => text,
+            };
+            self.line(&screen)?;
+            self.line(&self.paint(Style::new().dimmed(), &status))?;
+
+            let Some(keys) = self.read_line("view> ")? else {
+                return Ok(());
+            };
+            // a terminal which reads whole lines passes the arrow keys on as escape sequences
+            let keys = keys
+                .replace("\x1b[A", "k")
+                .replace("\x1b[B", "j")
+                .replace("\x1b[C", "l")
+                .replace("\x1b[D", "h");
+            for key in keys.trim().chars() {
+                let offset = match key {
+                    'h' => Point::new(-(width / 4).max(1), 0),
+                    'l' => Point::new((width / 4).max(1), 0),
+                    'k' => Point::new(0, -(height / 4).max(1)),
+                    'j' => Point::new(0, (height / 4).max(1)),
+                    'H' => Point::new(-width, 0),
+                    'L' => Point::new(width, 0),
+                    'K' => Point::new(0, -height),
+                    'J' => Point::new(0, height),
+                    'q' => return Ok(()),
+                    _ => Point::ORIGIN,
+                };
+                animation.scroll(offset);
+            }
+            text = animation.render(&frame);
+        }
+    }
+
+    fn paint(&self, style: Style, text: &str) -> String {
+        match self.settings.colour {
+            true => style.paint(text).to_string(),
+            false => text.to_string(),
+        }
+    }
+
+    fn line(&mut self, text: &str) -> Result<()> {
+        writeln!(self.out, "{}", text).map_err(terminal_error)
+    }
+
+    /// Shows `prompt` and waits for a line, `None` once the input ends
+    fn read_line(&mut self, prompt: &str) -> Result<Option<String>> {
+        write!(self.out, "{}", prompt).map_err(terminal_error)?;
+        self.out.flush().map_err(terminal_error)?;
+        let mut line = String::new();
+        match self.input.read_line(&mut line).map_err(terminal_error)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    }
+}
+
+fn terminal_error(source: io::Error) -> Error {
+    Error::Io {
+        file: "<terminal>".to_string(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    /// Output which can still be read after the explorer took it
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs the commands in `script` and returns everything written
+    fn explore(script: &str) -> String {
+        let out = Shared::default();
+        let settings = Settings {
+            fps: 0.0,
+            width: 6,
+            height: 3,
+            colour: false,
+        };
+        let mut explorer = Explorer::new(
+            Box::new(Cursor::new(script.to_string())),
+            Box::new(out.clone()),
+            settings,
+            Some("input"),
+        );
+        explorer.run().unwrap();
+        let written = out.0.borrow().clone();
+        String::from_utf8(written).unwrap()
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(Command::Day(14), parse_command("day 14").unwrap());
+        assert_eq!(Command::Day(8), parse_command(" 8 ").unwrap());
+        assert_eq!(Command::Run(Some(2)), parse_command("run 2").unwrap());
+        assert_eq!(
+            Command::Input("test".to_string()),
+            parse_command("input test").unwrap()
+        );
+        assert_eq!(Command::Quit, parse_command("exit").unwrap());
+        assert_eq!(
+            "`two` is not a number",
+            parse_command("run two").unwrap_err().to_string()
+        );
+        assert!(parse_command("dance").is_err());
+    }
+
+    #[test]
+    fn inputs_are_told_apart() {
+        assert_eq!(Choice::Real, Choice::new("real"));
+        assert_eq!(Choice::Variant("mini".to_string()), Choice::new("mini"));
+        assert_eq!(
+            Choice::File("/tmp/day16.txt".to_string()),
+            Choice::new("/tmp/day16.txt")
+        );
+    }
+
+    #[test]
+    fn parts_are_run() {
+        let output = explore("days\nday 8\ninput test\nrun\nrun 2\nquit\n");
+        assert!(output.contains("\n 8  Treetop Tree House               real test\n"));
+        assert!(output.contains("day08/test> Parse:  ("));
+        assert!(output.contains("Part 1: 21 ("));
+        assert!(output.contains("Part 2: 8 ("));
+        assert_eq!(2, output.matches("Part 2").count());
+    }
+
+    #[test]
+    fn grids_are_scrolled() {
+        let output = explore("day 14\ninput test\nview\nl\nJJ\nq\n");
+        let screens: Vec<&str> = output.split("view> ").collect();
+        assert_eq!(4, screens.len());
+        // the view starts at the source of the sand
+        assert!(screens[0].ends_with("......\n......\n...o..\n24 grains of sand came to rest\n497/0 of 10x10, h j k l or arrows scroll, H J K L a whole screen, q goes back\n"));
+        assert!(screens[1]
+            .starts_with("......\n......\n..o...\n24 grains of sand came to rest\n498/0 of 10x10"));
+        // scrolled beyond the bottom, so it stops at the edge
+        assert!(screens[2]
+            .starts_with("#ooo#.\noooo#.\noooo#.\n24 grains of sand came to rest\n498/6 of 10x10"));
+        // the input ended, so did the explorer
+        assert_eq!("day14/test> \n", screens[3]);
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        let output = explore("run\nday 99\nday 1\ninput missing\nview\ndance\n");
+        assert!(output.contains("Pick a day first, e.g. `day 14`"));
+        assert!(output.contains("There is no day 99"));
+        assert!(output.contains("There is no input input/day01-missing.txt"));
+        assert!(output.contains("There is no view for day 1"));
+        assert!(output.contains("Unknown command `dance`, try `help`"));
+    }
+}
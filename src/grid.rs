@@ -0,0 +1,352 @@
+use crate::{Error, Point, Result};
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+/// A rectangle of cells stored row by row. Cells are addressed by signed coordinates, the
+/// top-left cell sits at `origin`, so e.g. a map starting at x = 494 doesn't need manual offsets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+    origin: Point<i64>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its rows, which all need to have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(Error::invalid("The rows of the grid differ in length"));
+        }
+        Ok(Grid {
+            height: rows.len(),
+            width,
+            cells: rows.into_iter().flatten().collect(),
+            origin: Point::ORIGIN,
+        })
+    }
+
+    /// Parses one cell per character, one row per line. `cell` returns `None` for characters
+    /// which aren't allowed, they're reported with `message`.
+    pub fn parse(input: &str, message: &str, cell: impl Fn(char) -> Option<T>) -> Result<Grid<T>> {
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+
+        for line in input.lines() {
+            for (index, character) in line.char_indices() {
+                let text = &line[index..index + character.len_utf8()];
+                cells.push(
+                    cell(character)
+                        .ok_or_else(|| Error::parse(text, message).within(input, text))?,
+                );
+            }
+            let length = line.chars().count();
+            if *width.get_or_insert(length) != length {
+                return Err(Error::parse(line, "rows differ in length").within(input, line));
+            }
+            height += 1;
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Grid {
+                cells,
+                width,
+                height,
+                origin: Point::ORIGIN,
+            }),
+            _ => Err(Error::invalid("The grid is empty")),
+        }
+    }
+
+    /// Moves the grid, so its top-left cell has the coordinates `origin`
+    pub fn with_origin(mut self, origin: Point<i64>) -> Grid<T> {
+        self.origin = origin;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The coordinates of the top-left cell
+    pub fn origin(&self) -> Point<i64> {
+        self.origin
+    }
+
+    fn index_of(&self, point: Point<i64>) -> Option<usize> {
+        let x = usize::try_from(point.x - self.origin.x).ok()?;
+        let y = usize::try_from(point.y - self.origin.y).ok()?;
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    fn point_of(&self, index: usize) -> Point<i64> {
+        Point::new(
+            self.origin.x + (index % self.width) as i64,
+            self.origin.y + (index / self.width) as i64,
+        )
+    }
+
+    pub fn contains(&self, point: Point<i64>) -> bool {
+        self.index_of(point).is_some()
+    }
+
+    pub fn get(&self, point: Point<i64>) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point<i64>) -> Option<&mut T> {
+        self.index_of(point).map(|index| &mut self.cells[index])
+    }
+
+    /// All coordinates, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Point<i64>> + '_ {
+        (0..self.cells.len()).map(|index| self.point_of(index))
+    }
+
+    /// The cells together with their coordinates, row by row
+    pub fn cells(&self) -> impl Iterator<Item = (Point<i64>, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The cells, row by row
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// The coordinates of the first cell matching `predicate`
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<Point<i64>> {
+        self.cells
+            .iter()
+            .position(predicate)
+            .map(|index| self.point_of(index))
+    }
+
+    /// The up to four orthogonal neighbours of `point` which are inside the grid
+    pub fn neighbours4(&self, point: Point<i64>) -> impl Iterator<Item = Point<i64>> + '_ {
+        point
+            .neighbours4()
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    /// The up to eight neighbours of `point`, diagonal ones included, which are inside the grid
+    pub fn neighbours8(&self, point: Point<i64>) -> impl Iterator<Item = Point<i64>> + '_ {
+        point
+            .neighbours8()
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    /// The row with the index `y`, counted from the top and ignoring the origin
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The column with the index `x`, counted from the left and ignoring the origin
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+            origin: self.origin,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T> {
+        Grid {
+            cells: vec![value; width * height],
+            width,
+            height,
+            origin: Point::ORIGIN,
+        }
+    }
+
+    /// Mirrors the grid along its diagonal, rows become columns. The origin is kept.
+    pub fn transpose(&self) -> Grid<T> {
+        let cells = self.columns().flat_map(|column| column.cloned()).collect();
+        Grid {
+            cells,
+            width: self.height,
+            height: self.width,
+            origin: self.origin,
+        }
+    }
+
+    /// Turns the grid by 90° clockwise. The origin is kept.
+    pub fn rotate_right(&self) -> Grid<T> {
+        let cells = self
+            .columns()
+            .flat_map(|column| column.cloned().collect::<Vec<T>>().into_iter().rev())
+            .collect();
+        Grid {
+            cells,
+            width: self.height,
+            height: self.width,
+            origin: self.origin,
+        }
+    }
+
+    /// Turns the grid by 90° counter-clockwise. The origin is kept.
+    pub fn rotate_left(&self) -> Grid<T> {
+        let cells = (0..self.width)
+            .rev()
+            .flat_map(|x| self.column(x).cloned())
+            .collect();
+        Grid {
+            cells,
+            width: self.height,
+            height: self.width,
+            origin: self.origin,
+        }
+    }
+}
+
+impl<T> Index<Point<i64>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point<i64>) -> &T {
+        match self.get(point) {
+            Some(cell) => cell,
+            None => panic!("{:?} is outside of the grid", point),
+        }
+    }
+}
+
+impl<T> IndexMut<Point<i64>> for Grid<T> {
+    fn index_mut(&mut self, point: Point<i64>) -> &mut T {
+        match self.index_of(point) {
+            Some(index) => &mut self.cells[index],
+            None => panic!("{:?} is outside of the grid", point),
+        }
+    }
+}
+
+/// Draws the cells row by row, without anything in between
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn digits(input: &str) -> Result<Grid<u32>> {
+        Grid::parse(input, "expected a digit", |c| c.to_digit(10))
+    }
+
+    #[test]
+    fn grids_are_parsed() {
+        let grid = digits("123\n456\n").unwrap();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&6), grid.get(Point::new(2, 1)));
+        assert_eq!(None, grid.get(Point::new(3, 1)));
+        assert_eq!(None, grid.get(Point::new(-1, 0)));
+
+        assert_eq!(
+            "2:2: expected a digit `x`",
+            digits("123\n4x6").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "2:1: rows differ in length `45`",
+            digits("123\n45").unwrap_err().to_string()
+        );
+        assert_eq!("The grid is empty", digits("").unwrap_err().to_string());
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+    }
+
+    #[test]
+    fn origin_moves_the_coordinates() {
+        let mut grid = Grid::new(3, 2, '.').with_origin(Point::new(494, -1));
+        grid[Point::new(494, -1)] = '#';
+        *grid.get_mut(Point::new(496, 0)).unwrap() = 'o';
+
+        assert!(grid.contains(Point::new(496, 0)));
+        assert!(!grid.contains(Point::new(0, 0)));
+        assert_eq!("#..\n..o", grid.to_string());
+        assert_eq!(Some(Point::new(496, 0)), grid.find(|cell| *cell == 'o'));
+        assert_eq!(
+            vec![Point::new(494, -1), Point::new(495, -1)],
+            grid.positions().take(2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn neighbours_stay_inside() {
+        let grid = digits("123\n456\n789").unwrap();
+        let values = |points: Vec<Point<i64>>| points.iter().map(|p| grid[*p]).collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![2, 6, 8, 4],
+            values(grid.neighbours4(Point::new(1, 1)).collect())
+        );
+        assert_eq!(
+            vec![2, 4],
+            values(grid.neighbours4(Point::new(0, 0)).collect())
+        );
+        assert_eq!(
+            vec![2, 3, 6, 9, 8, 7, 4, 1],
+            values(grid.neighbours8(Point::new(1, 1)).collect())
+        );
+        assert_eq!(
+            vec![3, 9, 8, 5, 2],
+            values(grid.neighbours8(Point::new(2, 1)).collect())
+        );
+    }
+
+    #[test]
+    fn rows_and_columns_are_iterated() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!(
+            vec![&[1, 2, 3][..], &[4, 5, 6][..]],
+            grid.rows().collect::<Vec<_>>()
+        );
+        assert_eq!(&[4, 5, 6], grid.row(1));
+        assert_eq!(
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]],
+            grid.columns()
+                .map(|column| column.copied().collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn grids_are_turned() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!("14\n25\n36", grid.transpose().to_string());
+        assert_eq!("41\n52\n63", grid.rotate_right().to_string());
+        assert_eq!("36\n25\n14", grid.rotate_left().to_string());
+        assert_eq!(grid, grid.rotate_right().rotate_left());
+        assert_eq!(&[8, 10, 12], grid.map(|digit| digit * 2).row(1));
+    }
+}
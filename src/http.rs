@@ -0,0 +1,296 @@
+//! Just enough HTTP to talk to the puzzle website. How requests are sent is up to an [`Http`]
+//! backend: a small client on plain TCP for `http://` URLs like a local stand-in server, and
+//! `curl` for everything else, since there's no TLS in the standard library.
+
+use crate::{Error, Result};
+use clap::ValueEnum;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a request may take
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl Request {
+    pub fn get(url: &str) -> Request {
+        Request {
+            method: "GET",
+            url: url.to_string(),
+            headers: vec![],
+            body: None,
+        }
+    }
+
+    /// Sends `fields` like a HTML form does
+    pub fn post_form(url: &str, fields: &[(&str, &str)]) -> Request {
+        let body: Vec<String> = fields
+            .iter()
+            .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+            .collect();
+        Request {
+            method: "POST",
+            url: url.to_string(),
+            headers: vec![(
+                "Content-Type".to_string(),
+                "application/x-www-form-urlencoded".to_string(),
+            )],
+            body: Some(body.join("&")),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Request {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Percent-encodes everything but letters, digits and `-_.~`
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Sends requests and waits for the response
+pub trait Http {
+    fn send(&self, request: &Request) -> Result<Response>;
+}
+
+fn http_error(url: &str, message: &str) -> Error {
+    Error::Http {
+        url: url.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// HTTP/1.0 on a plain TCP connection, for `http://` URLs only
+pub struct PlainHttp;
+
+impl Http for PlainHttp {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let url = &request.url;
+        let (host, path) = url
+            .strip_prefix("http://")
+            .map(|rest| {
+                rest.split_once('/')
+                    .map_or((rest, ""), |(host, path)| (host, path))
+            })
+            .ok_or_else(|| http_error(url, "only http:// URLs can be sent without curl"))?;
+        let address = match host.contains(':') {
+            true => host.to_string(),
+            false => format!("{}:80", host),
+        };
+
+        // HTTP/1.0, so the server closes the connection after the response and doesn't chunk it
+        let mut text = format!(
+            "{} /{} HTTP/1.0\r\nHost: {}\r\n",
+            request.method, path, host
+        );
+        for (name, value) in &request.headers {
+            text += &format!("{}: {}\r\n", name, value);
+        }
+        let body = request.body.as_deref().unwrap_or("");
+        text += &format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+
+        let failed = |error: std::io::Error| http_error(url, &error.to_string());
+        let mut stream = TcpStream::connect(&address).map_err(failed)?;
+        stream.set_read_timeout(Some(TIMEOUT)).map_err(failed)?;
+        stream.write_all(text.as_bytes()).map_err(failed)?;
+        let mut raw = vec![];
+        stream.read_to_end(&mut raw).map_err(failed)?;
+        parse_response(&raw).ok_or_else(|| http_error(url, "the response isn't HTTP"))
+    }
+}
+
+/// Splits a raw response into its status and body
+fn parse_response(raw: &[u8]) -> Option<Response> {
+    let text = String::from_utf8_lossy(raw);
+    let (head, body) = text.split_once("\r\n\r\n")?;
+    let status_line = head.lines().next()?;
+    let mut words = status_line.split_whitespace();
+    if !words.next()?.starts_with("HTTP/") {
+        return None;
+    }
+    Some(Response {
+        status: words.next()?.parse().ok()?,
+        body: body.to_string(),
+    })
+}
+
+/// Runs `curl`, which does HTTPS. The headers are handed over on standard input, so the session
+/// token doesn't show up in the list of processes.
+pub struct Curl;
+
+impl Http for Curl {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let url = &request.url;
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut config = String::new();
+        for (name, value) in &request.headers {
+            config += &format!("header = {}\n", quote(&format!("{}: {}", name, value)));
+        }
+        if let Some(body) = &request.body {
+            config += &format!("data = {}\n", quote(body));
+        }
+
+        let failed = |error: std::io::Error| http_error(url, &format!("curl: {}", error));
+        let mut curl = Command::new("curl")
+            .args(["--silent", "--show-error", "--location", "--config", "-"])
+            .args(["--max-time", &TIMEOUT.as_secs().to_string()])
+            .args(["--request", request.method, "--write-out", "\n%{http_code}"])
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(failed)?;
+        if let Some(mut stdin) = curl.stdin.take() {
+            stdin.write_all(config.as_bytes()).map_err(failed)?;
+        }
+        let output = curl.wait_with_output().map_err(failed)?;
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr);
+            return Err(http_error(url, message.trim()));
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        let (body, status) = text
+            .rsplit_once('\n')
+            .ok_or_else(|| http_error(url, "curl printed no status"))?;
+        Ok(Response {
+            status: status
+                .trim()
+                .parse()
+                .map_err(|_| http_error(url, "curl printed no status"))?,
+            body: body.to_string(),
+        })
+    }
+}
+
+/// Which [`Http`] backend sends the requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// the plain client for `http://`, curl for everything else
+    Auto,
+    /// the plain client, `http://` only
+    Plain,
+    Curl,
+}
+
+impl Backend {
+    /// The backend for requests to `base_url`
+    pub fn http(self, base_url: &str) -> Box<dyn Http> {
+        match self {
+            Backend::Auto if base_url.starts_with("http://") => Box::new(PlainHttp),
+            Backend::Plain => Box::new(PlainHttp),
+            Backend::Auto | Backend::Curl => Box::new(Curl),
+        }
+    }
+}
+
+/// Keeps requests at least `interval` apart. The time of the last request is kept in a file, so
+/// that separate runs keep their distance, too.
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    interval: Duration,
+    stamp: Option<PathBuf>,
+}
+
+impl RateLimit {
+    pub fn new(interval: Duration, stamp: Option<PathBuf>) -> RateLimit {
+        RateLimit { interval, stamp }
+    }
+
+    fn last_request(&self) -> Option<SystemTime> {
+        let text = fs::read_to_string(self.stamp.as_ref()?).ok()?;
+        let millis: u64 = text.trim().parse().ok()?;
+        // the stamp is cut to whole milliseconds, the request may have gone out up to one later
+        Some(UNIX_EPOCH + Duration::from_millis(millis + 1))
+    }
+
+    /// Sleeps until the next request may be sent and notes down that it is, returns how long it
+    /// waited
+    pub fn wait(&mut self) -> Result<Duration> {
+        let waited = match self.last_request() {
+            Some(last) => {
+                let since = SystemTime::now().duration_since(last).unwrap_or_default();
+                self.interval.saturating_sub(since)
+            }
+            None => Duration::ZERO,
+        };
+        thread::sleep(waited);
+        if let Some(stamp) = &self.stamp {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            let written = stamp
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(stamp, now.as_millis().to_string()));
+            written.map_err(|source| Error::Io {
+                file: stamp.display().to_string(),
+                source,
+            })?;
+        }
+        Ok(waited)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn forms_are_encoded() {
+        let request = Request::post_form("http://x/y", &[("level", "1"), ("answer", "a b&c")]);
+        assert_eq!(Some("level=1&answer=a%20b%26c".to_string()), request.body);
+        assert_eq!("POST", request.method);
+    }
+
+    #[test]
+    fn responses_are_parsed() {
+        let raw = b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\n\r\nnot here\nyet";
+        assert_eq!(
+            Some(Response {
+                status: 404,
+                body: "not here\nyet".to_string()
+            }),
+            parse_response(raw)
+        );
+        assert_eq!(None, parse_response(b"SSH-2.0-OpenSSH\r\n\r\n"));
+        assert_eq!(None, parse_response(b"HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[test]
+    fn only_plain_urls_go_without_curl() {
+        let error = PlainHttp
+            .send(&Request::get("https://example.com/"))
+            .unwrap_err();
+        assert_eq!(
+            "Request to https://example.com/ failed: only http:// URLs can be sent without curl",
+            error.to_string()
+        );
+    }
+}
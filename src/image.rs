@@ -0,0 +1,433 @@
+//! Writes grids as images: a single PNG, or the frames of a simulation as animated PNG or GIF.
+//! Every cell becomes a square of pixels in the foreground colour its [`Palette`] gives it.
+
+use crate::animation::Palette;
+use crate::{Error, Grid, Point, Result};
+use ansi_term::{Colour, Style};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::Duration;
+
+type Rgb = [u8; 3];
+
+/// Cells without a foreground colour
+const DEFAULT_COLOUR: Rgb = [229, 229, 229];
+
+/// Outside of smaller frames
+const BACKGROUND: Rgb = [0, 0, 0];
+
+/// The 16 colours of xterm, the plain ones first
+const BASIC_COLOURS: [Rgb; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+/// How a terminal would show the foreground of `style`. Bold colours get brighter, dimmed ones
+/// darker.
+pub fn rgb(style: Style) -> Rgb {
+    let basic = |index: usize| match style.is_bold {
+        true => BASIC_COLOURS[index + 8],
+        false => BASIC_COLOURS[index],
+    };
+    let colour = match style.foreground {
+        None => DEFAULT_COLOUR,
+        Some(Colour::Black) => basic(0),
+        Some(Colour::Red) => basic(1),
+        Some(Colour::Green) => basic(2),
+        Some(Colour::Yellow) => basic(3),
+        Some(Colour::Blue) => basic(4),
+        Some(Colour::Purple) => basic(5),
+        Some(Colour::Cyan) => basic(6),
+        Some(Colour::White) => basic(7),
+        Some(Colour::Fixed(index)) => fixed(index),
+        Some(Colour::RGB(r, g, b)) => [r, g, b],
+    };
+    match style.is_dimmed {
+        true => colour.map(|channel| channel / 2),
+        false => colour,
+    }
+}
+
+/// The 256 colour table: the basic colours, a 6x6x6 cube and a ramp of greys
+fn fixed(index: u8) -> Rgb {
+    let level = |value: u8| match value {
+        0 => 0,
+        _ => 55 + value * 40,
+    };
+    match index {
+        0..=15 => BASIC_COLOURS[index as usize],
+        16..=231 => {
+            let cube = index - 16;
+            [level(cube / 36), level(cube / 6 % 6), level(cube % 6)]
+        }
+        _ => [8 + (index - 232) * 10; 3],
+    }
+}
+
+/// How the cells are turned into pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageOptions {
+    /// The width and height of a cell in pixels
+    pub scale: u32,
+    /// How long each frame of an animation is shown
+    pub delay: Duration,
+}
+
+impl Default for ImageOptions {
+    fn default() -> ImageOptions {
+        ImageOptions {
+            scale: 4,
+            delay: Duration::from_millis(50),
+        }
+    }
+}
+
+/// The frames of a simulation which go into an image. Only every `every`th frame is kept, so
+/// long simulations don't end up as huge files, but the last one always makes it in.
+#[derive(Debug, Clone)]
+pub struct Recording {
+    every: usize,
+    offered: usize,
+    kept: Vec<Grid<char>>,
+    last: Option<Grid<char>>,
+}
+
+impl Recording {
+    pub fn new(every: usize) -> Recording {
+        Recording {
+            every: every.max(1),
+            offered: 0,
+            kept: vec![],
+            last: None,
+        }
+    }
+
+    /// Hands over the next frame
+    pub fn offer(&mut self, grid: &Grid<char>) {
+        match self.offered % self.every {
+            0 => {
+                self.kept.push(grid.clone());
+                self.last = None;
+            }
+            _ => self.last = Some(grid.clone()),
+        }
+        self.offered += 1;
+    }
+
+    /// The frames which go into the image
+    pub fn frames(&self) -> impl Iterator<Item = &Grid<char>> {
+        self.kept.iter().chain(&self.last)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kept.is_empty()
+    }
+
+    /// Writes the frames as animated PNG or GIF, depending on the extension of `path`. Every
+    /// frame stands for the skipped ones, too, so the animation keeps its pace.
+    pub fn save(&self, path: &Path, palette: &Palette, options: ImageOptions) -> Result<()> {
+        let frames: Vec<&Grid<char>> = self.frames().collect();
+        let options = ImageOptions {
+            delay: options.delay * self.every as u32,
+            ..options
+        };
+        save_animation(path, &frames, palette, options)
+    }
+}
+
+/// The area all frames fit into, in the coordinates of the grids
+struct Canvas {
+    top_left: Point<i64>,
+    width: usize,
+    height: usize,
+    scale: usize,
+}
+
+impl Canvas {
+    fn around(frames: &[&Grid<char>], scale: u32) -> Result<Canvas> {
+        let first = frames
+            .first()
+            .ok_or_else(|| Error::invalid("There are no frames to save"))?;
+        let (mut top_left, mut bottom_right) = (first.origin(), first.origin());
+        for grid in frames {
+            let end = grid.origin() + Point::new(grid.width() as i64, grid.height() as i64);
+            top_left = Point::new(
+                top_left.x.min(grid.origin().x),
+                top_left.y.min(grid.origin().y),
+            );
+            bottom_right = Point::new(bottom_right.x.max(end.x), bottom_right.y.max(end.y));
+        }
+        let size = bottom_right - top_left;
+        if size.x == 0 || size.y == 0 {
+            return Err(Error::invalid("The frames are empty"));
+        }
+        Ok(Canvas {
+            top_left,
+            width: size.x as usize,
+            height: size.y as usize,
+            scale: scale.max(1) as usize,
+        })
+    }
+
+    fn pixel_width(&self) -> usize {
+        self.width * self.scale
+    }
+
+    fn pixel_height(&self) -> usize {
+        self.height * self.scale
+    }
+
+    /// The colour of every pixel, row by row
+    fn paint(&self, grid: &Grid<char>, colour: impl Fn(char) -> Rgb) -> Vec<Rgb> {
+        let mut pixels = Vec::with_capacity(self.pixel_width() * self.pixel_height());
+        for y in 0..self.height {
+            let row: Vec<Rgb> = (0..self.width)
+                .map(|x| {
+                    let point = self.top_left + Point::new(x as i64, y as i64);
+                    grid.get(point).map_or(BACKGROUND, |cell| colour(*cell))
+                })
+                .flat_map(|rgb| [rgb].repeat(self.scale))
+                .collect();
+            for _ in 0..self.scale {
+                pixels.extend_from_slice(&row);
+            }
+        }
+        pixels
+    }
+}
+
+fn create(path: &Path) -> Result<BufWriter<File>> {
+    let file = File::create(path).map_err(|source| Error::Io {
+        file: path.display().to_string(),
+        source,
+    })?;
+    Ok(BufWriter::new(file))
+}
+
+fn encoding_error(path: &Path, error: impl ToString) -> Error {
+    Error::invalid(&format!(
+        "Writing {} failed: {}",
+        path.display(),
+        error.to_string()
+    ))
+}
+
+/// Writes `grid` as a PNG
+pub fn save_png(path: &Path, grid: &Grid<char>, palette: &Palette, scale: u32) -> Result<()> {
+    let options = ImageOptions {
+        scale,
+        ..ImageOptions::default()
+    };
+    save_apng(path, &[grid], palette, options)
+}
+
+/// Writes the frames as animated PNG or GIF, depending on the extension of `path`
+pub fn save_animation(
+    path: &Path,
+    frames: &[&Grid<char>],
+    palette: &Palette,
+    options: ImageOptions,
+) -> Result<()> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("png") | Some("apng") => save_apng(path, frames, palette, options),
+        Some("gif") => save_gif(path, frames, palette, options),
+        _ => Err(Error::invalid(&format!(
+            "Don't know how to write {}, use .png or .gif",
+            path.display()
+        ))),
+    }
+}
+
+/// Writes the frames as animated PNG, a single frame as plain PNG
+pub fn save_apng(
+    path: &Path,
+    frames: &[&Grid<char>],
+    palette: &Palette,
+    options: ImageOptions,
+) -> Result<()> {
+    let canvas = Canvas::around(frames, options.scale)?;
+    let failed = |error: png::EncodingError| encoding_error(path, error);
+
+    let mut encoder = png::Encoder::new(
+        create(path)?,
+        canvas.pixel_width() as u32,
+        canvas.pixel_height() as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    if frames.len() > 1 {
+        encoder
+            .set_animated(frames.len() as u32, 0)
+            .map_err(failed)?;
+        let milliseconds = options.delay.as_millis().min(u16::MAX as u128) as u16;
+        encoder
+            .set_frame_delay(milliseconds, 1000)
+            .map_err(failed)?;
+    }
+    let mut writer = encoder.write_header().map_err(failed)?;
+    for grid in frames {
+        let pixels = canvas.paint(grid, |cell| rgb(palette.style(cell)));
+        writer
+            .write_image_data(pixels.as_flattened())
+            .map_err(failed)?;
+    }
+    writer.finish().map_err(failed)
+}
+
+/// Writes the frames as looping GIF, which can hold up to 256 colours
+pub fn save_gif(
+    path: &Path,
+    frames: &[&Grid<char>],
+    palette: &Palette,
+    options: ImageOptions,
+) -> Result<()> {
+    let canvas = Canvas::around(frames, options.scale)?;
+    let failed = |error: gif::EncodingError| encoding_error(path, error);
+    let too_large = || Error::invalid("The frames are too large for a GIF");
+    let width = u16::try_from(canvas.pixel_width()).map_err(|_| too_large())?;
+    let height = u16::try_from(canvas.pixel_height()).map_err(|_| too_large())?;
+
+    let mut colours: Vec<Rgb> = vec![BACKGROUND];
+    let mut indexed = vec![];
+    for grid in frames {
+        let pixels = canvas.paint(grid, |cell| rgb(palette.style(cell)));
+        let mut indices = Vec::with_capacity(pixels.len());
+        for pixel in pixels {
+            let index = match colours.iter().position(|colour| *colour == pixel) {
+                Some(index) => index,
+                None => {
+                    colours.push(pixel);
+                    colours.len() - 1
+                }
+            };
+            indices.push(
+                u8::try_from(index).map_err(|_| {
+                    Error::invalid("The palette has more colours than a GIF can hold")
+                })?,
+            );
+        }
+        indexed.push(indices);
+    }
+
+    let mut encoder =
+        gif::Encoder::new(create(path)?, width, height, colours.as_flattened()).map_err(failed)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(failed)?;
+    // GIFs count in hundredths of a second
+    let delay = (options.delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+    for indices in indexed {
+        let frame = gif::Frame {
+            width,
+            height,
+            delay,
+            buffer: Cow::Owned(indices),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame).map_err(failed)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ansi_term::Colour::{Blue, Fixed, Red, RGB};
+    use std::fs;
+
+    fn grid(text: &str) -> Grid<char> {
+        Grid::parse(text, "", Some).unwrap()
+    }
+
+    #[test]
+    fn styles_become_colours() {
+        assert_eq!([205, 0, 0], rgb(Red.normal()));
+        assert_eq!([255, 0, 0], rgb(Red.bold()));
+        assert_eq!([0, 0, 119], rgb(Blue.dimmed()));
+        assert_eq!([1, 2, 3], rgb(RGB(1, 2, 3).normal()));
+        assert_eq!([255, 0, 0], rgb(Fixed(196).normal()));
+        assert_eq!([238, 238, 238], rgb(Fixed(255).normal()));
+        assert_eq!(DEFAULT_COLOUR, rgb(Style::new()));
+    }
+
+    #[test]
+    fn frames_are_skipped_but_not_the_last() {
+        let mut recording = Recording::new(3);
+        assert!(recording.is_empty());
+        for cell in ['a', 'b', 'c', 'd', 'e'] {
+            recording.offer(&grid(&cell.to_string()));
+        }
+        let frames: Vec<String> = recording.frames().map(|grid| grid.to_string()).collect();
+        assert_eq!(vec!["a", "d", "e"], frames);
+
+        recording.offer(&grid("f"));
+        recording.offer(&grid("g"));
+        assert_eq!(3, recording.len());
+    }
+
+    #[test]
+    fn frames_share_a_canvas() {
+        let small = grid("#");
+        let large = grid("..\n.#").with_origin(Point::new(-1, 0));
+        let canvas = Canvas::around(&[&small, &large], 2).unwrap();
+        assert_eq!((4, 4), (canvas.pixel_width(), canvas.pixel_height()));
+
+        let white = [255, 255, 255];
+        let pixels = canvas.paint(&small, |_| white);
+        let black = BACKGROUND;
+        assert_eq!(
+            vec![black, black, white, white, black, black, white, white],
+            pixels[..8].to_vec()
+        );
+        assert!(Canvas::around(&[], 1).is_err());
+    }
+
+    #[test]
+    fn animations_are_written() {
+        let dir = std::env::temp_dir().join(format!("aoc-image-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let palette = Palette::new().with('#', Red.normal());
+        let frames = [&grid("#."), &grid(".#")];
+
+        for name in ["frames.png", "frames.gif", "single.png"] {
+            let frames = match name {
+                "single.png" => &frames[..1],
+                _ => &frames[..],
+            };
+            save_animation(&dir.join(name), frames, &palette, ImageOptions::default()).unwrap();
+        }
+        let png = fs::read(dir.join("frames.png")).unwrap();
+        assert_eq!(b"\x89PNG", &png[..4]);
+        assert!(png.windows(4).any(|chunk| chunk == b"acTL"));
+        let single = fs::read(dir.join("single.png")).unwrap();
+        assert!(!single.windows(4).any(|chunk| chunk == b"acTL"));
+        assert_eq!(b"GIF89a", &fs::read(dir.join("frames.gif")).unwrap()[..6]);
+
+        let error = save_animation(
+            &dir.join("frames.bmp"),
+            &frames,
+            &palette,
+            ImageOptions::default(),
+        );
+        assert!(error.is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
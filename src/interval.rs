@@ -0,0 +1,311 @@
+use crate::point::Coordinate;
+use std::fmt::{self, Debug, Formatter};
+
+/// A set of whole numbers stored as sorted, inclusive intervals. Intervals which overlap or touch
+/// are merged, so each set has exactly one representation.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+/// Whether the interval ending at `end` lies before the one starting at `start` without touching it
+fn apart<T: Coordinate>(end: T, start: T) -> bool {
+    end < start && end.offset(1) != Some(start)
+}
+
+impl<T: Coordinate> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { intervals: vec![] }
+    }
+
+    /// The numbers from `start` to `end`, both included, in either order
+    pub fn interval(start: T, end: T) -> IntervalSet<T> {
+        IntervalSet {
+            intervals: vec![(start.min(end), start.max(end))],
+        }
+    }
+
+    /// Adds the numbers from `start` to `end`, both included, in either order
+    pub fn insert(&mut self, start: T, end: T) {
+        let (start, end) = (start.min(end), start.max(end));
+        let first = self
+            .intervals
+            .partition_point(|(_, known_end)| apart(*known_end, start));
+        let last = self
+            .intervals
+            .partition_point(|(known_start, _)| !apart(end, *known_start));
+        let merged = match first < last {
+            true => (
+                start.min(self.intervals[first].0),
+                end.max(self.intervals[last - 1].1),
+            ),
+            false => (start, end),
+        };
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    /// The intervals in ascending order, none of them overlap or touch
+    pub fn intervals(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    /// The intervals of missing numbers between the first and the last number of the set
+    pub fn gaps(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals.windows(2).filter_map(|pair| {
+            let start = pair[0].1.offset(1)?;
+            let end = pair[1].0.offset(-1)?;
+            Some((start, end))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of numbers in the set
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .map(|(start, end)| *end - *start)
+            .fold(T::ZERO, |sum, length| {
+                (sum + length).offset(1).expect("length too large")
+            })
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|(start, _)| *start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.intervals.last().map(|(_, end)| *end)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.intervals.partition_point(|(_, end)| *end < value);
+        self.intervals
+            .get(index)
+            .is_some_and(|(start, _)| *start <= value)
+    }
+
+    /// Whether all numbers of `other` are in this set, too
+    pub fn is_superset(&self, other: &IntervalSet<T>) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    /// Whether both sets have a number in common
+    pub fn overlaps(&self, other: &IntervalSet<T>) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut union = self.clone();
+        for (start, end) in other.intervals() {
+            union.insert(start, end);
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = vec![];
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            let (start, end) = (a.0.max(b.0), a.1.min(b.1));
+            if start <= end {
+                intervals.push((start, end));
+            }
+            // the interval ending first can't overlap anything else
+            match a.1 < b.1 {
+                true => i += 1,
+                false => j += 1,
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    /// The numbers of this set which aren't in `other`
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = vec![];
+        let mut removed = other.intervals.iter().peekable();
+        for &(mut start, end) in &self.intervals {
+            // skip what ends before this interval, it may still overlap the next one
+            while removed
+                .next_if(|(_, removed_end)| *removed_end < start)
+                .is_some()
+            {}
+            let mut rest = Some(start);
+            for &(removed_start, removed_end) in removed.clone() {
+                if removed_start > end {
+                    break;
+                }
+                if removed_start > start {
+                    intervals.push((start, removed_start.offset(-1).expect("start is smaller")));
+                }
+                rest = removed_end.offset(1).filter(|next| *next <= end);
+                match rest {
+                    Some(next) => start = next,
+                    None => break,
+                }
+            }
+            if let Some(start) = rest {
+                intervals.push((start, end));
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    /// The numbers of the set from `start` to `end`, both included
+    pub fn clip(&self, start: T, end: T) -> IntervalSet<T> {
+        self.intersection(&IntervalSet::interval(start, end))
+    }
+}
+
+impl<T: Coordinate> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(intervals: I) -> IntervalSet<T> {
+        let mut set = IntervalSet::new();
+        for (start, end) in intervals {
+            set.insert(start, end);
+        }
+        set
+    }
+}
+
+/// Lists the intervals like `{1..=3, 7..=7}`
+impl<T: Debug> Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let intervals: Vec<String> = self
+            .intervals
+            .iter()
+            .map(|(start, end)| format!("{:?}..={:?}", start, end))
+            .collect();
+        write!(f, "{{{}}}", intervals.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet<i64> {
+        intervals.iter().copied().collect()
+    }
+
+    #[test]
+    fn intervals_are_merged() {
+        let mut set = set(&[(5, 7), (1, 2)]);
+        assert_eq!(vec![(1, 2), (5, 7)], set.intervals().collect::<Vec<_>>());
+        set.insert(3, 3);
+        assert_eq!(vec![(1, 3), (5, 7)], set.intervals().collect::<Vec<_>>());
+        set.insert(10, 4);
+        assert_eq!(vec![(1, 10)], set.intervals().collect::<Vec<_>>());
+        assert_eq!(10, set.len());
+        assert_eq!("{1..=10}", format!("{:?}", set));
+    }
+
+    #[test]
+    fn sets_are_combined() {
+        let a = set(&[(-3, 13), (15, 25)]);
+        let b = set(&[(0, 20)]);
+        assert_eq!(set(&[(-3, 25)]), a.union(&b));
+        assert_eq!(set(&[(0, 13), (15, 20)]), a.intersection(&b));
+        assert_eq!(set(&[(-3, -1), (21, 25)]), a.difference(&b));
+        assert_eq!(set(&[(14, 14)]), b.difference(&a));
+        assert_eq!(set(&[(0, 13), (15, 20)]), a.clip(0, 20));
+        assert_eq!(vec![(14, 14)], a.gaps().collect::<Vec<_>>());
+
+        assert!(a.contains(14 - 1) && !a.contains(14) && !a.contains(26));
+        assert!(a.is_superset(&set(&[(0, 5), (16, 16)])));
+        assert!(!a.is_superset(&b));
+        assert!(a.overlaps(&b));
+        assert!(!a.overlaps(&set(&[(14, 14)])));
+        assert_eq!((Some(-3), Some(25)), (a.min(), a.max()));
+    }
+
+    #[test]
+    fn limits_of_the_type_are_kept() {
+        let set = IntervalSet::interval(0_usize, 3).union(&IntervalSet::interval(usize::MAX, 6));
+        assert_eq!(
+            vec![(0, 3), (6, usize::MAX)],
+            set.intervals().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            set,
+            set.union(&IntervalSet::interval(usize::MAX, usize::MAX))
+        );
+        assert_eq!(
+            IntervalSet::interval(0, 3),
+            set.difference(&IntervalSet::interval(5, usize::MAX))
+        );
+    }
+
+    /// Up to a handful of small intervals, so they often overlap or touch
+    fn intervals() -> impl Strategy<Value = Vec<(i64, i64)>> {
+        prop::collection::vec((-30_i64..30, 0_i64..8), 0..6).prop_map(|intervals| {
+            intervals
+                .into_iter()
+                .map(|(start, length)| (start, start + length))
+                .collect()
+        })
+    }
+
+    fn model(intervals: &[(i64, i64)]) -> BTreeSet<i64> {
+        intervals
+            .iter()
+            .flat_map(|(start, end)| *start..=*end)
+            .collect()
+    }
+
+    fn values(set: &IntervalSet<i64>) -> BTreeSet<i64> {
+        model(&set.intervals().collect::<Vec<_>>())
+    }
+
+    proptest! {
+        #[test]
+        fn sets_hold_what_was_inserted(intervals in intervals()) {
+            let set = set(&intervals);
+            let model = model(&intervals);
+            prop_assert_eq!(&model, &values(&set));
+            prop_assert_eq!(model.len() as i64, set.len());
+            for value in -40..45 {
+                prop_assert_eq!(model.contains(&value), set.contains(value));
+            }
+            // sorted, neither overlapping nor touching
+            let list: Vec<_> = set.intervals().collect();
+            prop_assert!(list.iter().all(|(start, end)| start <= end));
+            prop_assert!(list.windows(2).all(|pair| pair[0].1 + 1 < pair[1].0));
+        }
+
+        #[test]
+        fn sets_behave_like_sets(a in intervals(), b in intervals()) {
+            let (set_a, set_b) = (set(&a), set(&b));
+            let (model_a, model_b) = (model(&a), model(&b));
+
+            let union: BTreeSet<i64> = model_a.union(&model_b).copied().collect();
+            let intersection: BTreeSet<i64> = model_a.intersection(&model_b).copied().collect();
+            let difference: BTreeSet<i64> = model_a.difference(&model_b).copied().collect();
+            prop_assert_eq!(union, values(&set_a.union(&set_b)));
+            prop_assert_eq!(&intersection, &values(&set_a.intersection(&set_b)));
+            prop_assert_eq!(difference, values(&set_a.difference(&set_b)));
+            prop_assert_eq!(model_a.is_superset(&model_b), set_a.is_superset(&set_b));
+            prop_assert_eq!(!intersection.is_empty(), set_a.overlaps(&set_b));
+        }
+
+        #[test]
+        fn gaps_and_clips_fit(intervals in intervals(), start in -35_i64..35, length in 0_i64..20) {
+            let set = set(&intervals);
+            let expected = model(&intervals);
+
+            let clipped: BTreeSet<i64> = expected.range(start..=start + length).copied().collect();
+            prop_assert_eq!(clipped, values(&set.clip(start, start + length)));
+
+            let gaps: Vec<(i64, i64)> = set.gaps().collect();
+            if let (Some(min), Some(max)) = (set.min(), set.max()) {
+                let missing: BTreeSet<i64> = (min..=max).filter(|value| !expected.contains(value)).collect();
+                prop_assert_eq!(missing, model(&gaps));
+            } else {
+                prop_assert!(gaps.is_empty());
+            }
+        }
+    }
+}
@@ -1,9 +1,35 @@
 extern crate core;
 
+use animation::{Animation, View};
 use ansi_term::Colour::Yellow;
+use clap::ValueEnum;
+use random::Random;
+use serde::{Serialize, Serializer};
 use std::fmt::{Debug, Display, Formatter};
-use std::process::exit;
-use std::{fmt, fs};
+use std::io::Read;
+use std::path::Path;
+use std::{env, fmt, fs, io};
+use svg::Svg;
+
+pub mod animation;
+pub mod bench;
+pub mod cycle;
+pub mod error;
+pub mod explore;
+pub mod grid;
+pub mod http;
+pub mod image;
+pub mod interval;
+pub mod parse;
+pub mod point;
+pub mod random;
+pub mod scaffold;
+pub mod search;
+pub mod site;
+pub mod submit;
+pub mod svg;
+pub mod verify;
+pub mod watch;
 
 pub mod day01;
 pub mod day02;
@@ -31,49 +57,296 @@ pub mod day23;
 pub mod day24;
 pub mod day25;
 
-pub fn read_file(filename: &str) -> String {
-    match fs::read_to_string(filename) {
-        Ok(data) => data,
-        Err(_) => {
-            println!("Couln't read file {}", filename);
-            exit(1);
+pub use error::{Error, Result};
+pub use grid::Grid;
+pub use interval::IntervalSet;
+pub use point::{Point, Point3};
+
+/// A puzzle solver for one day of the calendar
+pub trait Solution: Sync {
+    fn day(&self) -> u32;
+    fn title(&self) -> &'static str;
+    /// Only parses the input, so the parser can be timed on its own. The parts parse the input
+    /// again, their times include this.
+    fn parse(&self, _input: &str) -> Result<()> {
+        Ok(())
+    }
+    fn part1(&self, input: &str) -> Result<Answer>;
+    fn part2(&self, input: &str) -> Result<Answer>;
+
+    /// Plays the simulation behind the puzzle, frame by frame
+    fn animate(&self, _input: &str, _animation: &mut Animation) -> Result<()> {
+        Err(Error::invalid(&format!(
+            "There is no animation for day {}",
+            self.day()
+        )))
+    }
+
+    /// Draws the geometry of the puzzle, like paths or areas
+    fn draw(&self, _input: &str) -> Result<Svg> {
+        Err(Error::invalid(&format!(
+            "There is no drawing for day {}",
+            self.day()
+        )))
+    }
+
+    /// The grid the puzzle ends with, e.g. the sand at rest, to be scrolled through in the
+    /// explorer
+    fn view(&self, _input: &str) -> Result<View> {
+        Err(Error::invalid(&format!(
+            "There is no view for day {}",
+            self.day()
+        )))
+    }
+
+    /// A random input of about `size` lines, items or cells, as the parser expects it
+    fn generate(&self, _size: usize, _random: &mut Random) -> Result<String> {
+        Err(Error::invalid(&format!(
+            "There is no generator for day {}",
+            self.day()
+        )))
+    }
+
+    fn solve(&self, part: u32, input: &str) -> Result<Answer> {
+        match part {
+            1 => self.part1(input),
+            2 => self.part2(input),
+            _ => Err(Error::invalid(&format!("There is no part {}", part))),
         }
     }
 }
 
-pub fn print_headline(title: &str) {
-    println!();
-    println!("{}", Yellow.bold().paint(title));
-    let line = title.chars().into_iter().map(|_| "=").collect::<String>();
-    println!("{}", Yellow.bold().paint(line));
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    Unsolved,
 }
 
-#[derive(PartialEq, Clone, Copy, Eq)]
-pub struct Point<T> {
-    x: T,
-    y: T,
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Unsolved => write!(f, "not solved yet"),
+        }
+    }
 }
 
-impl<T> Point<T> {
-    pub fn new(x: T, y: T) -> Point<T> {
-        Point { x, y }
+macro_rules! answer_from_number {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(number: $t) -> Answer {
+                Answer::Number(number as i64)
+            }
+        })*
+    };
+}
+
+answer_from_number!(i32, i64, u32, u64, usize);
+
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Answer::Number(number) => serializer.serialize_i64(*number),
+            Answer::Text(text) => serializer.serialize_str(text),
+            Answer::Unsolved => serializer.serialize_none(),
+        }
     }
 }
 
-impl Display for Point<i32> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}/{}", self.x, self.y)
+impl From<String> for Answer {
+    fn from(text: String) -> Answer {
+        Answer::Text(text)
     }
 }
 
-impl Debug for Point<usize> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}|{})", self.x, self.y)
+static SOLUTIONS: &[&dyn Solution] = &[
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+/// All registered days, ordered by day number
+pub fn solutions() -> &'static [&'static dyn Solution] {
+    SOLUTIONS
+}
+
+pub fn solution(day: u32) -> Option<&'static dyn Solution> {
+    SOLUTIONS
+        .iter()
+        .find(|solution| solution.day() == day)
+        .copied()
+}
+
+/// Environment variable overriding the directory the puzzle inputs are read from
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+const DEFAULT_INPUT_DIR: &str = "input";
+
+/// Where the input for a run of a day comes from
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum InputSource {
+    File(String),
+    Stdin,
+}
+
+impl InputSource {
+    pub fn read(&self) -> Result<String> {
+        match self {
+            InputSource::File(filename) => read_file(filename),
+            InputSource::Stdin => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(|source| Error::Io {
+                        file: self.to_string(),
+                        source,
+                    })?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+impl Display for InputSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::File(filename) => write!(f, "{}", filename),
+            InputSource::Stdin => write!(f, "<stdin>"),
+        }
+    }
+}
+
+/// Decides which input a day reads. An explicit `path` wins (`-` being stdin), otherwise it's
+/// `dayNN.txt` or `dayNN-<variant>.txt` inside of `input_dir`, which falls back to
+/// [`INPUT_DIR_VAR`] and then to `input`.
+pub fn resolve_input(
+    day: u32,
+    path: Option<&str>,
+    variant: Option<&str>,
+    input_dir: Option<&str>,
+) -> InputSource {
+    match path {
+        Some("-") => InputSource::Stdin,
+        Some(path) => InputSource::File(path.to_string()),
+        None => {
+            let input_dir = input_dir_or_default(input_dir);
+            let filename = match variant {
+                Some(variant) => format!("day{:02}-{}.txt", day, variant),
+                None => format!("day{:02}.txt", day),
+            };
+            InputSource::File(Path::new(&input_dir).join(filename).display().to_string())
+        }
+    }
+}
+
+/// `input_dir` if it's given, otherwise [`INPUT_DIR_VAR`] and then `input`
+pub fn input_dir_or_default(input_dir: Option<&str>) -> String {
+    match input_dir {
+        Some(input_dir) => input_dir.to_string(),
+        None => env::var(INPUT_DIR_VAR).unwrap_or_else(|_| DEFAULT_INPUT_DIR.to_string()),
+    }
+}
+
+/// The variants of the example inputs `dayNN-<variant>.txt` of a day, ordered by name
+pub fn input_variants(day: u32, input_dir: Option<&str>) -> Vec<String> {
+    let prefix = format!("day{:02}-", day);
+    let mut variants: Vec<String> = fs::read_dir(input_dir_or_default(input_dir))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            let variant = name.strip_prefix(&prefix)?.strip_suffix(".txt")?;
+            Some(variant.to_string())
+        })
+        .collect();
+    variants.sort();
+    variants
+}
+
+pub fn read_file(filename: &str) -> Result<String> {
+    fs::read_to_string(filename).map_err(|source| Error::Io {
+        file: filename.to_string(),
+        source,
+    })
+}
+
+/// How results get printed
+#[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// colourised text
+    Text,
+    /// text without ANSI colours
+    Plain,
+    /// one JSON object per line
+    Json,
+}
+
+pub fn print_headline(title: &str, format: Format) {
+    let line = title.chars().map(|_| "=").collect::<String>();
+    match format {
+        Format::Text => {
+            println!();
+            println!("{}", Yellow.bold().paint(title));
+            println!("{}", Yellow.bold().paint(line));
+        }
+        Format::Plain => {
+            println!();
+            println!("{}", title);
+            println!("{}", line);
+        }
+        Format::Json => {}
     }
 }
 
-impl Debug for Point<i32> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}|{})", self.x, self.y)
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn inputs_are_resolved() {
+        assert_eq!(
+            InputSource::File("input/day08.txt".to_string()),
+            resolve_input(8, None, None, Some("input"))
+        );
+        assert_eq!(
+            InputSource::File("examples/day16-mini.txt".to_string()),
+            resolve_input(16, None, Some("mini"), Some("examples"))
+        );
+        assert_eq!(
+            InputSource::File("my/input.txt".to_string()),
+            resolve_input(3, Some("my/input.txt"), Some("test"), Some("input"))
+        );
+        assert_eq!(InputSource::Stdin, resolve_input(3, Some("-"), None, None));
+    }
+
+    #[test]
+    fn variants_are_found() {
+        assert_eq!(vec!["mini", "test"], input_variants(16, Some("input")));
+        assert_eq!(vec!["larger", "test"], input_variants(9, Some("input")));
+        assert!(input_variants(16, Some("missing")).is_empty());
     }
 }
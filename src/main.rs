@@ -1,142 +1,1212 @@
-use adventofcode::{day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day20, day21, print_headline};
-use clap::Parser;
+use adventofcode::animation::{Animation, Settings};
+use adventofcode::bench::{format_duration, run_each, timed, timed_with_limit, Interrupted, Stats};
+use adventofcode::explore::Explorer;
+use adventofcode::http::{Backend, RateLimit};
+use adventofcode::image::ImageOptions;
+use adventofcode::random::Random;
+use adventofcode::scaffold::new_day;
+use adventofcode::site::{
+    base_url_or_default, cached_input, fetch_input, read_session, Fetched, Site, SESSION_FILE,
+};
+use adventofcode::submit::{submit_answer, Attempt, History, Verdict, HISTORY_FILE};
+use adventofcode::verify::{read_answers, Status, ANSWERS_FILE};
+use adventofcode::watch::{run_tests, watched_files, Snapshot, TestRun};
+use adventofcode::{
+    print_headline, resolve_input, solution, solutions, Answer, Error, Format, Result, Solution,
+};
+use ansi_term::Colour::{Green, Red, Yellow};
+use ansi_term::Style;
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a part may take when all days are run
+const DEFAULT_TIMEOUT_SECONDS: f64 = 10.0;
+/// Remembers when the website was last asked for something, so separate runs keep their distance
+const LAST_REQUEST_FILE: &str = "output/last-request";
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(group(ArgGroup::new("playback").args(["animate", "record"]).multiple(true)))]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
     /// the day for which to run the code
     day: Option<u32>,
+    #[command(flatten)]
+    input: InputArgs,
+    #[command(flatten)]
+    selection: Selection,
+    /// how the results are printed
+    #[arg(long, value_enum, default_value_t = Format::Text, global = true)]
+    format: Format,
+    /// play the simulation of the day in the terminal before solving it
+    #[arg(long, requires = "day")]
+    animate: bool,
+    /// record the simulation of the day as animated PNG or GIF, e.g. `output/day14.gif`
+    #[arg(long, requires = "day")]
+    record: Option<PathBuf>,
+    #[command(flatten)]
+    playback: PlaybackArgs,
+    /// draw the puzzle as `output/dayNN.svg`
+    #[arg(long, requires = "day")]
+    svg: bool,
+}
+
+#[derive(Args)]
+struct PlaybackArgs {
+    /// frames per second of the animation, 0 plays it as fast as possible
+    #[arg(long, default_value_t = 20.0, requires = "playback")]
+    fps: f64,
+    /// the size of the animation in characters, e.g. `120x40`
+    #[arg(long, default_value = "80x24", value_parser = parse_size, requires = "animate")]
+    viewport: (usize, usize),
+    /// only record every Nth frame, so long simulations make smaller images
+    #[arg(long, default_value_t = 1, requires = "record")]
+    every: usize,
+    /// the width and height of a cell in the recording, in pixels
+    #[arg(long, default_value_t = 4, requires = "record")]
+    scale: u32,
+}
+
+impl PlaybackArgs {
+    fn settings(&self, format: Format) -> Settings {
+        Settings {
+            fps: self.fps,
+            width: self.viewport.0,
+            // the caption needs a line, too
+            height: self.viewport.1.saturating_sub(1),
+            colour: format == Format::Text,
+        }
+    }
+
+    fn image_options(&self) -> ImageOptions {
+        let default = ImageOptions::default();
+        ImageOptions {
+            scale: self.scale,
+            delay: match self.fps > 0.0 {
+                true => Duration::from_secs_f64(1.0 / self.fps),
+                false => default.delay,
+            },
+        }
+    }
+}
+
+fn parse_size(text: &str) -> std::result::Result<(usize, usize), String> {
+    let size = text.split_once('x').and_then(|(width, height)| {
+        Some((width.parse::<usize>().ok()?, height.parse::<usize>().ok()?))
+    });
+    match size {
+        Some((width, height)) if width > 0 && height > 1 => Ok((width, height)),
+        _ => Err("expected a size like `80x24`".to_string()),
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run every part repeatedly and report min/median/max timings
+    Bench {
+        /// the day to benchmark, all days if missing
+        day: Option<u32>,
+        /// how often each part is run
+        #[arg(short = 'n', long, default_value_t = 10)]
+        repeat: usize,
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        selection: Selection,
+    },
+    /// Check the solutions against the recorded answers
+    Verify {
+        /// the day to check, all recorded days if missing
+        day: Option<u32>,
+        /// the file with the recorded answers
+        #[arg(long, default_value = ANSWERS_FILE)]
+        answers: String,
+        /// skip answers marked as slow
+        #[arg(long)]
+        quick: bool,
+    },
+    /// Print a random input for a day, e.g. to time the solutions on bigger inputs
+    Generate {
+        /// the day to generate an input for
+        day: u32,
+        /// how big the input gets, in lines, items or cells depending on the day
+        #[arg(long, default_value_t = 100)]
+        size: usize,
+        /// the same seed always gives the same input
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Explore the days interactively: pick days and inputs, run parts and scroll through grids
+    Explore {
+        /// directory containing the inputs [default: $AOC_INPUT_DIR or `input`]
+        #[arg(long)]
+        input_dir: Option<String>,
+        /// the size of grid views in characters, e.g. `120x40`
+        #[arg(long, default_value = "80x24", value_parser = parse_size)]
+        viewport: (usize, usize),
+    },
+    /// Solve a day again whenever its inputs or sources change, and show how the answers changed
+    Watch {
+        /// the day to watch
+        day: u32,
+        #[command(flatten)]
+        input: InputArgs,
+        /// also run the tests of the day after every change
+        #[arg(long)]
+        tests: bool,
+        /// how often the files are checked, in seconds
+        #[arg(long, default_value_t = 0.5)]
+        interval: f64,
+        /// give up on a part after this many seconds
+        #[arg(long)]
+        timeout: Option<f64>,
+    },
+    /// Download the inputs of days which aren't there yet, with the session token
+    Fetch {
+        /// the days to download
+        #[arg(required = true)]
+        days: Vec<u32>,
+        /// directory containing the inputs [default: $AOC_INPUT_DIR or `input`]
+        #[arg(long)]
+        input_dir: Option<String>,
+        #[command(flatten)]
+        site: SiteArgs,
+    },
+    /// Send the answer of a part to the website, unless the history shows it's wrong
+    Submit {
+        /// the day to submit
+        day: u32,
+        /// the part to submit
+        #[arg(value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: u32,
+        /// send this answer instead of solving the part with the real input
+        #[arg(long)]
+        answer: Option<String>,
+        /// directory containing the inputs [default: $AOC_INPUT_DIR or `input`]
+        #[arg(long)]
+        input_dir: Option<String>,
+        /// the file with all answers sent so far
+        #[arg(long, default_value = HISTORY_FILE)]
+        history: String,
+        #[command(flatten)]
+        site: SiteArgs,
+    },
+    /// Create the module and inputs of a new day and register it
+    NewDay {
+        /// the day to create
+        day: u32,
+        /// the title of the puzzle
+        #[arg(long)]
+        title: String,
+    },
+}
+
+#[derive(Args)]
+struct InputArgs {
+    /// read the input from this file instead, `-` reads from stdin
+    #[arg(short, long, requires = "day", conflicts_with_all = ["test", "variant"])]
+    input: Option<String>,
+    /// use the example input dayNN-test.txt
+    #[arg(short, long, conflicts_with = "variant")]
+    test: bool,
+    /// use the input dayNN-<VARIANT>.txt, e.g. `mini`
+    #[arg(long)]
+    variant: Option<String>,
+    /// directory containing the inputs [default: $AOC_INPUT_DIR or `input`]
+    #[arg(long)]
+    input_dir: Option<String>,
+}
+
+impl InputArgs {
+    fn variant(&self) -> Option<&str> {
+        match self.test {
+            true => Some("test"),
+            false => self.variant.as_deref(),
+        }
+    }
+
+    /// Reads and parses the input of the day, returns the input's name along with it
+    fn load(&self, solution: &dyn Solution) -> (String, Result<Loaded>) {
+        let source = resolve_input(
+            solution.day(),
+            self.input.as_deref(),
+            self.variant(),
+            self.input_dir.as_deref(),
+        );
+        let filename = source.to_string();
+        let loaded = source.read().and_then(|input| {
+            let (parsed, parse_time) = timed(|| solution.parse(&input));
+            parsed.map(|_| Loaded {
+                input: input.into(),
+                parse_time,
+            })
+        });
+        let loaded = loaded.map_err(|error| error.in_file(&filename));
+        (filename, loaded)
+    }
+}
+
+#[derive(Args)]
+struct SiteArgs {
+    /// where the website is, e.g. a local stand-in [default: $AOC_BASE_URL or adventofcode.com]
+    #[arg(long)]
+    base_url: Option<String>,
+    /// the file with the session token, used unless $AOC_SESSION is set
+    #[arg(long, default_value = SESSION_FILE)]
+    session_file: String,
+    /// how requests are sent
+    #[arg(long, value_enum, default_value_t = Backend::Auto)]
+    http: Backend,
+    /// the least number of seconds between two requests
+    #[arg(long, default_value_t = 5.0)]
+    interval: f64,
+}
+
+impl SiteArgs {
+    fn site(&self) -> Result<Site> {
+        let base_url = base_url_or_default(self.base_url.as_deref());
+        let limit = RateLimit::new(
+            Duration::from_secs_f64(self.interval),
+            Some(PathBuf::from(LAST_REQUEST_FILE)),
+        );
+        Ok(Site::new(
+            &base_url,
+            &read_session(&self.session_file)?,
+            self.http.http(&base_url),
+            limit,
+        ))
+    }
+}
+
+#[derive(Args)]
+struct Selection {
+    /// only run these days, e.g. `--only 1,5,12`
+    #[arg(long, value_delimiter = ',', conflicts_with = "day")]
+    only: Vec<u32>,
+    /// leave out these days, e.g. `--skip 16,19`
+    #[arg(long, value_delimiter = ',', conflicts_with = "day")]
+    skip: Vec<u32>,
+    /// give up on a part after this many seconds [default: 10 when running all days]
+    #[arg(long)]
+    timeout: Option<f64>,
+}
+
+impl Selection {
+    /// The days to run, or the first day which doesn't exist
+    fn days(&self, day: Option<u32>) -> std::result::Result<Vec<&'static dyn Solution>, u32> {
+        if let Some(day) = day {
+            return solution(day).map(|solution| vec![solution]).ok_or(day);
+        }
+        if let Some(unknown) = self
+            .only
+            .iter()
+            .chain(&self.skip)
+            .find(|day| solution(**day).is_none())
+        {
+            return Err(*unknown);
+        }
+        Ok(solutions()
+            .iter()
+            .filter(|solution| self.only.is_empty() || self.only.contains(&solution.day()))
+            .filter(|solution| !self.skip.contains(&solution.day()))
+            .copied()
+            .collect())
+    }
+
+    fn limit(&self, day: Option<u32>) -> Option<Duration> {
+        let default = match day {
+            Some(_) => None,
+            None => Some(DEFAULT_TIMEOUT_SECONDS),
+        };
+        self.timeout.or(default).map(Duration::from_secs_f64)
+    }
+}
+
+/// The input of a day which was read and parsed successfully
+struct Loaded {
+    input: Arc<str>,
+    parse_time: Duration,
+}
+
+/// What came out of the first run of a part
+enum Outcome {
+    Solved(Answer, Duration),
+    TimedOut(Duration),
+    Failed(Error),
+}
+
+impl Outcome {
+    fn status(&self) -> &'static str {
+        match self {
+            Outcome::Solved(Answer::Unsolved, _) => "unsolved",
+            Outcome::Solved(_, _) => "solved",
+            Outcome::TimedOut(_) => "timeout",
+            Outcome::Failed(_) => "error",
+        }
+    }
+
+    /// The common fields of the JSON object describing this part
+    fn to_json(&self, solution: &dyn Solution, part: u32, filename: &str) -> Value {
+        let mut object = json!({
+            "day": solution.day(),
+            "title": solution.title(),
+            "part": part,
+            "input": filename,
+            "status": self.status(),
+        });
+        match self {
+            Outcome::Solved(Answer::Unsolved, _) => {}
+            Outcome::Solved(answer, _) => object["answer"] = json!(answer),
+            Outcome::TimedOut(limit) => object["timeout_ms"] = json!(milliseconds(*limit)),
+            Outcome::Failed(error) => object["error"] = json!(error.to_string()),
+        }
+        object
+    }
+}
+
+/// Everything that came out of running one day, it's printed once the day is done
+struct DayRun {
+    solution: &'static dyn Solution,
+    filename: String,
+    /// the parse time and the outcome of both parts
+    result: Result<(Duration, Vec<Outcome>)>,
+}
+
+impl DayRun {
+    /// Whether the input couldn't be loaded or a part returned an error or timed out
+    fn failed(&self) -> bool {
+        match &self.result {
+            Ok((_, outcomes)) => outcomes
+                .iter()
+                .any(|outcome| matches!(outcome, Outcome::Failed(_) | Outcome::TimedOut(_))),
+            Err(_) => true,
+        }
+    }
+}
+
+/// The median timings of one benchmarked day, `None` where a step was skipped or failed
+struct BenchSummary {
+    day: u32,
+    title: &'static str,
+    parse: Option<Duration>,
+    parts: [Option<Duration>; 2],
 }
 
-fn main() {
+fn main() -> ExitCode {
     let args = Cli::parse();
+    let printer = Printer {
+        format: args.format,
+    };
+
+    match args.command {
+        Some(Command::Verify {
+            day,
+            answers,
+            quick,
+        }) => return verify(day, &answers, quick, &printer),
+        Some(Command::Explore {
+            input_dir,
+            viewport,
+        }) => {
+            if let Err(error) = explore(input_dir.as_deref(), viewport, args.format) {
+                printer.json(json!({ "status": "error", "error": error.to_string() }));
+                printer.text_error(&error);
+                return ExitCode::FAILURE;
+            }
+        }
+        Some(Command::Watch {
+            day,
+            input,
+            tests,
+            interval,
+            timeout,
+        }) => match solution(day) {
+            Some(solution) => {
+                let limit = timeout.map(Duration::from_secs_f64);
+                let interval = Duration::from_secs_f64(interval);
+                if let Err(error) = watch(solution, &input, tests, interval, limit, &printer) {
+                    printer.json(json!({ "status": "error", "error": error.to_string() }));
+                    printer.text_error(&error);
+                    return ExitCode::FAILURE;
+                }
+            }
+            None => {
+                printer.invalid_day(day);
+                return ExitCode::FAILURE;
+            }
+        },
+        Some(Command::Fetch {
+            days,
+            input_dir,
+            site,
+        }) => return fetch(&days, input_dir.as_deref(), &site, &printer),
+        Some(Command::Submit {
+            day,
+            part,
+            answer,
+            input_dir,
+            history,
+            site,
+        }) => match solution(day) {
+            Some(solution) => {
+                let answer = answer.map(Ok).unwrap_or_else(|| {
+                    let source = resolve_input(day, None, None, input_dir.as_deref());
+                    let filename = source.to_string();
+                    match source.read().and_then(|input| solution.solve(part, &input)) {
+                        Ok(Answer::Unsolved) => Err(Error::invalid(&format!(
+                            "Part {} of day {} isn't solved yet",
+                            part, day
+                        ))),
+                        Ok(answer) => Ok(answer.to_string()),
+                        Err(error) => Err(error.in_file(&filename)),
+                    }
+                });
+                let submitted =
+                    answer.and_then(|answer| submit(day, part, &answer, &history, &site, &printer));
+                match submitted {
+                    Ok(Verdict::Correct) => {}
+                    Ok(_) => return ExitCode::FAILURE,
+                    Err(error) => {
+                        printer.json(json!({ "status": "error", "error": error.to_string() }));
+                        printer.text_error(&error);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            None => {
+                printer.invalid_day(day);
+                return ExitCode::FAILURE;
+            }
+        },
+        Some(Command::NewDay { day, title }) => match new_day(Path::new("."), day, &title) {
+            Ok(written) => {
+                for file in written {
+                    printer.json(json!({ "day": day, "written": file }));
+                    printer.text(&format!("wrote {}", file.display()));
+                }
+            }
+            Err(error) => {
+                printer.json(json!({ "status": "error", "error": error.to_string() }));
+                printer.text_error(&error);
+                return ExitCode::FAILURE;
+            }
+        },
+        Some(Command::Generate { day, size, seed }) => match solution(day) {
+            Some(solution) => match solution.generate(size, &mut Random::new(seed)) {
+                Ok(input) => {
+                    printer.json(json!({ "day": day, "size": size, "seed": seed, "input": input }));
+                    if args.format != Format::Json {
+                        print!("{}", input);
+                    }
+                }
+                Err(error) => {
+                    printer.json(json!({ "status": "error", "error": error.to_string() }));
+                    printer.text_error(&error);
+                    return ExitCode::FAILURE;
+                }
+            },
+            None => {
+                printer.invalid_day(day);
+                return ExitCode::FAILURE;
+            }
+        },
+        Some(Command::Bench {
+            day,
+            repeat,
+            input,
+            selection,
+        }) => match selection.days(day) {
+            Ok(days) => {
+                // days are benchmarked one after another, so they don't compete for the CPU
+                let limit = selection.limit(day);
+                let summaries: Vec<BenchSummary> = days
+                    .iter()
+                    .filter_map(|solution| bench_day(*solution, repeat, &input, limit, &printer))
+                    .collect();
+                printer.bench_summary(&summaries, repeat);
+            }
+            Err(day) => {
+                printer.invalid_day(day);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => match args.selection.days(args.day) {
+            Ok(days) => {
+                if args.animate || args.record.is_some() {
+                    if let Err(error) = play(days[0], &args, &printer) {
+                        printer.json(json!({ "status": "error", "error": error.to_string() }));
+                        printer.text_error(&error);
+                        return ExitCode::FAILURE;
+                    }
+                }
+                if args.svg {
+                    if let Err(error) = draw(days[0], &args.input, &printer) {
+                        printer.json(json!({ "status": "error", "error": error.to_string() }));
+                        printer.text_error(&error);
+                        return ExitCode::FAILURE;
+                    }
+                }
+                let limit = args.selection.limit(args.day);
+                let runs: Vec<DayRun> =
+                    run_each(&days, |solution| run_day(*solution, &args.input, limit));
+                for run in &runs {
+                    printer.day_run(run);
+                }
+                if runs.iter().any(DayRun::failed) {
+                    return ExitCode::FAILURE;
+                }
+            }
+            Err(day) => {
+                printer.invalid_day(day);
+                return ExitCode::FAILURE;
+            }
+        },
+    }
+    ExitCode::SUCCESS
+}
+
+/// Plays the simulation of the day in the terminal and/or records it as image
+fn play(solution: &dyn Solution, args: &Cli, printer: &Printer) -> Result<()> {
+    if args.animate && args.format == Format::Json {
+        return Err(Error::invalid(
+            "Animations can't be combined with JSON output",
+        ));
+    }
+    let (_, loaded) = args.input.load(solution);
+    let loaded = loaded?;
+    let mut animation = match args.animate {
+        true => Animation::stdout(args.playback.settings(args.format)),
+        false => Animation::sink(),
+    };
+    if args.record.is_some() {
+        animation.record(args.playback.every);
+    }
+    let played = solution.animate(&loaded.input, &mut animation);
+    animation.finish()?;
+    played?;
+
+    if let Some(path) = &args.record {
+        animation.save(path, args.playback.image_options())?;
+        printer.json(json!({ "day": solution.day(), "written": path }));
+        printer.text(&format!("wrote {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Polls the files of the day and solves it again whenever one of them changed, until it's
+/// interrupted
+fn watch(
+    solution: &'static dyn Solution,
+    input_args: &InputArgs,
+    tests: bool,
+    interval: Duration,
+    limit: Option<Duration>,
+    printer: &Printer,
+) -> Result<()> {
+    if input_args.input.as_deref() == Some("-") {
+        return Err(Error::invalid("Standard input can't be watched"));
+    }
+    let mut previous: Option<Vec<(String, String)>> = None;
+    let mut snapshot = Snapshot::default();
+    loop {
+        let mut files = watched_files(solution.day(), input_args.input_dir.as_deref());
+        files.extend(input_args.input.iter().map(PathBuf::from));
+        let current = Snapshot::take(&files);
+        let changes = current.changes(&snapshot);
+        snapshot = current;
+        if changes.is_empty() {
+            thread::sleep(interval);
+            continue;
+        }
+
+        if previous.is_some() {
+            for file in &changes {
+                printer.json(json!({ "day": solution.day(), "changed": file }));
+                printer.text(&format!("changed {}", file.display()));
+            }
+            if changes.iter().any(|file| file.starts_with("src")) {
+                printer.text(&printer.paint(
+                    Yellow.normal(),
+                    "The sources changed, the answers still come from the running build: restart \
+                     `watch` to solve with the new code",
+                ));
+            }
+        }
+        let run = run_day(solution, input_args, limit);
+        previous = Some(printer.watched_run(&run, previous.as_deref()));
+        if tests {
+            printer.test_run(solution, &run_tests(solution.day())?);
+        }
+        printer.text(&printer.paint(
+            Style::new().dimmed(),
+            &format!("watching {} files, Ctrl-C stops", files.len()),
+        ));
+    }
+}
+
+/// Downloads the inputs which are missing, the session token is only needed if one is
+fn fetch(
+    days: &[u32],
+    input_dir: Option<&str>,
+    site_args: &SiteArgs,
+    printer: &Printer,
+) -> ExitCode {
+    if let Some(day) = days.iter().find(|day| solution(**day).is_none()) {
+        printer.invalid_day(*day);
+        return ExitCode::FAILURE;
+    }
+    let mut site = None;
+    let mut failed = false;
+    for &day in days {
+        let fetched = match (cached_input(day, input_dir), &mut site) {
+            (Some(path), _) => Ok(Fetched::Cached(path)),
+            (None, Some(site)) => fetch_input(site, day, input_dir),
+            (None, None) => site_args
+                .site()
+                .and_then(|new| fetch_input(site.insert(new), day, input_dir)),
+        };
+        match fetched {
+            Ok(Fetched::Cached(path)) => {
+                printer.json(json!({ "day": day, "status": "cached", "file": path }));
+                printer.text(&format!(
+                    "day {:02}: {} is there already",
+                    day,
+                    path.display()
+                ));
+            }
+            Ok(Fetched::Downloaded(path)) => {
+                printer.json(json!({ "day": day, "status": "downloaded", "file": path }));
+                printer.text(&format!(
+                    "day {:02}: {}",
+                    day,
+                    printer.paint(Green.normal(), &format!("downloaded {}", path.display()))
+                ));
+            }
+            Err(error) => {
+                printer.json(json!({ "day": day, "status": "error", "error": error.to_string() }));
+                printer.text_error(&error);
+                failed = true;
+            }
+        }
+    }
+    match failed {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+/// Sends `answer` unless the history knows it's wrong, and records the attempt
+fn submit(
+    day: u32,
+    part: u32,
+    answer: &str,
+    history_file: &str,
+    site_args: &SiteArgs,
+    printer: &Printer,
+) -> Result<Verdict> {
+    let now = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    };
+    let mut history = History::read(history_file)?;
+    history.check(day, part, answer, now())?;
+
+    let reply = submit_answer(&mut site_args.site()?, day, part, answer)?;
+    history.record(Attempt::new(day, part, answer, &reply, now()));
+    history.write(history_file)?;
+
+    let bounds = history.bounds(day, part);
+    printer.json(json!({
+        "day": day,
+        "part": part,
+        "answer": answer,
+        "status": reply.verdict,
+        "message": reply.message,
+        "wait_s": reply.wait.map(|wait| wait.as_secs()),
+        "bounds": [bounds.0, bounds.1],
+    }));
+    let style = match reply.verdict {
+        Verdict::Correct => Green.bold(),
+        verdict if verdict.is_wrong() => Red.bold(),
+        _ => Yellow.bold(),
+    };
+    printer.text(&format!(
+        "day {:02} part {}: {}",
+        day,
+        part,
+        printer.paint(style, &format!("{} {}", answer, reply.verdict.describe()))
+    ));
+    printer.text(&printer.paint(Style::new().dimmed(), &reply.message));
+    if reply.verdict != Verdict::Correct {
+        let range = match bounds {
+            (Some(low), Some(high)) => format!("between {} and {}", low, high),
+            (Some(low), None) => format!("above {}", low),
+            (None, Some(high)) => format!("below {}", high),
+            (None, None) => String::new(),
+        };
+        if !range.is_empty() {
+            printer.text(&format!("the answer lies {}", range));
+        }
+    }
+    Ok(reply.verdict)
+}
+
+/// Runs the explorer on the terminal until it's left
+fn explore(input_dir: Option<&str>, (width, height): (usize, usize), format: Format) -> Result<()> {
+    if format == Format::Json {
+        return Err(Error::invalid(
+            "The explorer can't be combined with JSON output",
+        ));
+    }
+    let settings = Settings {
+        fps: 0.0,
+        width,
+        // the caption and the status line need a line each
+        height: height.saturating_sub(2).max(1),
+        colour: format == Format::Text,
+    };
+    Explorer::stdio(settings, input_dir).run()
+}
+
+/// Writes the drawing of the day to `output/dayNN.svg`
+fn draw(solution: &dyn Solution, input_args: &InputArgs, printer: &Printer) -> Result<()> {
+    let (_, loaded) = input_args.load(solution);
+    let svg = solution.draw(&loaded?.input)?;
+    let path = PathBuf::from(format!("output/day{:02}.svg", solution.day()));
+    svg.save(&path)?;
+    printer.json(json!({ "day": solution.day(), "written": path }));
+    printer.text(&format!("wrote {}", path.display()));
+    Ok(())
+}
+
+/// Runs a part once, giving up after `limit`
+fn first_run(
+    solution: &'static dyn Solution,
+    part: u32,
+    input: &Arc<str>,
+    filename: &str,
+    limit: Option<Duration>,
+) -> Outcome {
+    let input = Arc::clone(input);
+    match timed_with_limit(limit, move || solution.solve(part, &input)) {
+        Ok((Ok(answer), duration)) => Outcome::Solved(answer, duration),
+        Ok((Err(error), _)) => Outcome::Failed(error.in_file(filename)),
+        Err(Interrupted::TimedOut) => Outcome::TimedOut(limit.unwrap_or_default()),
+        Err(Interrupted::Panicked) => {
+            Outcome::Failed(Error::invalid(&format!("Part {} panicked", part)).in_file(filename))
+        }
+    }
+}
+
+fn run_day(
+    solution: &'static dyn Solution,
+    input_args: &InputArgs,
+    limit: Option<Duration>,
+) -> DayRun {
+    let (filename, loaded) = input_args.load(solution);
+    let result = loaded.map(|loaded| {
+        let outcomes = (1..=2)
+            .map(|part| first_run(solution, part, &loaded.input, &filename, limit))
+            .collect();
+        (loaded.parse_time, outcomes)
+    });
+
+    DayRun {
+        solution,
+        filename,
+        result,
+    }
+}
+
+fn bench_day(
+    solution: &'static dyn Solution,
+    repeat: usize,
+    input_args: &InputArgs,
+    limit: Option<Duration>,
+    printer: &Printer,
+) -> Option<BenchSummary> {
+    // loading the input is the warm-up of the parser, the first run of a part sorts out broken,
+    // unsolved or too slow parts
+    printer.day_headline(solution);
+    let (filename, loaded) = input_args.load(solution);
+    let loaded = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            printer.load_error(solution, &filename, &error);
+            return None;
+        }
+    };
+    let mut summary = BenchSummary {
+        day: solution.day(),
+        title: solution.title(),
+        parse: None,
+        parts: [None, None],
+    };
+
+    let parse = Stats::new(&repeated(repeat, || solution.parse(&loaded.input)));
+    summary.parse = parse.map(|stats| stats.median);
+    printer.stats("Parse", parse);
+
+    for part in 1..=2 {
+        let outcome = first_run(solution, part, &loaded.input, &filename, limit);
+        let stats = match outcome {
+            Outcome::Solved(Answer::Unsolved, _) | Outcome::TimedOut(_) | Outcome::Failed(_) => {
+                None
+            }
+            Outcome::Solved(_, _) => {
+                Stats::new(&repeated(repeat, || solution.solve(part, &loaded.input)))
+            }
+        };
+        summary.parts[part as usize - 1] = stats.map(|stats| stats.median);
+
+        let mut object = outcome.to_json(solution, part, &filename);
+        object["runs"] = json!(repeat);
+        object["parse_ms"] = stats_to_json(parse);
+        object["time_ms"] = stats_to_json(stats);
+        printer.json(object);
+        match outcome {
+            Outcome::Solved(Answer::Unsolved, _) | Outcome::TimedOut(_) | Outcome::Failed(_) => {
+                printer.outcome(part, &outcome, |_| String::new())
+            }
+            Outcome::Solved(_, _) => printer.stats(&format!("Part {}", part), stats),
+        }
+    }
+
+    Some(summary)
+}
+
+fn repeated<T>(repeat: usize, step: impl Fn() -> T) -> Vec<Duration> {
+    (0..repeat).map(|_| timed(&step).1).collect()
+}
+
+fn verify(day: Option<u32>, answers_file: &str, quick: bool, printer: &Printer) -> ExitCode {
+    let answers = match read_answers(answers_file) {
+        Ok(answers) => answers,
+        Err(error) => {
+            printer.json(json!({ "status": "error", "error": error.to_string() }));
+            printer.text_error(&error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // one row per day and input file, one column per part
+    let mut rows: BTreeMap<(u32, &str), [String; 2]> = BTreeMap::new();
+    let mut problems: Vec<String> = vec![];
+
+    for recorded in &answers {
+        if day.is_some_and(|day| day != recorded.day) {
+            continue;
+        }
+        let mut object = json!({
+            "day": recorded.day,
+            "part": recorded.part,
+            "input": recorded.input,
+            "expected": recorded.expected(),
+        });
+        let cell = match quick && recorded.slow {
+            true => {
+                object["status"] = json!("skipped");
+                printer.paint(Style::new().dimmed(), "skip  ")
+            }
+            false => match recorded.verify() {
+                Status::Pass => {
+                    object["status"] = json!("pass");
+                    printer.paint(Green.normal(), "pass  ")
+                }
+                Status::Fail(answer) => {
+                    problems.push(format!(
+                        "Day {:02} part {}: expected {}, got {}",
+                        recorded.day,
+                        recorded.part,
+                        recorded.expected(),
+                        answer
+                    ));
+                    object["status"] = json!("fail");
+                    object["answer"] = json!(answer);
+                    printer.paint(Red.bold(), "FAIL  ")
+                }
+                Status::Error(error) => {
+                    problems.push(format!(
+                        "Day {:02} part {}: {}",
+                        recorded.day, recorded.part, error
+                    ));
+                    object["status"] = json!("error");
+                    object["error"] = json!(error.to_string());
+                    printer.paint(Red.bold(), "ERROR ")
+                }
+            },
+        };
+        printer.json(object);
+        let row = rows
+            .entry((recorded.day, &recorded.input))
+            .or_insert_with(|| ["-     ".to_string(), "-     ".to_string()]);
+        let column = (recorded.part as usize)
+            .checked_sub(1)
+            .and_then(|index| row.get_mut(index));
+        if let Some(column) = column {
+            *column = cell;
+        }
+    }
+
+    printer.headline("Verify");
+    printer.text(&format!(
+        "{:<3} {:<25} {:<6} {:<6}",
+        "Day", "Input", "Part 1", "Part 2"
+    ));
+    for ((day, input), [part1, part2]) in &rows {
+        printer.text(&format!("{:02}  {:<25} {} {}", day, input, part1, part2));
+    }
+    for problem in &problems {
+        printer.text_error(&Error::invalid(problem));
+    }
+
+    match problems.is_empty() {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    }
+}
 
-    run_day(args.day, false);
+/// Writes the results either as (colourised) text or as JSON lines, never mixing both
+struct Printer {
+    format: Format,
 }
 
-fn run_day(day: Option<u32>, run_all: bool) {
-    match day {
-        None => {
-            for i in 1..22 {
-                if i != 16 {
-                    run_day(Some(i), true);
-                } else {
-                    println!("Day 16 only solo, too long");
+impl Printer {
+    fn headline(&self, title: &str) {
+        print_headline(title, self.format);
+    }
+
+    fn text(&self, line: &str) {
+        if self.format != Format::Json {
+            println!("{}", line);
+        }
+    }
+
+    fn json(&self, object: Value) {
+        if self.format == Format::Json {
+            println!("{}", object);
+        }
+    }
+
+    fn paint(&self, style: Style, text: &str) -> String {
+        match self.format {
+            Format::Text => style.paint(text).to_string(),
+            Format::Plain | Format::Json => text.to_string(),
+        }
+    }
+
+    fn time(&self, duration: Duration) -> String {
+        self.paint(
+            Style::new().dimmed(),
+            &format!("({})", format_duration(duration)),
+        )
+    }
+
+    fn text_error(&self, error: &Error) {
+        self.text(&self.paint(Red.bold(), &error.to_string()));
+    }
+
+    fn day_headline(&self, solution: &dyn Solution) {
+        self.headline(&format!(
+            "Day {:02} -{:>25}",
+            solution.day(),
+            solution.title()
+        ));
+    }
+
+    /// The input couldn't be read or parsed, so neither part could run
+    fn load_error(&self, solution: &dyn Solution, filename: &str, error: &Error) {
+        for part in 1..=2 {
+            self.json(json!({
+                "day": solution.day(),
+                "title": solution.title(),
+                "part": part,
+                "input": filename,
+                "status": "error",
+                "error": error.to_string(),
+            }));
+        }
+        self.text_error(error);
+    }
+
+    /// Prints the answers of a run of `watch` along with how they changed since the `previous`
+    /// run, returns them to be compared with the next run
+    fn watched_run(
+        &self,
+        run: &DayRun,
+        previous: Option<&[(String, String)]>,
+    ) -> Vec<(String, String)> {
+        self.day_headline(run.solution);
+        let (parse_time, outcomes) = match &run.result {
+            Ok(result) => result,
+            Err(error) => {
+                self.load_error(run.solution, &run.filename, error);
+                return vec![("Input".to_string(), error.to_string())];
+            }
+        };
+        self.text(&format!("Parse:  {}", self.time(*parse_time)));
+
+        let mut answers = vec![];
+        for (part, outcome) in (1..).zip(outcomes) {
+            let label = format!("Part {}", part);
+            let answer = match outcome {
+                Outcome::Solved(answer, _) => answer.to_string(),
+                Outcome::TimedOut(limit) => format!("timed out after {}", format_duration(*limit)),
+                Outcome::Failed(error) => error.to_string(),
+            };
+            let before = previous.and_then(|previous| {
+                previous
+                    .iter()
+                    .find(|(known, _)| *known == label)
+                    .map(|(_, answer)| answer.as_str())
+            });
+
+            let mut object = outcome.to_json(run.solution, part, &run.filename);
+            object["changed"] = json!(before.is_some_and(|before| before != answer));
+            if let Some(before) = before {
+                object["previous"] = json!(before);
+            }
+            self.json(object);
+            let change = match before {
+                None => String::new(),
+                Some(before) if before == answer => {
+                    self.paint(Style::new().dimmed(), ", unchanged")
                 }
+                Some(before) => self.paint(Yellow.normal(), &format!(", was {}", before)),
+            };
+            self.outcome(part, outcome, |duration| {
+                format!(" {}{}", self.time(duration), change)
+            });
+            if !matches!(outcome, Outcome::Solved(..)) && !change.is_empty() {
+                self.text(&format!("        {}", change.trim_start_matches(", ")));
+            }
+            answers.push((label, answer));
+        }
+        answers
+    }
+
+    fn test_run(&self, solution: &dyn Solution, run: &TestRun) {
+        self.json(json!({
+            "day": solution.day(),
+            "tests": { "success": run.success, "passed": run.passed, "failed": run.failed },
+        }));
+        match run.success {
+            true => {
+                self.text(&self.paint(Green.normal(), &format!("Tests: {} passed", run.passed)))
+            }
+            false => {
+                self.text(run.output.trim_end());
+                self.text(&self.paint(
+                    Red.bold(),
+                    &format!("Tests: {} passed, {} failed", run.passed, run.failed),
+                ));
+            }
+        }
+    }
+
+    fn day_run(&self, run: &DayRun) {
+        self.day_headline(run.solution);
+        let (parse_time, outcomes) = match &run.result {
+            Ok(result) => result,
+            Err(error) => return self.load_error(run.solution, &run.filename, error),
+        };
+        self.text(&format!("Parse:  {}", self.time(*parse_time)));
+
+        for (part, outcome) in (1..).zip(outcomes) {
+            let mut object = outcome.to_json(run.solution, part, &run.filename);
+            object["parse_ms"] = json!(milliseconds(*parse_time));
+            if let Outcome::Solved(_, duration) = outcome {
+                object["time_ms"] = json!(milliseconds(*duration));
+            }
+            self.json(object);
+            self.outcome(part, outcome, |duration| {
+                format!(" {}", self.time(duration))
+            });
+        }
+    }
+
+    fn invalid_day(&self, day: u32) {
+        let message = format!("Value [{}] for day is invalid", day);
+        self.json(json!({ "status": "error", "error": message }));
+        self.text(&message);
+    }
+
+    /// Prints the text line of a part, `timing` decorates solved answers
+    fn outcome(&self, part: u32, outcome: &Outcome, timing: impl Fn(Duration) -> String) {
+        match outcome {
+            Outcome::Solved(Answer::Unsolved, _) => {
+                self.text(&format!("Part {}: {}", part, Answer::Unsolved))
             }
+            Outcome::Solved(answer, duration) => self.text(&format!(
+                "Part {}: {}{}",
+                part,
+                self.paint(Yellow.bold(), &answer.to_string()),
+                timing(*duration)
+            )),
+            Outcome::TimedOut(limit) => self.text(&self.paint(
+                Red.normal(),
+                &format!("Part {}: timed out after {}", part, format_duration(*limit)),
+            )),
+            Outcome::Failed(error) => self.text_error(error),
+        }
+    }
+
+    fn stats(&self, label: &str, stats: Option<Stats>) {
+        if let Some(stats) = stats {
+            self.text(&format!(
+                "{:<7} min {:>10} | median {:>10} | max {:>10}",
+                format!("{}:", label),
+                format_duration(stats.min),
+                format_duration(stats.median),
+                format_duration(stats.max)
+            ));
         }
-        Some(1) => {
-            print_headline("Day 01 -         Calorie Counting");
-            day01::run();
-            day01::run2();
-        }
-        Some(2) => {
-            print_headline("Day 02 -      Rock Paper Scissors");
-            day02::run();
-            day02::run2();
-        }
-        Some(3) => {
-            print_headline("Day 03 -  Rucksack Reorganization");
-            day03::run();
-            day03::run2();
-        }
-        Some(4) => {
-            print_headline("Day 04 -             Camp Cleanup");
-            day04::run();
-            day04::run2();
-        }
-        Some(5) => {
-            print_headline("Day 05 -            Supply Stacks");
-            day05::run();
-            day05::run2();
-        }
-        Some(6) => {
-            print_headline("Day 06 -           Tuning Trouble");
-            day06::run();
-            day06::run2();
-        }
-        Some(7) => {
-            print_headline("Day 07 -  No Space Left On Device");
-            day07::run();
-            day07::run2();
-        }
-        Some(8) => {
-            print_headline("Day 08 -       Treetop Tree House");
-            day08::run();
-            day08::run2();
-        }
-        Some(9) => {
-            print_headline("Day 09 -              Rope Bridge");
-            day09::run();
-            day09::run2();
-        }
-        Some(10) => {
-            print_headline("Day 10 -         Cathode-Ray Tube");
-            day10::run();
-            day10::run2();
-        }
-        Some(11) => {
-            print_headline("Day 11 -     Monkey in the Middle");
-            day11::run();
-            day11::run2();
-        }
-        Some(12) => {
-            print_headline("Day 12 -  Hill Climbing Algorithm");
-            day12::run();
-            day12::run2();
-        }
-        Some(13) => {
-            print_headline("Day 13 -          Distress Signal");
-            day13::run();
-            day13::run2();
-        }
-        Some(14) => {
-            print_headline("Day 14 -       Regolith Reservoir");
-            day14::run();
-            day14::run2();
-        }
-        Some(15) => {
-            print_headline("Day 15 -    Beacon Exclusion Zone");
-            day15::run();
-            if !run_all {
-                day15::run2();
-            } else {
-                println!("Takes too long! Start day 15");
-            }
-        }
-        Some(16) => {
-            print_headline("Day 16 -    Proboscidea Volcanium");
-            day16::run();
-            day16::run2();
-        }
-        Some(17) => {
-            print_headline("Day 17 -         Pyroclastic Flow");
-            day17::run();
-            if !run_all {
-                day17::run2();
-            } else {
-                println!("Takes too long! Start day 17");
-            }
-        }
-        Some(18) => {
-            print_headline("Day 18 -        Boiling Boulders");
-            day18::run();
-            day18::run2();
-        }
-        Some(19) => {
-            print_headline("Day 19 -     Not Enough Minerals");
-            day19::run();
-            day19::run2();
-        }
-        Some(20) => {
-            print_headline("Day 20 -Grove Positioning System");
-            day20::run();
-            day20::run2();
-        }
-        Some(21) => {
-            print_headline("Day 21 -             Monkey Math");
-            day21::run();
-            day21::run2();
-        }
-        _ => println!("Value [{}] for day is invalid", day.unwrap()),
+    }
+
+    fn bench_summary(&self, summaries: &[BenchSummary], repeat: usize) {
+        let column = |duration: &Option<Duration>| match duration {
+            Some(duration) => format_duration(*duration),
+            None => "-".to_string(),
+        };
+
+        self.headline(&format!("Summary - median of {} runs", repeat));
+        self.text(&format!(
+            "{:<3} {:<25} {:>10} {:>10} {:>10}",
+            "Day", "Title", "Parse", "Part 1", "Part 2"
+        ));
+        for summary in summaries {
+            self.text(&format!(
+                "{:<3} {:<25} {:>10} {:>10} {:>10}",
+                format!("{:02}", summary.day),
+                summary.title,
+                column(&summary.parse),
+                column(&summary.parts[0]),
+                column(&summary.parts[1])
+            ));
+        }
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn stats_to_json(stats: Option<Stats>) -> Value {
+    match stats {
+        Some(stats) => json!({
+            "min": milliseconds(stats.min),
+            "median": milliseconds(stats.median),
+            "max": milliseconds(stats.max),
+        }),
+        None => Value::Null,
     }
 }
@@ -0,0 +1,396 @@
+//! Small building blocks for puzzle inputs. Everything hands out slices of the text it was given,
+//! so errors point at the right line and column of the whole input.
+
+use crate::error::{parse_number, Context};
+use crate::{Error, Result};
+use std::str::FromStr;
+
+/// Reads a piece of text from left to right, e.g. one line of the input
+pub struct Scanner<'a> {
+    text: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(text: &'a str) -> Scanner<'a> {
+        Scanner { text, rest: text }
+    }
+
+    /// The text which hasn't been read yet
+    pub fn rest(&self) -> &'a str {
+        self.rest
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    /// An error for `at`, which has to be a slice of the scanned text
+    pub fn error(&self, at: &'a str, message: &str) -> Error {
+        Error::parse(at, message).within(self.text, at)
+    }
+
+    fn advance(&mut self, length: usize) -> &'a str {
+        let (taken, rest) = self.rest.split_at(length);
+        self.rest = rest;
+        taken
+    }
+
+    /// Skips `literal`, which has to come next. The error points at the first character which
+    /// differs.
+    pub fn literal(&mut self, literal: &str) -> Result<()> {
+        if self.rest.starts_with(literal) {
+            self.advance(literal.len());
+            return Ok(());
+        }
+        let same = self
+            .rest
+            .char_indices()
+            .zip(literal.chars())
+            .find(|((_, found), expected)| found != expected)
+            .map_or(self.rest.len(), |((index, _), _)| index);
+        let found = &self.rest[same..];
+        let found = &found[..found.find('\n').unwrap_or(found.len())];
+        Err(self.error(found, &format!("expected `{}`", literal.trim())))
+    }
+
+    /// Skips whichever of `literals` comes next and returns it
+    pub fn one_of(&mut self, literals: &[&str]) -> Result<&'a str> {
+        match literals
+            .iter()
+            .find(|literal| self.rest.starts_with(*literal))
+        {
+            Some(literal) => Ok(self.advance(literal.len())),
+            None => {
+                let list: Vec<String> = literals
+                    .iter()
+                    .map(|literal| format!("`{}`", literal.trim()))
+                    .collect();
+                Err(self.error(
+                    self.next_token(),
+                    &format!("expected one of {}", list.join(", ")),
+                ))
+            }
+        }
+    }
+
+    /// Reads a whole number, negative ones included
+    pub fn number<T: FromStr>(&mut self) -> Result<T> {
+        let length = self
+            .rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '+'))
+            .unwrap_or(self.rest.len());
+        let token = match length {
+            0 => self.next_token(),
+            _ => &self.rest[..length],
+        };
+        let number = parse_number(token).within(self.text, token)?;
+        self.advance(length);
+        Ok(number)
+    }
+
+    /// Reads a name made of letters, digits and underscores
+    pub fn word(&mut self) -> Result<&'a str> {
+        let length = self
+            .rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest.len());
+        match length {
+            0 => Err(self.error(self.next_token(), "expected a name")),
+            _ => Ok(self.advance(length)),
+        }
+    }
+
+    /// Reads everything up to the next `delimiter`, which is left for the next read
+    pub fn until(&mut self, delimiter: &str) -> Result<&'a str> {
+        match self.rest.find(delimiter) {
+            Some(length) => Ok(self.advance(length)),
+            None => Err(self.error(
+                &self.rest[self.rest.len()..],
+                &format!("expected `{}`", delimiter.trim()),
+            )),
+        }
+    }
+
+    /// Reads everything which is left
+    pub fn take_rest(&mut self) -> &'a str {
+        self.advance(self.rest.len())
+    }
+
+    /// Makes sure nothing is left
+    pub fn end(&self) -> Result<()> {
+        match self.rest.is_empty() {
+            true => Ok(()),
+            false => Err(self.error(self.rest, "unexpected text")),
+        }
+    }
+
+    /// The text up to the next whitespace, to show in errors
+    fn next_token(&self) -> &'a str {
+        &self.rest[..self
+            .rest
+            .find(char::is_whitespace)
+            .unwrap_or(self.rest.len())]
+    }
+}
+
+/// Matches `text` against `template`, in which every `{}` stands for a field. Returns the text of
+/// the fields. A field reaches up to the text following it in the template, the last one up to the
+/// end when nothing follows.
+///
+/// ```
+/// # use adventofcode::parse::fields;
+/// let [count, from] = fields("move 3 from 2", "move {} from {}").unwrap();
+/// assert_eq!(("3", "2"), (count, from));
+/// ```
+pub fn fields<'a, const N: usize>(text: &'a str, template: &str) -> Result<[&'a str; N]> {
+    let mut parts = template.split("{}");
+    assert_eq!(
+        N + 1,
+        template.split("{}").count(),
+        "the template has to have {} fields",
+        N
+    );
+
+    let mut scanner = Scanner::new(text);
+    let mut fields = [""; N];
+    scanner.literal(parts.next().unwrap_or_default())?;
+    for (field, literal) in fields.iter_mut().zip(parts) {
+        *field = match literal.is_empty() {
+            true => scanner.take_rest(),
+            false => scanner.until(literal)?,
+        };
+        scanner.literal(literal)?;
+    }
+    scanner.end()?;
+    Ok(fields)
+}
+
+/// Like [`fields`], but all fields are numbers
+///
+/// ```
+/// # use adventofcode::parse::numbers;
+/// let [x, y] = numbers::<i64, 2>("x=-3, y=4", "x={}, y={}").unwrap();
+/// assert_eq!((-3, 4), (x, y));
+/// ```
+pub fn numbers<T: FromStr, const N: usize>(text: &str, template: &str) -> Result<[T; N]> {
+    let numbers = fields::<N>(text, template)?
+        .iter()
+        .map(|field| parse_number(field).within(text, field))
+        .collect::<Result<Vec<T>>>()?;
+    Ok(numbers
+        .try_into()
+        .unwrap_or_else(|_| unreachable!("there are N fields")))
+}
+
+/// All whole numbers in `text`, ignoring everything in between. A `-` right in front of a digit
+/// makes the number negative, unless it follows a letter or digit like in `4-6`.
+pub fn integers<T: FromStr>(text: &str) -> Result<Vec<T>> {
+    let mut numbers = vec![];
+    let mut position = 0;
+    while let Some(found) = text[position..].find(|c: char| c.is_ascii_digit()) {
+        let mut start = position + found;
+        let end = text[start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(text.len(), |length| start + length);
+        let before = &text[..start];
+        if before.ends_with('-') && !before[..start - 1].ends_with(|c: char| c.is_alphanumeric()) {
+            start -= 1;
+        }
+        let token = &text[start..end];
+        numbers.push(parse_number(token).within(text, token)?);
+        position = end;
+    }
+    Ok(numbers)
+}
+
+/// Parses every line of `input` with `parse`
+pub fn lines<'a, T>(input: &'a str, mut parse: impl FnMut(&'a str) -> Result<T>) -> Result<Vec<T>> {
+    input
+        .lines()
+        .map(|line| parse(line).within(input, line))
+        .collect()
+}
+
+/// Parses the items of a list like `79, 98` with `parse`
+pub fn separated<'a, T>(
+    text: &'a str,
+    separator: &str,
+    mut parse: impl FnMut(&'a str) -> Result<T>,
+) -> Result<Vec<T>> {
+    text.split(separator)
+        .map(|item| parse(item).within(text, item))
+        .collect()
+}
+
+/// The blocks of lines which are separated by blank lines
+pub fn split_blocks(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split("\n\n")
+        .map(|block| block.trim_matches('\n'))
+        .filter(|block| !block.is_empty())
+}
+
+/// Parses every block of lines with `parse`, see [`split_blocks`]
+pub fn blocks<'a, T>(
+    input: &'a str,
+    mut parse: impl FnMut(&'a str) -> Result<T>,
+) -> Result<Vec<T>> {
+    split_blocks(input)
+        .map(|block| parse(block).within(input, block))
+        .collect()
+}
+
+/// Splits a line like `  Starting items: 79, 98` into its key and value, both without surrounding
+/// whitespace
+pub fn key_value<'a>(line: &'a str, separator: &str) -> Result<(&'a str, &'a str)> {
+    let (key, value) = line.split_once(separator).ok_or_else(|| {
+        let text = line.trim();
+        Error::parse(text, &format!("expected `key{}value`", separator)).within(line, text)
+    })?;
+    Ok((key.trim(), value.trim()))
+}
+
+/// Cuts a line into columns of `width` characters each, the last one may be shorter. Meant for
+/// drawings like `[Z] [M] [P]`.
+pub fn columns(line: &str, width: usize) -> impl Iterator<Item = &str> {
+    let mut starts: Vec<usize> = line
+        .char_indices()
+        .step_by(width.max(1))
+        .map(|(index, _)| index)
+        .collect();
+    starts.push(line.len());
+    (0..starts.len() - 1).map(move |i| &line[starts[i]..starts[i + 1]])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn text_is_scanned() {
+        let mut scanner = Scanner::new("root: pppw + -42");
+        assert_eq!("root", scanner.word().unwrap());
+        scanner.literal(": ").unwrap();
+        assert_eq!("pppw", scanner.word().unwrap());
+        assert_eq!(" +", scanner.until(" -").unwrap());
+        assert_eq!(" ", scanner.one_of(&["+", " "]).unwrap());
+        assert_eq!(-42, scanner.number::<i32>().unwrap());
+        assert!(scanner.is_done());
+        scanner.end().unwrap();
+    }
+
+    #[test]
+    fn scanning_errors_point_at_the_problem() {
+        let error = |result: Result<()>| result.unwrap_err().to_string();
+
+        let mut scanner = Scanner::new("Valve BB has flow=1");
+        scanner.literal("Valve ").unwrap();
+        scanner.word().unwrap();
+        assert_eq!(
+            "1:18: expected `has flow rate=` `=1`",
+            error(scanner.literal(" has flow rate="))
+        );
+
+        let mut scanner = Scanner::new("x=2-, y=x");
+        scanner.literal("x=").unwrap();
+        assert_eq!(
+            "1:3: expected a number `2-`",
+            error(scanner.number::<i32>().map(|_| ()))
+        );
+        assert_eq!(
+            "1:3: expected one of `-`, `+` `2-,`",
+            error(scanner.one_of(&["-", "+"]).map(|_| ()))
+        );
+        assert_eq!("1:3: unexpected text `2-, y=x`", error(scanner.end()));
+        assert_eq!(
+            "1:10: expected `;` ``",
+            error(scanner.until(";").map(|_| ()))
+        );
+        assert_eq!("2-, y=x", scanner.take_rest());
+    }
+
+    #[test]
+    fn templates_are_filled() {
+        assert_eq!(
+            ["AA", "0", "tunnels lead to valves DD, II"],
+            fields::<3>(
+                "Valve AA has flow rate=0; tunnels lead to valves DD, II",
+                "Valve {} has flow rate={}; {}"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            [1, -2, 3],
+            numbers::<i32, 3>("move 1 from -2 to 3", "move {} from {} to {}").unwrap()
+        );
+        assert_eq!(
+            "1:13: expected a number `x`",
+            numbers::<i32, 3>("move 1 from x to 3", "move {} from {} to {}")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "1:2: expected `move` `voe 1 from 2 to 3`",
+            numbers::<i32, 3>("mvoe 1 from 2 to 3", "move {} from {} to {}")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "1:13: unexpected text ` .`",
+            fields::<1>("Blueprint 1: .", "Blueprint {}:")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn integers_are_found() {
+        assert_eq!(
+            vec![2, 18, -2, 15],
+            integers::<i64>("Sensor at x=2, y=18: closest beacon is at x=-2, y=15").unwrap()
+        );
+        assert_eq!(vec![4, 6, 8], integers::<u8>("4-6,8").unwrap());
+        assert_eq!(Vec::<i32>::new(), integers::<i32>("no numbers").unwrap());
+        assert_eq!(
+            "1:4: expected a number `300`",
+            integers::<u8>("1, 300").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn lists_and_blocks_are_split() {
+        let input = "Monkey 0:\n  Starting items: 79, 98\n\nMonkey 1:\n  Starting items: 5, x\n";
+        let items = blocks(input, |block| {
+            let (key, value) = key_value(block.lines().nth(1).unwrap_or_default(), ":")?;
+            assert_eq!("Starting items", key);
+            separated(value, ", ", parse_number::<u32>).within(block, value)
+        });
+        assert_eq!(
+            "5:22: expected a number `x`",
+            items.unwrap_err().to_string()
+        );
+
+        assert_eq!(
+            vec!["a\nb", "c"],
+            split_blocks("a\nb\n\n\nc\n").collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![vec![1, 2], vec![3]],
+            lines("1,2\n3", |line| separated(line, ",", parse_number::<i32>)).unwrap()
+        );
+        assert_eq!(
+            "1:3: expected `key: value` `Monkey`",
+            key_value("  Monkey", ": ").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn columns_are_cut() {
+        assert_eq!(
+            vec!["    ", "[D] ", "   "],
+            columns("    [D]    ", 4).collect::<Vec<_>>()
+        );
+        assert_eq!(0, columns("", 4).count());
+    }
+}
@@ -0,0 +1,388 @@
+use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Offsets of the four orthogonal neighbours: up, right, down, left
+const ORTHOGONAL: [(i8, i8); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Offsets of all eight neighbours, clockwise starting with up
+const ALL_AROUND: [(i8, i8); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// Offsets of the six neighbours sharing a face in 3D
+const FACES: [(i8, i8, i8); 6] = [
+    (0, 1, 0),
+    (0, -1, 0),
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+/// The numeric types points are made of
+pub trait Coordinate:
+    Copy + Ord + Default + Display + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    /// `|self - other|`, which also works for unsigned types
+    fn distance(self, other: Self) -> Self;
+    /// `self + delta`, `None` if that doesn't fit into the type, e.g. below zero for `usize`
+    fn offset(self, delta: i8) -> Option<Self>;
+}
+
+macro_rules! coordinate {
+    ($($t:ty),*) => {
+        $(impl Coordinate for $t {
+            const ZERO: $t = 0;
+
+            fn distance(self, other: $t) -> $t {
+                self.max(other) - self.min(other)
+            }
+
+            fn offset(self, delta: i8) -> Option<$t> {
+                match delta >= 0 {
+                    true => self.checked_add(delta as $t),
+                    false => self.checked_sub(delta.unsigned_abs() as $t),
+                }
+            }
+        })*
+    };
+}
+
+coordinate!(i32, i64, usize);
+
+/// The coordinates which can point into every direction
+pub trait Signed: Coordinate + Neg<Output = Self> {
+    const ONE: Self;
+    const MINUS_ONE: Self;
+    fn signum(self) -> Self;
+}
+
+macro_rules! signed {
+    ($($t:ty),*) => {
+        $(impl Signed for $t {
+            const ONE: $t = 1;
+            const MINUS_ONE: $t = -1;
+
+            fn signum(self) -> $t {
+                <$t>::signum(self)
+            }
+        })*
+    };
+}
+
+signed!(i32, i64);
+
+/// A point on a 2D map, `y` grows downwards like the lines of the input. Points are ordered by
+/// `x` first and `y` second.
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Default)]
+pub struct Point<T> {
+    pub(crate) x: T,
+    pub(crate) y: T,
+}
+
+impl<T> Point<T> {
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T: Coordinate> Point<T> {
+    pub const ORIGIN: Point<T> = Point {
+        x: T::ZERO,
+        y: T::ZERO,
+    };
+
+    pub fn x(&self) -> T {
+        self.x
+    }
+
+    pub fn y(&self) -> T {
+        self.y
+    }
+
+    pub fn manhattan(&self, other: &Point<T>) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y)
+    }
+
+    /// The number of king's moves between both points
+    pub fn chebyshev(&self, other: &Point<T>) -> T {
+        self.x.distance(other.x).max(self.y.distance(other.y))
+    }
+
+    /// The up to four orthogonal neighbours, clockwise starting with up
+    pub fn neighbours4(self) -> impl Iterator<Item = Point<T>> {
+        ORTHOGONAL
+            .iter()
+            .filter_map(move |(dx, dy)| self.offset(*dx, *dy))
+    }
+
+    /// The up to eight neighbours including the diagonal ones, clockwise starting with up
+    pub fn neighbours8(self) -> impl Iterator<Item = Point<T>> {
+        ALL_AROUND
+            .iter()
+            .filter_map(move |(dx, dy)| self.offset(*dx, *dy))
+    }
+
+    fn offset(self, dx: i8, dy: i8) -> Option<Point<T>> {
+        Some(Point::new(self.x.offset(dx)?, self.y.offset(dy)?))
+    }
+}
+
+impl<T: Signed> Point<T> {
+    pub const UP: Point<T> = Point {
+        x: T::ZERO,
+        y: T::MINUS_ONE,
+    };
+    pub const RIGHT: Point<T> = Point {
+        x: T::ONE,
+        y: T::ZERO,
+    };
+    pub const DOWN: Point<T> = Point {
+        x: T::ZERO,
+        y: T::ONE,
+    };
+    pub const LEFT: Point<T> = Point {
+        x: T::MINUS_ONE,
+        y: T::ZERO,
+    };
+    /// Clockwise starting with up
+    pub const DIRECTIONS: [Point<T>; 4] = [Self::UP, Self::RIGHT, Self::DOWN, Self::LEFT];
+
+    /// The sign of both coordinates, i.e. a step of at most one into the same direction
+    pub fn signum(&self) -> Point<T> {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl<T: Coordinate> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Coordinate> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Coordinate> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Point<T>) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coordinate> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Point<T>) {
+        *self = *self - other;
+    }
+}
+
+/// Scales both coordinates
+impl<T: Coordinate> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, factor: T) -> Point<T> {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl<T: Signed> Neg for Point<T> {
+    type Output = Point<T>;
+
+    fn neg(self) -> Point<T> {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+/// Widens the coordinates, e.g. to address a [`Grid`](crate::Grid)
+impl From<Point<i32>> for Point<i64> {
+    fn from(point: Point<i32>) -> Point<i64> {
+        Point::new(point.x.into(), point.y.into())
+    }
+}
+
+impl<T: Display> Display for Point<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.x, self.y)
+    }
+}
+
+impl<T: Display> Debug for Point<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({}|{})", self.x, self.y)
+    }
+}
+
+/// A point in 3D space
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Default)]
+pub struct Point3<T> {
+    pub(crate) x: T,
+    pub(crate) y: T,
+    pub(crate) z: T,
+}
+
+impl<T> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: Coordinate> Point3<T> {
+    pub const ORIGIN: Point3<T> = Point3 {
+        x: T::ZERO,
+        y: T::ZERO,
+        z: T::ZERO,
+    };
+
+    pub fn x(&self) -> T {
+        self.x
+    }
+
+    pub fn y(&self) -> T {
+        self.y
+    }
+
+    pub fn z(&self) -> T {
+        self.z
+    }
+
+    pub fn manhattan(&self, other: &Point3<T>) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y) + self.z.distance(other.z)
+    }
+
+    pub fn chebyshev(&self, other: &Point3<T>) -> T {
+        self.x
+            .distance(other.x)
+            .max(self.y.distance(other.y))
+            .max(self.z.distance(other.z))
+    }
+
+    /// The up to six neighbours sharing a face with this point
+    pub fn neighbours6(self) -> impl Iterator<Item = Point3<T>> {
+        FACES.iter().filter_map(move |(dx, dy, dz)| {
+            Some(Point3::new(
+                self.x.offset(*dx)?,
+                self.y.offset(*dy)?,
+                self.z.offset(*dz)?,
+            ))
+        })
+    }
+}
+
+impl<T: Coordinate> Add for Point3<T> {
+    type Output = Point3<T>;
+
+    fn add(self, other: Point3<T>) -> Point3<T> {
+        Point3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Coordinate> Sub for Point3<T> {
+    type Output = Point3<T>;
+
+    fn sub(self, other: Point3<T>) -> Point3<T> {
+        Point3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Coordinate> Mul<T> for Point3<T> {
+    type Output = Point3<T>;
+
+    fn mul(self, factor: T) -> Point3<T> {
+        Point3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl<T: Display> Display for Point3<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.x, self.y, self.z)
+    }
+}
+
+impl<T: Display> Debug for Point3<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({}|{}|{})", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn points_are_calculated_with() {
+        let a = Point::new(3, -2);
+        let b = Point::new(-1, 4);
+        assert_eq!(Point::new(2, 2), a + b);
+        assert_eq!(Point::new(4, -6), a - b);
+        assert_eq!(Point::new(6, -4), a * 2);
+        assert_eq!(Point::new(-3, 2), -a);
+        assert_eq!(Point::new(1, -1), a.signum());
+        assert_eq!(Point::new(3, -1), a + Point::DOWN);
+
+        let mut c = Point::<i64>::ORIGIN;
+        c += Point::RIGHT;
+        c -= Point::UP;
+        assert_eq!(Point::new(1, 1), c);
+        assert_eq!((1, 1), (c.x(), c.y()));
+    }
+
+    #[test]
+    fn distances_are_measured() {
+        let a = Point::new(3, -2);
+        let b = Point::new(-1, 4);
+        assert_eq!(10, a.manhattan(&b));
+        assert_eq!(6, a.chebyshev(&b));
+        assert_eq!(3, Point::new(5_usize, 1).manhattan(&Point::new(3, 2)));
+        assert_eq!(6, Point3::new(1, 2, 3).manhattan(&Point3::new(2, 0, 6)));
+        assert_eq!(3, Point3::new(1, 2, 3).chebyshev(&Point3::new(2, 0, 6)));
+    }
+
+    #[test]
+    fn neighbours_are_found() {
+        assert_eq!(
+            vec![
+                Point::new(1, 0),
+                Point::new(2, 1),
+                Point::new(1, 2),
+                Point::new(0, 1)
+            ],
+            Point::new(1, 1).neighbours4().collect::<Vec<_>>()
+        );
+        assert_eq!(8, Point::new(0_i32, 0).neighbours8().count());
+        // unsigned points stay on the map
+        assert_eq!(
+            vec![Point::new(1, 0), Point::new(0, 1)],
+            Point::new(0_usize, 0).neighbours4().collect::<Vec<_>>()
+        );
+        assert_eq!(3, Point::new(0_usize, 0).neighbours8().count());
+        assert_eq!(6, Point3::new(0, 0, 0).neighbours6().count());
+        assert_eq!(3, Point3::new(0_usize, 0, 0).neighbours6().count());
+    }
+
+    #[test]
+    fn points_are_ordered_and_printed() {
+        let mut points = vec![Point::new(2, 1), Point::new(1, 5), Point::new(1, 2)];
+        points.sort();
+        assert_eq!(
+            vec![Point::new(1, 2), Point::new(1, 5), Point::new(2, 1)],
+            points
+        );
+        assert_eq!("1/-2", Point::new(1, -2).to_string());
+        assert_eq!("(1|2)", format!("{:?}", Point::new(1_usize, 2)));
+        assert_eq!("1/2/3", Point3::new(1, 2, 3).to_string());
+    }
+}
@@ -0,0 +1,94 @@
+//! A small seeded random number generator for generated puzzle inputs. The same seed gives the
+//! same input on every machine and with every version of the dependencies.
+
+use std::ops::RangeInclusive;
+
+/// SplitMix64, which is fast and good enough for test data
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number between the bounds of `range`, both included
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (low, high) = range.into_inner();
+        assert!(low <= high, "empty range {}..={}", low, high);
+        let span = high.abs_diff(low).wrapping_add(1);
+        match span {
+            // the whole range of i64
+            0 => self.next_u64() as i64,
+            _ => low.wrapping_add((self.next_u64() % span) as i64),
+        }
+    }
+
+    /// An index into a slice of `len` items
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0, "no index into an empty slice");
+        (self.next_u64() % len as u64) as usize
+    }
+
+    /// True with the given probability between 0 and 1
+    pub fn chance(&mut self, probability: f64) -> bool {
+        // the upper 53 bits make a uniform f64 in 0..1
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn seeds_repeat() {
+        let numbers = |seed| {
+            let mut random = Random::new(seed);
+            (0..5).map(|_| random.next_u64()).collect::<Vec<_>>()
+        };
+        assert_eq!(numbers(7), numbers(7));
+        assert_ne!(numbers(7), numbers(8));
+        // the reference values of SplitMix64
+        assert_eq!(0xe220_a839_7b1d_cdaf, Random::new(0).next_u64());
+    }
+
+    #[test]
+    fn numbers_stay_in_range() {
+        let mut random = Random::new(1);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&random.range(-3..=3)));
+            assert!(random.index(5) < 5);
+        }
+        assert_eq!(4, random.range(4..=4));
+        random.range(i64::MIN..=i64::MAX);
+        assert!(!random.chance(0.0));
+        assert!(random.chance(1.0));
+
+        let mut items: Vec<u32> = (0..20).collect();
+        random.shuffle(&mut items);
+        assert_ne!((0..20).collect::<Vec<_>>(), items);
+        items.sort();
+        assert_eq!((0..20).collect::<Vec<_>>(), items);
+    }
+}
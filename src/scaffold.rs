@@ -0,0 +1,338 @@
+use crate::{read_file, Error, Result};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// The module of a new day, `DAY`, `NN` and `TITLE` get replaced
+const TEMPLATE: &str = r#"use crate::error::{parse_number, Context};
+use crate::{Answer, Result, Solution};
+
+fn parse_input(input: &str) -> Result<Vec<i64>> {
+    input
+        .lines()
+        .map(|line| parse_number(line).within(input, line))
+        .collect()
+}
+
+pub fn part1(input: &str) -> Result<Answer> {
+    let _values = parse_input(input)?;
+    Ok(Answer::Unsolved)
+}
+
+pub fn part2(input: &str) -> Result<Answer> {
+    let _values = parse_input(input)?;
+    Ok(Answer::Unsolved)
+}
+
+pub struct DayNN;
+
+impl Solution for DayNN {
+    fn day(&self) -> u32 {
+        DAY
+    }
+    fn title(&self) -> &'static str {
+        TITLE
+    }
+    fn parse(&self, input: &str) -> Result<()> {
+        parse_input(input).map(|_| ())
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        part1(input)
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read_file;
+
+    #[test]
+    fn example_is_solved() {
+        let input = read_file("input/dayNN-test.txt").unwrap();
+        assert_eq!(Answer::Unsolved, part1(&input).unwrap());
+        assert_eq!(Answer::Unsolved, part2(&input).unwrap());
+    }
+}
+"#;
+
+/// A day which is registered, but not started yet. `new-day` replaces it.
+const STUB: &str = r#"use crate::{Answer, Result, Solution};
+
+pub struct DayNN;
+
+impl Solution for DayNN {
+    fn day(&self) -> u32 {
+        DAY
+    }
+    fn title(&self) -> &'static str {
+        TITLE
+    }
+    fn part1(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+}
+"#;
+
+fn fill(template: &str, day: u32, title: &str) -> String {
+    template
+        .replace("NN", &format!("{:02}", day))
+        .replace("DAY", &day.to_string())
+        .replace("TITLE", title)
+}
+
+/// Fills the module template for `day`
+pub fn module_source(day: u32, title: &str) -> String {
+    fill(TEMPLATE, day, &format!("{:?}", title))
+}
+
+/// Whether `source` is the [stub](STUB) of `day`, whatever its title
+fn is_stub(source: &str, day: u32) -> bool {
+    let stub = fill(STUB, day, "\0");
+    let (before, after) = stub.split_once('\0').expect("the stub has a title");
+    source
+        .strip_prefix(before)
+        .and_then(|rest| rest.strip_suffix(after))
+        .is_some_and(|title| title.starts_with('"') && !title.contains('\n'))
+}
+
+/// Adds `pub mod dayNN;` and the entry in `SOLUTIONS` to the source of `lib.rs`, both sorted in
+/// between the existing days
+pub fn register(lib: &str, day: u32) -> Result<String> {
+    let module = format!("pub mod day{:02};", day);
+    let entry = format!("    &day{:02}::Day{:02},", day, day);
+    if lib.lines().any(|line| line == module || line == entry) {
+        return Err(Error::invalid(&format!(
+            "Day {} is already registered",
+            day
+        )));
+    }
+
+    let lines: Vec<&str> = lib.lines().collect();
+    let lines = insert_sorted(&lines, &module, |line| {
+        line.strip_prefix("pub mod day")?.strip_suffix(';')
+    })?;
+    let lines = insert_sorted(&lines, &entry, |line| {
+        line.strip_prefix("    &day")?
+            .split_once("::")
+            .map(|(day, _)| day)
+    })?;
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Inserts `new` into the block of lines for which `day_of` finds a day number, so it stays
+/// ordered
+fn insert_sorted<'a>(
+    lines: &[&'a str],
+    new: &'a str,
+    day_of: impl Fn(&str) -> Option<&str>,
+) -> Result<Vec<&'a str>> {
+    let key = |line: &str| day_of(line).and_then(|day| day.parse::<u32>().ok());
+    let new_day = key(new);
+    let block: Vec<usize> = (0..lines.len())
+        .filter(|&i| key(lines[i]).is_some())
+        .collect();
+    let (first, last) = match (block.first(), block.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => {
+            return Err(Error::invalid(&format!(
+                "Found no place for `{}`",
+                new.trim()
+            )))
+        }
+    };
+    let position = (first..=last)
+        .find(|&i| key(lines[i]) > new_day)
+        .unwrap_or(last + 1);
+
+    let mut result = lines.to_vec();
+    result.insert(position, new);
+    Ok(result)
+}
+
+/// Creates the module of a new day below `root`, registers it in `src/lib.rs` and adds empty
+/// inputs along with the sidecar file for the example's answers. A registered [stub](STUB) is
+/// replaced, anything else is kept: an existing module is an error and existing inputs stay as
+/// they are. Returns the files which were created or changed.
+pub fn new_day(root: &Path, day: u32, title: &str) -> Result<Vec<PathBuf>> {
+    if !(1..=25).contains(&day) {
+        return Err(Error::invalid(&format!("There is no day {}", day)));
+    }
+    let module_dir = root.join(format!("src/day{:02}", day));
+    let module_file = module_dir.join("mod.rs");
+    let stub = fs::read_to_string(&module_file)
+        .ok()
+        .filter(|source| is_stub(source, day));
+    if stub.is_none() && module_dir.exists() {
+        return Err(Error::invalid(&format!(
+            "{} already exists",
+            module_dir.display()
+        )));
+    }
+
+    // lib.rs is patched before anything is written, a stub is registered already
+    let lib_file = root.join("src/lib.rs");
+    let lib = if stub.is_some() {
+        None
+    } else {
+        Some(register(&read_file(&lib_file.display().to_string())?, day)?)
+    };
+
+    let written_module = fs::create_dir_all(&module_dir)
+        .and_then(|_| fs::write(&module_file, module_source(day, title)))
+        .map_err(|source| write_error(&module_file, source))
+        .and_then(|_| match lib {
+            Some(lib) => fs::write(&lib_file, lib).map_err(|source| write_error(&lib_file, source)),
+            None => Ok(()),
+        });
+    if let Err(error) = written_module {
+        // a new module without its registration would only be in the way of the next attempt
+        let _ = match &stub {
+            Some(stub) => fs::write(&module_file, stub),
+            None => fs::remove_dir_all(&module_dir),
+        };
+        return Err(error);
+    }
+    let mut written = vec![module_file];
+    if stub.is_none() {
+        written.push(lib_file);
+    }
+
+    // the sidecar holds the expected answers of the example for the `examples` test
+    let inputs = [
+        (format!("day{:02}.txt", day), String::new()),
+        (format!("day{:02}-test.txt", day), String::new()),
+        (
+            format!("day{:02}-test.toml", day),
+            format!("# answers of day{:02}-test.txt, e.g. `part1 = 42`\n", day),
+        ),
+    ];
+    for (name, content) in inputs {
+        let input_file = root.join("input").join(name);
+        if !input_file.exists() {
+            fs::write(&input_file, content).map_err(|source| write_error(&input_file, source))?;
+            written.push(input_file);
+        }
+    }
+    Ok(written)
+}
+
+fn write_error(file: &Path, source: io::Error) -> Error {
+    Error::invalid(&format!("Couldn't write the file: {}", source))
+        .in_file(&file.display().to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LIB: &str = "pub mod error;
+
+pub mod day01;
+pub mod day03;
+
+static SOLUTIONS: &[&dyn Solution] = &[
+    &day01::Day01,
+    &day03::Day03,
+];
+";
+
+    #[test]
+    fn template_is_filled() {
+        let source = module_source(7, "No \"Space\" Left");
+        assert!(source.contains("pub struct Day07;"));
+        assert!(source.contains("        7\n"));
+        assert!(source.contains("        \"No \\\"Space\\\" Left\"\n"));
+        assert!(source.contains("input/day07-test.txt"));
+    }
+
+    #[test]
+    fn days_are_registered_in_order() {
+        let lib = register(LIB, 2).unwrap();
+        assert!(lib.contains("pub mod day01;\npub mod day02;\npub mod day03;\n"));
+        assert!(lib.contains("    &day01::Day01,\n    &day02::Day02,\n    &day03::Day03,\n"));
+
+        let lib = register(LIB, 4).unwrap();
+        assert!(lib.contains("pub mod day03;\npub mod day04;\n\nstatic"));
+        assert!(lib.contains("    &day03::Day03,\n    &day04::Day04,\n];"));
+
+        assert_eq!(
+            "Day 3 is already registered",
+            register(LIB, 3).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn existing_work_is_kept() {
+        let root = std::env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("input")).unwrap();
+        fs::write(root.join("src/lib.rs"), LIB).unwrap();
+        fs::write(root.join("input/day02.txt"), "42\n").unwrap();
+
+        let written = new_day(&root, 2, "Rock Paper Scissors").unwrap();
+        assert_eq!(4, written.len());
+        assert_eq!(
+            "42\n",
+            fs::read_to_string(root.join("input/day02.txt")).unwrap()
+        );
+        assert_eq!(
+            "",
+            fs::read_to_string(root.join("input/day02-test.txt")).unwrap()
+        );
+        assert!(fs::read_to_string(root.join("src/day02/mod.rs"))
+            .unwrap()
+            .contains("Rock Paper Scissors"));
+
+        let error = new_day(&root, 2, "Rock Paper Scissors").unwrap_err();
+        assert!(error.to_string().ends_with("day02 already exists"));
+        assert!(new_day(&root, 26, "Too late").is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn stubs_are_replaced() {
+        assert!(is_stub(include_str!("day23/mod.rs"), 23));
+        assert!(is_stub(include_str!("day25/mod.rs"), 25));
+        assert!(!is_stub(include_str!("day23/mod.rs"), 24));
+        assert!(!is_stub(&module_source(23, "Unstable Diffusion"), 23));
+
+        let root = std::env::temp_dir().join(format!("aoc-stub-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/day03")).unwrap();
+        fs::create_dir_all(root.join("input")).unwrap();
+        fs::write(root.join("src/lib.rs"), LIB).unwrap();
+        let stub = fill(STUB, 3, "\"Rucksack Reorganization\"");
+        fs::write(root.join("src/day03/mod.rs"), &stub).unwrap();
+
+        let written = new_day(&root, 3, "Rucksack Reorganization").unwrap();
+        assert!(!written.contains(&root.join("src/lib.rs")));
+        assert_eq!(LIB, fs::read_to_string(root.join("src/lib.rs")).unwrap());
+        assert_eq!(
+            module_source(3, "Rucksack Reorganization"),
+            fs::read_to_string(root.join("src/day03/mod.rs")).unwrap()
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn failures_leave_nothing_behind() {
+        let root = std::env::temp_dir().join(format!("aoc-failure-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("input")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod error;\n").unwrap();
+
+        let error = new_day(&root, 2, "Rock Paper Scissors").unwrap_err();
+        assert_eq!("Found no place for `pub mod day02;`", error.to_string());
+        assert!(!root.join("src/day02").exists());
+        assert!(!root.join("input/day02.txt").exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
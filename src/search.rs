@@ -0,0 +1,296 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Everything a search reached: the cost of the cheapest way to each node and the node it was
+/// reached from, so the way itself can be reconstructed
+#[derive(Debug, Clone)]
+pub struct Distances<N> {
+    found: HashMap<N, (u64, Option<N>)>,
+}
+
+impl<N: Eq + Hash + Clone> Distances<N> {
+    fn new() -> Distances<N> {
+        Distances {
+            found: HashMap::new(),
+        }
+    }
+
+    /// The cost of the cheapest way from a start to `node`, `None` if it can't be reached
+    pub fn get(&self, node: &N) -> Option<u64> {
+        self.found.get(node).map(|(cost, _)| *cost)
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.found.contains_key(node)
+    }
+
+    /// The number of reached nodes, starts included
+    pub fn len(&self) -> usize {
+        self.found.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.found.is_empty()
+    }
+
+    /// The reached nodes with their costs, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&N, u64)> {
+        self.found.iter().map(|(node, (cost, _))| (node, *cost))
+    }
+
+    /// The nodes on the cheapest way to `node`, from its start up to `node` itself
+    pub fn path(&self, node: &N) -> Option<Vec<N>> {
+        let mut path = vec![node.clone()];
+        let mut current = &self.found.get(node)?.1;
+        while let Some(previous) = current {
+            path.push(previous.clone());
+            current = &self.found[previous].1;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Remembers `node` unless it's already known at the same or lower cost
+    fn improve(&mut self, node: &N, cost: u64, previous: Option<N>) -> bool {
+        match self.found.get(node) {
+            Some((known, _)) if *known <= cost => false,
+            _ => {
+                self.found.insert(node.clone(), (cost, previous));
+                true
+            }
+        }
+    }
+}
+
+/// Breadth-first search from `start`, every step costs one
+pub fn bfs<N, I>(start: N, neighbours: impl FnMut(&N) -> I) -> Distances<N>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    bfs_multi([start], neighbours)
+}
+
+/// Breadth-first search from all `starts` at once, so every node gets the distance to the
+/// nearest of them
+pub fn bfs_multi<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> Distances<N>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = Distances::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if distances.improve(&start, 0, None) {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let distance = distances.get(&node).unwrap_or_default() + 1;
+        for next in neighbours(&node) {
+            if !distances.contains(&next) {
+                distances.improve(&next, distance, Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// Dijkstra's search from `start`, `neighbours` yields the reachable nodes along with the cost
+/// of the step
+pub fn dijkstra<N, I>(start: N, neighbours: impl FnMut(&N) -> I) -> Distances<N>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    dijkstra_multi([start], neighbours)
+}
+
+/// Dijkstra's search from all `starts` at once
+pub fn dijkstra_multi<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+) -> Distances<N>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let (distances, _) = best_first(starts, |_| false, neighbours, |_| 0);
+    distances
+}
+
+/// A* search from `start` to the first node for which `is_goal` holds. `heuristic` estimates the
+/// remaining cost and must never overestimate it, otherwise the result may not be the cheapest.
+/// Returns the cost and the nodes on the way, from `start` to the goal.
+pub fn astar<N, I>(
+    start: N,
+    is_goal: impl Fn(&N) -> bool,
+    neighbours: impl FnMut(&N) -> I,
+    heuristic: impl Fn(&N) -> u64,
+) -> Option<(u64, Vec<N>)>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let (distances, goal) = best_first([start], is_goal, neighbours, heuristic);
+    let goal = goal?;
+    Some((distances.get(&goal)?, distances.path(&goal)?))
+}
+
+/// Expands the node with the lowest cost plus estimate first until a goal is found or nothing is
+/// left. With a heuristic of zero that's Dijkstra's search.
+fn best_first<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    is_goal: impl Fn(&N) -> bool,
+    mut neighbours: impl FnMut(&N) -> I,
+    heuristic: impl Fn(&N) -> u64,
+) -> (Distances<N>, Option<N>)
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut distances = Distances::new();
+    // the heap only holds indices into `queued`, so nodes don't need to be ordered
+    let mut queued = vec![];
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        if distances.improve(&start, 0, None) {
+            heap.push((Reverse(heuristic(&start)), Reverse(0), queued.len()));
+            queued.push(start);
+        }
+    }
+
+    while let Some((_, Reverse(cost), index)) = heap.pop() {
+        let node = queued[index].clone();
+        if distances.get(&node) != Some(cost) {
+            // a cheaper way to the node was found after this entry was queued
+            continue;
+        }
+        if is_goal(&node) {
+            return (distances, Some(node));
+        }
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if distances.improve(&next, next_cost, Some(node.clone())) {
+                heap.push((
+                    Reverse(next_cost + heuristic(&next)),
+                    Reverse(next_cost),
+                    queued.len(),
+                ));
+                queued.push(next);
+            }
+        }
+    }
+    (distances, None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Grid, Point};
+
+    const MAZE: &str = "\
+..#....
+.##.##.
+...#...
+.#...#.";
+
+    fn open_neighbours(maze: &Grid<char>, point: Point<i64>) -> Vec<Point<i64>> {
+        maze.neighbours4(point)
+            .filter(|neighbour| maze[*neighbour] == '.')
+            .collect()
+    }
+
+    fn maze() -> Grid<char> {
+        Grid::parse(MAZE, "expected a wall", Some).unwrap()
+    }
+
+    #[test]
+    fn mazes_are_searched_breadth_first() {
+        let maze = maze();
+        let distances = bfs(Point::new(0, 0), |point| open_neighbours(&maze, *point));
+
+        assert_eq!(Some(12), distances.get(&Point::new(6, 0)));
+        assert_eq!(None, distances.get(&Point::new(2, 0)));
+        assert_eq!(Some(0), distances.get(&Point::new(0, 0)));
+        assert_eq!(20, distances.len());
+
+        let path = distances.path(&Point::new(2, 3)).unwrap();
+        assert_eq!(6, path.len());
+        assert_eq!(Point::new(0, 0), path[0]);
+        assert_eq!(Point::new(2, 3), path[5]);
+        assert!(path.windows(2).all(|step| step[0].manhattan(&step[1]) == 1));
+    }
+
+    #[test]
+    fn the_nearest_start_wins() {
+        let maze = maze();
+        let distances = bfs_multi([Point::new(0, 0), Point::new(6, 3)], |point| {
+            open_neighbours(&maze, *point)
+        });
+
+        assert_eq!(Some(2), distances.get(&Point::new(0, 2)));
+        assert_eq!(Some(3), distances.get(&Point::new(6, 0)));
+        assert_eq!(
+            Some(vec![Point::new(6, 3), Point::new(6, 2), Point::new(5, 2)]),
+            distances.path(&Point::new(5, 2))
+        );
+    }
+
+    #[test]
+    fn weighted_graphs_are_searched() {
+        // the direct edge from a to c is more expensive than the detour over b
+        let edges = HashMap::from([
+            ('a', vec![('b', 1), ('c', 5)]),
+            ('b', vec![('c', 2), ('d', 7)]),
+            ('c', vec![('d', 1)]),
+            ('d', vec![]),
+            ('e', vec![('a', 1)]),
+        ]);
+        let neighbours = |node: &char| edges[node].clone();
+
+        let distances = dijkstra('a', neighbours);
+        assert_eq!(Some(3), distances.get(&'c'));
+        assert_eq!(Some(4), distances.get(&'d'));
+        assert_eq!(None, distances.get(&'e'));
+        assert_eq!(Some(vec!['a', 'b', 'c', 'd']), distances.path(&'d'));
+
+        let distances = dijkstra_multi(['a', 'c'], neighbours);
+        assert_eq!(Some(1), distances.get(&'d'));
+        assert_eq!(Some(vec!['c', 'd']), distances.path(&'d'));
+
+        assert_eq!(
+            Some((4, vec!['a', 'b', 'c', 'd'])),
+            astar('a', |node| *node == 'd', neighbours, |_| 0)
+        );
+        assert_eq!(None, astar('a', |node| *node == 'e', neighbours, |_| 0));
+    }
+
+    #[test]
+    fn astar_finds_the_cheapest_way() {
+        let maze = maze();
+        let goal = Point::new(6, 0);
+        let neighbours = |point: &Point<i64>| {
+            open_neighbours(&maze, *point)
+                .into_iter()
+                .map(|neighbour| (neighbour, 1))
+        };
+
+        let (cost, path) = astar(
+            Point::new(0, 0),
+            |point| *point == goal,
+            neighbours,
+            |point| point.manhattan(&goal) as u64,
+        )
+        .unwrap();
+        assert_eq!(12, cost);
+        assert_eq!(13, path.len());
+        assert_eq!(Some(&goal), path.last());
+    }
+}
@@ -0,0 +1,144 @@
+//! Talks to the puzzle website: downloads the inputs into the input directory. Every request
+//! carries the session token and waits for the [`RateLimit`], files already there are never
+//! downloaded again.
+
+use crate::http::{Http, RateLimit, Request, Response};
+use crate::{input_dir_or_default, read_file, Error, Result};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const BASE_URL_VAR: &str = "AOC_BASE_URL";
+pub const SESSION_VAR: &str = "AOC_SESSION";
+pub const SESSION_FILE: &str = ".session";
+pub const YEAR: u32 = 2022;
+
+const USER_AGENT: &str = concat!("adventofcode-2022/", env!("CARGO_PKG_VERSION"));
+
+/// `base_url`, else `$AOC_BASE_URL`, else the real website
+pub fn base_url_or_default(base_url: Option<&str>) -> String {
+    match base_url {
+        Some(base_url) => base_url.to_string(),
+        None => env::var(BASE_URL_VAR).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+    }
+}
+
+/// The session token from `$AOC_SESSION` or else from `file`, with or without `session=`
+pub fn read_session(file: &str) -> Result<String> {
+    let token = match env::var(SESSION_VAR) {
+        Ok(token) => token,
+        Err(_) => read_file(file).map_err(|_| {
+            Error::invalid(&format!(
+                "No session token, set {} or put it into {}",
+                SESSION_VAR, file
+            ))
+        })?,
+    };
+    let token = token.trim();
+    let token = token.strip_prefix("session=").unwrap_or(token);
+    match token.is_empty() {
+        true => Err(Error::invalid(&format!(
+            "The session token in {} is empty",
+            file
+        ))),
+        false => Ok(token.to_string()),
+    }
+}
+
+/// The puzzle website, or a stand-in for it
+pub struct Site {
+    base_url: String,
+    session: String,
+    http: Box<dyn Http>,
+    limit: RateLimit,
+}
+
+impl Site {
+    pub fn new(base_url: &str, session: &str, http: Box<dyn Http>, limit: RateLimit) -> Site {
+        Site {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            http,
+            limit,
+        }
+    }
+
+    pub fn day_url(&self, day: u32) -> String {
+        format!("{}/{}/day/{}", self.base_url, YEAR, day)
+    }
+
+    /// Sends `request` as the logged in user, once the rate limit allows
+    pub fn send(&mut self, request: Request) -> Result<Response> {
+        self.limit.wait()?;
+        let request = request
+            .header("Cookie", &format!("session={}", self.session))
+            .header("User-Agent", USER_AGENT);
+        self.http.send(&request)
+    }
+
+    /// Sends `request` about `day`, any status but 200 is an error
+    pub fn send_for_day(&mut self, day: u32, request: Request) -> Result<Response> {
+        let url = request.url.clone();
+        let response = self.send(request)?;
+        let message = match response.status {
+            200 => return Ok(response),
+            404 => format!("day {} isn't unlocked yet", day),
+            400 | 401 | 403 => "the session token was rejected".to_string(),
+            status => format!("unexpected status {}", status),
+        };
+        Err(Error::Http { url, message })
+    }
+}
+
+/// Where the input of a day came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    /// the file was there already, nothing was sent
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+fn input_path(day: u32, input_dir: Option<&str>) -> PathBuf {
+    Path::new(&input_dir_or_default(input_dir)).join(format!("day{:02}.txt", day))
+}
+
+/// The input of `day` if it was downloaded already. An empty file, like the one `new-day` leaves,
+/// counts as missing.
+pub fn cached_input(day: u32, input_dir: Option<&str>) -> Option<PathBuf> {
+    let path = input_path(day, input_dir);
+    fs::metadata(&path)
+        .is_ok_and(|metadata| metadata.len() > 0)
+        .then_some(path)
+}
+
+/// Downloads the input of `day` to `dayNN.txt` in the input directory, unless it's there already
+pub fn fetch_input(site: &mut Site, day: u32, input_dir: Option<&str>) -> Result<Fetched> {
+    if let Some(path) = cached_input(day, input_dir) {
+        return Ok(Fetched::Cached(path));
+    }
+    let path = input_path(day, input_dir);
+
+    let url = format!("{}/input", site.day_url(day));
+    let response = site.send_for_day(day, Request::get(&url))?;
+    if response.body.is_empty() {
+        return Err(Error::Http {
+            url,
+            message: "the input is empty".to_string(),
+        });
+    }
+
+    // written next to it first, so an interrupted download leaves no half file behind
+    let partial = path.with_extension("txt.part");
+    let io_error = |source| Error::Io {
+        file: path.display().to_string(),
+        source,
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    fs::write(&partial, &response.body)
+        .and_then(|_| fs::rename(&partial, &path))
+        .map_err(io_error)?;
+    Ok(Fetched::Downloaded(path))
+}
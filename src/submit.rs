@@ -0,0 +1,395 @@
+//! Sends answers to the puzzle website and keeps every attempt in a history file. The history is
+//! what keeps us from sending an answer which is known to be wrong: one that was rejected before,
+//! or one outside of the range the "too high" and "too low" replies leave.
+
+use crate::http::Request;
+use crate::site::Site;
+use crate::verify::from_toml;
+use crate::{read_file, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Default location of the history, it's not checked in
+pub const HISTORY_FILE: &str = "submissions.toml";
+
+/// What the website made of an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// wrong, without a hint in which direction
+    Wrong,
+    /// the answer wasn't looked at, the last one was too recent
+    Wait,
+    /// the part was solved already, or the part before wasn't
+    WrongLevel,
+    /// a reply which isn't understood
+    Unknown,
+}
+
+impl Verdict {
+    pub fn is_wrong(self) -> bool {
+        matches!(self, Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong)
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Verdict::Correct => "is correct",
+            Verdict::TooHigh => "is too high",
+            Verdict::TooLow => "is too low",
+            Verdict::Wrong => "is wrong",
+            Verdict::Wait => "wasn't checked, the last answer was too recent",
+            Verdict::WrongLevel => "wasn't checked, the part isn't open",
+            Verdict::Unknown => "got a reply which isn't understood",
+        }
+    }
+}
+
+/// The reply to an answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub verdict: Verdict,
+    /// how long until the next answer may be sent
+    pub wait: Option<Duration>,
+    /// what the website said, without the markup
+    pub message: String,
+}
+
+/// Reads the verdict from the text of the reply page
+pub fn parse_reply(page: &str) -> Reply {
+    let message = reply_text(page);
+    let verdict = if message.contains("That's the right answer") {
+        Verdict::Correct
+    } else if message.contains("too high") {
+        Verdict::TooHigh
+    } else if message.contains("too low") {
+        Verdict::TooLow
+    } else if message.contains("That's not the right answer") {
+        Verdict::Wrong
+    } else if message.contains("You gave an answer too recently") {
+        Verdict::Wait
+    } else if message.contains("You don't seem to be solving the right level") {
+        Verdict::WrongLevel
+    } else {
+        Verdict::Unknown
+    };
+    Reply {
+        verdict,
+        wait: parse_wait(&message),
+        message,
+    }
+}
+
+/// The text of the `<article>` with the reply, or of the whole page if there's none
+fn reply_text(page: &str) -> String {
+    let article = page
+        .split_once("<article>")
+        .and_then(|(_, rest)| Some(rest.split_once("</article>")?.0))
+        .unwrap_or(page);
+    let mut text = String::new();
+    let mut in_tag = false;
+    for char in article.chars() {
+        match char {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(char),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// How long the reply asks to wait, from `You have 1m 34s left to wait` after answering too
+/// early and `Please wait one minute` or `wait 5 minutes` after a wrong answer
+fn parse_wait(message: &str) -> Option<Duration> {
+    if let Some((before, _)) = message.split_once(" left to wait") {
+        let seconds: u64 = before
+            .split_whitespace()
+            .rev()
+            .map_while(|word| {
+                let (number, unit) = word.split_at(word.len().checked_sub(1)?);
+                let factor = match unit {
+                    "h" => 3600,
+                    "m" => 60,
+                    "s" => 1,
+                    _ => return None,
+                };
+                Some(number.parse::<u64>().ok()? * factor)
+            })
+            .sum();
+        return Some(Duration::from_secs(seconds));
+    }
+    if message.contains("wait one minute") {
+        return Some(Duration::from_secs(60));
+    }
+    let words: Vec<&str> = message.split_whitespace().collect();
+    words.windows(3).find_map(|window| match window {
+        ["wait", minutes, unit] if unit.starts_with("minute") => {
+            Some(Duration::from_secs(minutes.parse::<u64>().ok()? * 60))
+        }
+        _ => None,
+    })
+}
+
+/// Sends `answer` for a part and reads the verdict
+pub fn submit_answer(site: &mut Site, day: u32, part: u32, answer: &str) -> Result<Reply> {
+    let url = format!("{}/answer", site.day_url(day));
+    let level = part.to_string();
+    let request = Request::post_form(&url, &[("level", &level), ("answer", answer)]);
+    let response = site.send_for_day(day, request)?;
+    Ok(parse_reply(&response.body))
+}
+
+/// One `[[attempt]]` of the history file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Attempt {
+    pub day: u32,
+    pub part: u32,
+    pub answer: String,
+    pub verdict: Verdict,
+    /// when the answer was sent, in seconds since the Unix epoch
+    pub time: u64,
+    /// how many seconds the website asked to wait before the next answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait: Option<u64>,
+}
+
+impl Attempt {
+    pub fn new(day: u32, part: u32, answer: &str, reply: &Reply, time: u64) -> Attempt {
+        Attempt {
+            day,
+            part,
+            answer: answer.to_string(),
+            verdict: reply.verdict,
+            time,
+            wait: reply.wait.map(|wait| wait.as_secs()),
+        }
+    }
+}
+
+/// Every answer sent so far, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default, rename = "attempt")]
+    pub attempts: Vec<Attempt>,
+}
+
+impl History {
+    /// Reads the history, a missing file is an empty history
+    pub fn read(filename: &str) -> Result<History> {
+        if !Path::new(filename).exists() {
+            return Ok(History::default());
+        }
+        from_toml(&read_file(filename)?).map_err(|error| error.in_file(filename))
+    }
+
+    pub fn write(&self, filename: &str) -> Result<()> {
+        let content = toml::to_string(self).map_err(|error| Error::invalid(&error.to_string()))?;
+        let io_error = |source| Error::Io {
+            file: filename.to_string(),
+            source,
+        };
+        if let Some(dir) = Path::new(filename).parent() {
+            fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        fs::write(filename, content).map_err(io_error)
+    }
+
+    pub fn record(&mut self, attempt: Attempt) {
+        self.attempts.push(attempt);
+    }
+
+    fn of(&self, day: u32, part: u32) -> impl Iterator<Item = &Attempt> {
+        self.attempts
+            .iter()
+            .filter(move |attempt| attempt.day == day && attempt.part == part)
+    }
+
+    /// The highest answer which was too low and the lowest which was too high, the right answer
+    /// lies in between
+    pub fn bounds(&self, day: u32, part: u32) -> (Option<i64>, Option<i64>) {
+        let numbers = |verdict: Verdict| {
+            self.of(day, part)
+                .filter(move |attempt| attempt.verdict == verdict)
+                .filter_map(|attempt| attempt.answer.parse::<i64>().ok())
+        };
+        (
+            numbers(Verdict::TooLow).max(),
+            numbers(Verdict::TooHigh).min(),
+        )
+    }
+
+    /// Fails if sending `answer` at `now` (seconds since the Unix epoch) is known to be pointless
+    pub fn check(&self, day: u32, part: u32, answer: &str, now: u64) -> Result<()> {
+        let refuse = |message: String| Err(Error::invalid(&message));
+        if let Some(correct) = self
+            .of(day, part)
+            .find(|attempt| attempt.verdict == Verdict::Correct)
+        {
+            return refuse(format!(
+                "Day {} part {} is solved already, the answer was {}",
+                day, part, correct.answer
+            ));
+        }
+        if let Some(last) = self.of(day, part).last() {
+            let until = last.time + last.wait.unwrap_or(0);
+            if until > now {
+                return refuse(format!(
+                    "Wait another {}s before answering day {} part {} again",
+                    until - now,
+                    day,
+                    part
+                ));
+            }
+        }
+        if let Some(wrong) = self
+            .of(day, part)
+            .find(|attempt| attempt.answer == answer && attempt.verdict.is_wrong())
+        {
+            return refuse(format!(
+                "{} was sent before and {}",
+                answer,
+                wrong.verdict.describe()
+            ));
+        }
+        if let Ok(number) = answer.parse::<i64>() {
+            match self.bounds(day, part) {
+                (Some(low), _) if number <= low => {
+                    return refuse(format!(
+                        "{} is too low, {} was too low already",
+                        answer, low
+                    ))
+                }
+                (_, Some(high)) if number >= high => {
+                    return refuse(format!(
+                        "{} is too high, {} was too high already",
+                        answer, high
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn page(article: &str) -> String {
+        format!(
+            "<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>",
+            article
+        )
+    }
+
+    #[test]
+    fn replies_are_understood() {
+        let reply = parse_reply(&page(
+            "That's the right answer!  You are <span class=\"day-success\">one gold star</span> \
+             closer to collecting enough star fruit. <a href=\"/2022/day/5#part2\">[Continue]</a>",
+        ));
+        assert_eq!(Verdict::Correct, reply.verdict);
+        assert_eq!(None, reply.wait);
+        assert!(reply
+            .message
+            .starts_with("That's the right answer! You are one gold star"));
+
+        let reply = parse_reply(&page(
+            "That's not the right answer; your answer is too high. Please wait one minute before \
+             trying again. <a href=\"/2022/day/5\">[Return to Day 5]</a>",
+        ));
+        assert_eq!(Verdict::TooHigh, reply.verdict);
+        assert_eq!(Some(Duration::from_secs(60)), reply.wait);
+
+        let reply = parse_reply(&page(
+            "That's not the right answer; your answer is too low. Please wait 5 minutes before \
+             trying again.",
+        ));
+        assert_eq!(Verdict::TooLow, reply.verdict);
+        assert_eq!(Some(Duration::from_secs(300)), reply.wait);
+
+        let reply = parse_reply(&page(
+            "That's not the right answer. Please wait one minute.",
+        ));
+        assert_eq!(Verdict::Wrong, reply.verdict);
+
+        let reply = parse_reply(&page(
+            "You gave an answer too recently; you have to wait after submitting an answer before \
+             trying again.  You have 1m 34s left to wait.",
+        ));
+        assert_eq!(Verdict::Wait, reply.verdict);
+        assert_eq!(Some(Duration::from_secs(94)), reply.wait);
+
+        let reply = parse_reply(&page(
+            "You don't seem to be solving the right level.  Did you already complete it?",
+        ));
+        assert_eq!(Verdict::WrongLevel, reply.verdict);
+        assert_eq!(Verdict::Unknown, parse_reply("<h1>Oops</h1>").verdict);
+    }
+
+    fn attempt(part: u32, answer: &str, verdict: Verdict, time: u64) -> Attempt {
+        Attempt {
+            day: 5,
+            part,
+            answer: answer.to_string(),
+            verdict,
+            time,
+            wait: Some(60),
+        }
+    }
+
+    #[test]
+    fn known_wrong_answers_are_refused() {
+        let mut history = History::default();
+        assert!(history.check(5, 1, "100", 0).is_ok());
+        history.record(attempt(1, "100", Verdict::TooHigh, 0));
+        history.record(attempt(1, "50", Verdict::TooLow, 100));
+        history.record(attempt(1, "ABC", Verdict::Wrong, 200));
+
+        let refusal = |answer: &str| history.check(5, 1, answer, 1000).unwrap_err().to_string();
+        assert_eq!("ABC was sent before and is wrong", refusal("ABC"));
+        assert_eq!("100 was sent before and is too high", refusal("100"));
+        assert_eq!("120 is too high, 100 was too high already", refusal("120"));
+        assert_eq!("7 is too low, 50 was too low already", refusal("7"));
+        assert_eq!((Some(50), Some(100)), history.bounds(5, 1));
+        assert!(history.check(5, 1, "75", 1000).is_ok());
+        assert!(history.check(5, 1, "XYZ", 1000).is_ok());
+        // the other part has its own bounds
+        assert!(history.check(5, 2, "120", 1000).is_ok());
+
+        assert_eq!(
+            "Wait another 10s before answering day 5 part 1 again",
+            history.check(5, 1, "75", 250).unwrap_err().to_string()
+        );
+
+        history.record(attempt(1, "75", Verdict::Correct, 1000));
+        assert_eq!(
+            "Day 5 part 1 is solved already, the answer was 75",
+            history.check(5, 1, "75", 2000).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn history_survives_writing() {
+        let mut history = History::default();
+        history.record(attempt(1, "100", Verdict::TooHigh, 1670000000));
+        history.record(Attempt {
+            wait: None,
+            ..attempt(2, "CMZ", Verdict::Correct, 1670000100)
+        });
+        let content = toml::to_string(&history).unwrap();
+        assert!(content.contains("verdict = \"too-high\""), "{}", content);
+        assert_eq!(history, from_toml::<History>(&content).unwrap());
+        assert_eq!(
+            History::default(),
+            History::read("input/no-such-history.toml").unwrap()
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Draws puzzle geometry as SVG. Shapes are given in puzzle coordinates, the view box is fitted to
+//! them, so it doesn't matter whether they span ten or ten million units. Lines keep their width
+//! and markers their size relative to the whole picture.
+
+use crate::{Error, Point, Result};
+use std::fmt::{self, Display, Formatter, Write};
+use std::fs;
+use std::path::Path;
+
+/// Room around the shapes, relative to the size of the picture
+const MARGIN: f64 = 0.02;
+
+/// The radius of points and markers, relative to the size of the picture
+const RADIUS: f64 = 0.004;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Point {
+        at: Point<i64>,
+        colour: String,
+    },
+    Polyline {
+        points: Vec<Point<i64>>,
+        colour: String,
+    },
+    Polygon {
+        points: Vec<Point<i64>>,
+        stroke: String,
+        fill: String,
+    },
+    Marker {
+        at: Point<i64>,
+        label: String,
+        colour: String,
+    },
+}
+
+/// A picture made of points, lines, polygons and labelled markers. Colours are anything SVG
+/// understands, like `red` or `#336699`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Svg {
+    shapes: Vec<Shape>,
+    title: String,
+}
+
+impl Svg {
+    pub fn new(title: &str) -> Svg {
+        Svg {
+            shapes: vec![],
+            title: title.to_string(),
+        }
+    }
+
+    pub fn point(&mut self, at: impl Into<Point<i64>>, colour: &str) -> &mut Svg {
+        self.shapes.push(Shape::Point {
+            at: at.into(),
+            colour: colour.to_string(),
+        });
+        self
+    }
+
+    /// Lines from point to point, e.g. a path which was walked
+    pub fn polyline<P: Into<Point<i64>>>(
+        &mut self,
+        points: impl IntoIterator<Item = P>,
+        colour: &str,
+    ) -> &mut Svg {
+        self.shapes.push(Shape::Polyline {
+            points: points.into_iter().map(Into::into).collect(),
+            colour: colour.to_string(),
+        });
+        self
+    }
+
+    /// A closed shape, its inside is filled with a translucent `fill`
+    pub fn polygon<P: Into<Point<i64>>>(
+        &mut self,
+        points: impl IntoIterator<Item = P>,
+        stroke: &str,
+        fill: &str,
+    ) -> &mut Svg {
+        self.shapes.push(Shape::Polygon {
+            points: points.into_iter().map(Into::into).collect(),
+            stroke: stroke.to_string(),
+            fill: fill.to_string(),
+        });
+        self
+    }
+
+    /// A point with a label next to it
+    pub fn marker(&mut self, at: impl Into<Point<i64>>, label: &str, colour: &str) -> &mut Svg {
+        self.shapes.push(Shape::Marker {
+            at: at.into(),
+            label: label.to_string(),
+            colour: colour.to_string(),
+        });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    fn points(&self) -> impl Iterator<Item = Point<i64>> + '_ {
+        self.shapes.iter().flat_map(|shape| match shape {
+            Shape::Point { at, .. } | Shape::Marker { at, .. } => vec![*at],
+            Shape::Polyline { points, .. } | Shape::Polygon { points, .. } => points.clone(),
+        })
+    }
+
+    /// The smallest and largest coordinates of all shapes
+    fn bounds(&self) -> Option<(Point<i64>, Point<i64>)> {
+        let mut points = self.points();
+        let first = points.next()?;
+        Some(points.fold((first, first), |(min, max), point| {
+            (
+                Point::new(min.x.min(point.x), min.y.min(point.y)),
+                Point::new(max.x.max(point.x), max.y.max(point.y)),
+            )
+        }))
+    }
+
+    /// `min-x min-y width height` of the area with all shapes and a margin around them
+    pub fn view_box(&self) -> (f64, f64, f64, f64) {
+        let (min, max) = self.bounds().unwrap_or((Point::ORIGIN, Point::ORIGIN));
+        let size = ((max.x - min.x).max(max.y - min.y) as f64).max(1.0);
+        let margin = size * MARGIN;
+        (
+            min.x as f64 - margin,
+            min.y as f64 - margin,
+            (max.x - min.x) as f64 + 2.0 * margin,
+            (max.y - min.y) as f64 + 2.0 * margin,
+        )
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string()).map_err(|source| Error::Io {
+            file: path.display().to_string(),
+            source,
+        })
+    }
+}
+
+fn coordinates(points: &[Point<i64>]) -> String {
+    let pairs: Vec<String> = points
+        .iter()
+        .map(|point| format!("{},{}", point.x, point.y))
+        .collect();
+    pairs.join(" ")
+}
+
+/// Makes text safe to put into an element or attribute
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The whole SVG document
+impl Display for Svg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (x, y, width, height) = self.view_box();
+        let radius = width.max(height) * RADIUS;
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="800" preserveAspectRatio="xMidYMid meet">"#,
+            x, y, width, height
+        )?;
+        writeln!(f, "<title>{}</title>", escape(&self.title))?;
+        // the lines are as thick on screen as the picture is zoomed
+        let stroke = r#"stroke-width="1.5" vector-effect="non-scaling-stroke""#;
+        for shape in &self.shapes {
+            let mut line = String::new();
+            match shape {
+                Shape::Point { at, colour } => write!(
+                    line,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                    at.x,
+                    at.y,
+                    radius,
+                    escape(colour)
+                )?,
+                Shape::Polyline { points, colour } => write!(
+                    line,
+                    r#"<polyline points="{}" fill="none" stroke="{}" {}/>"#,
+                    coordinates(points),
+                    escape(colour),
+                    stroke
+                )?,
+                Shape::Polygon {
+                    points,
+                    stroke: colour,
+                    fill,
+                } => write!(
+                    line,
+                    r#"<polygon points="{}" fill="{}" fill-opacity="0.3" stroke="{}" {}/>"#,
+                    coordinates(points),
+                    escape(fill),
+                    escape(colour),
+                    stroke
+                )?,
+                Shape::Marker { at, label, colour } => write!(
+                    line,
+                    r#"<g fill="{}"><circle cx="{}" cy="{}" r="{}"/><text x="{}" y="{}" font-size="{}" font-family="sans-serif">{}</text></g>"#,
+                    escape(colour),
+                    at.x,
+                    at.y,
+                    radius * 1.5,
+                    at.x as f64 + radius * 2.5,
+                    at.y as f64 - radius * 1.5,
+                    radius * 5.0,
+                    escape(label)
+                )?,
+            }
+            writeln!(f, "{}", line)?;
+        }
+        write!(f, "</svg>")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn the_view_box_fits_the_shapes() {
+        let mut svg = Svg::new("test");
+        assert!(svg.is_empty());
+        svg.point(Point::new(10, 20), "red")
+            .polyline([Point::new(0, 0), Point::new(0, 50)], "blue")
+            .marker(Point::new(-90, 10), "S", "green");
+        assert_eq!((-92.0, -2.0, 104.0, 54.0), svg.view_box());
+
+        // a single point still gets some room
+        let mut svg = Svg::new("dot");
+        svg.point(Point::<i32>::new(50, 0), "red");
+        assert_eq!((49.98, -0.02, 0.04, 0.04), svg.view_box());
+    }
+
+    #[test]
+    fn shapes_are_written() {
+        let mut svg = Svg::new("rocks & sand");
+        svg.polygon(
+            [Point::new(0, 1), Point::new(1, 0), Point::new(0, -1)],
+            "red",
+            "orange",
+        )
+        .polyline([Point::new(498, 4), Point::new(498, 6)], "#333")
+        .marker(Point::new(500, 0), "<source>", "blue");
+        let text = svg.to_string();
+
+        assert!(text.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\""));
+        assert!(text.ends_with("</svg>"));
+        assert!(text.contains("<title>rocks &amp; sand</title>"));
+        assert!(text.contains(r#"<polygon points="0,1 1,0 0,-1" fill="orange""#));
+        assert!(text.contains(r##"<polyline points="498,4 498,6" fill="none" stroke="#333""##));
+        assert!(text.contains(">&lt;source&gt;</text>"));
+        assert_eq!(6, text.lines().count());
+    }
+}
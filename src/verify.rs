@@ -0,0 +1,235 @@
+use crate::{read_file, solution, Answer, Error, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// Default location of the recorded answers, relative to the crate root
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Expected {
+    Number(i64),
+    Text(String),
+}
+
+/// One `[[answer]]` entry of the answers file
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordedAnswer {
+    pub day: u32,
+    pub part: u32,
+    pub input: String,
+    pub expected: Expected,
+    /// takes too long to be checked by the test suite
+    #[serde(default)]
+    pub slow: bool,
+}
+
+#[derive(Debug)]
+pub enum Status {
+    Pass,
+    Fail(Answer),
+    Error(Error),
+}
+
+#[derive(Deserialize)]
+struct AnswersFile {
+    #[serde(default, rename = "answer")]
+    answers: Vec<RecordedAnswer>,
+}
+
+impl RecordedAnswer {
+    pub fn expected(&self) -> Answer {
+        match &self.expected {
+            Expected::Number(number) => Answer::Number(*number),
+            Expected::Text(text) => Answer::Text(text.clone()),
+        }
+    }
+
+    /// Solves the recorded part again and compares the result with the expected answer
+    pub fn verify(&self) -> Status {
+        let solution = match solution(self.day) {
+            Some(solution) => solution,
+            None => return Status::Error(Error::invalid(&format!("There is no day {}", self.day))),
+        };
+        let answer = read_file(&self.input).and_then(|input| {
+            solution
+                .solve(self.part, &input)
+                .map_err(|error| error.in_file(&self.input))
+        });
+
+        match answer {
+            Ok(answer) if answer == self.expected() => Status::Pass,
+            Ok(answer) => Status::Fail(answer),
+            Err(error) => Status::Error(error),
+        }
+    }
+}
+
+/// The sidecar `dayNN-<variant>.toml` of an example input with its expected answers
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExampleAnswers {
+    part1: Option<Expected>,
+    part2: Option<Expected>,
+}
+
+pub(crate) fn from_toml<T: DeserializeOwned>(content: &str) -> Result<T> {
+    toml::from_str::<T>(content).map_err(|error| match error.span() {
+        Some(span) => {
+            let text = content[span].lines().next().unwrap_or_default();
+            Error::parse(text, error.message().trim()).within(content, text)
+        }
+        None => Error::invalid(error.message().trim()),
+    })
+}
+
+pub fn parse_answers(content: &str) -> Result<Vec<RecordedAnswer>> {
+    from_toml::<AnswersFile>(content).map(|file| file.answers)
+}
+
+pub fn read_answers(filename: &str) -> Result<Vec<RecordedAnswer>> {
+    parse_answers(&read_file(filename)?).map_err(|error| error.in_file(filename))
+}
+
+/// The day of an example input named `dayNN-<variant>.txt`
+fn example_day(filename: &str) -> Option<u32> {
+    let (day, variant) = filename
+        .strip_prefix("day")?
+        .strip_suffix(".txt")?
+        .split_once('-')?;
+    match variant.is_empty() {
+        true => None,
+        false => day.parse().ok(),
+    }
+}
+
+/// Collects the answers of all example inputs `dayNN-<variant>.txt` in `dir` which come with a
+/// sidecar file `dayNN-<variant>.toml`, e.g. `part1 = 24000`. Examples without one are ignored.
+pub fn discover_examples(dir: &str) -> Result<Vec<RecordedAnswer>> {
+    let entries = fs::read_dir(dir).map_err(|source| Error::Io {
+        file: dir.to_string(),
+        source,
+    })?;
+    let mut inputs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    inputs.sort();
+
+    let mut answers = vec![];
+    for input in inputs {
+        let day = match input
+            .file_name()
+            .and_then(|name| example_day(&name.to_string_lossy()))
+        {
+            Some(day) => day,
+            None => continue,
+        };
+        let sidecar = input.with_extension("toml");
+        if !sidecar.exists() {
+            continue;
+        }
+        let sidecar = sidecar.to_string_lossy();
+        let expected: ExampleAnswers =
+            from_toml(&read_file(&sidecar)?).map_err(|error| error.in_file(&sidecar))?;
+
+        for (part, expected) in [(1, expected.part1), (2, expected.part2)] {
+            if let Some(expected) = expected {
+                answers.push(RecordedAnswer {
+                    day,
+                    part,
+                    input: input.to_string_lossy().to_string(),
+                    expected,
+                    slow: false,
+                });
+            }
+        }
+    }
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn answers_are_parsed() {
+        let answers = parse_answers(
+            r#"
+[[answer]]
+day = 1
+part = 2
+input = "input/day01-test.txt"
+expected = 45000
+
+[[answer]]
+day = 10
+part = 2
+input = "input/day10.txt"
+expected = "RJERPEFC"
+slow = true
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(2, answers.len());
+        assert_eq!(Answer::Number(45000), answers[0].expected());
+        assert!(!answers[0].slow);
+        assert_eq!(Answer::Text("RJERPEFC".to_string()), answers[1].expected());
+        assert!(answers[1].slow);
+    }
+
+    #[test]
+    fn example_files_are_recognized() {
+        assert_eq!(Some(1), example_day("day01-test.txt"));
+        assert_eq!(Some(16), example_day("day16-mini.txt"));
+        assert_eq!(None, example_day("day16.txt"));
+        assert_eq!(None, example_day("day16-.txt"));
+        assert_eq!(None, example_day("day16-test.toml"));
+    }
+
+    #[test]
+    fn examples_are_discovered() {
+        let examples = discover_examples("input").unwrap();
+        let example = examples
+            .iter()
+            .find(|example| example.day == 1 && example.part == 2)
+            .unwrap();
+        assert_eq!("input/day01-test.txt", example.input);
+        assert_eq!(Answer::Number(45000), example.expected());
+    }
+
+    #[test]
+    fn broken_entries_are_reported() {
+        let error = parse_answers("[[answer]]\nday = 1\npart = \"one\"\n").unwrap_err();
+        assert!(error.to_string().starts_with("3:8: "), "{}", error);
+    }
+
+    #[test]
+    fn answers_are_verified() {
+        let answers = parse_answers(
+            r#"
+[[answer]]
+day = 1
+part = 1
+input = "input/day01-test.txt"
+expected = 24000
+
+[[answer]]
+day = 1
+part = 2
+input = "input/day01-test.txt"
+expected = 1
+"#,
+        )
+        .unwrap();
+
+        assert!(matches!(answers[0].verify(), Status::Pass));
+        assert!(matches!(
+            answers[1].verify(),
+            Status::Fail(Answer::Number(45000))
+        ));
+    }
+}
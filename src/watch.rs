@@ -0,0 +1,183 @@
+//! Notices when the inputs or the sources of a day change, by polling the files' modification
+//! times, so `watch` can solve the day again and run its tests.
+
+use crate::{input_dir_or_default, Error, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+/// The files of a day worth watching: its inputs and example answers in `input_dir` and its
+/// module in `src/dayNN`. Files which appear later are found by listing again.
+pub fn watched_files(day: u32, input_dir: Option<&str>) -> Vec<PathBuf> {
+    let prefix = format!("day{:02}", day);
+    let inputs = list(Path::new(&input_dir_or_default(input_dir)))
+        .into_iter()
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            name.strip_prefix(&prefix)
+                .is_some_and(|rest| rest.starts_with(['.', '-']))
+        });
+    let sources = list(&Path::new("src").join(&prefix));
+    let mut files: Vec<PathBuf> = inputs.chain(sources).collect();
+    files.sort();
+    files
+}
+
+/// The files in `dir`, nothing if it can't be read
+fn list(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// When the watched files were modified and how long they were
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    files: BTreeMap<PathBuf, Option<(SystemTime, u64)>>,
+}
+
+impl Snapshot {
+    pub fn take(files: &[PathBuf]) -> Snapshot {
+        let files = files
+            .iter()
+            .map(|path| {
+                let metadata = fs::metadata(path).ok();
+                let stamp =
+                    metadata.and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+                (path.clone(), stamp)
+            })
+            .collect();
+        Snapshot { files }
+    }
+
+    /// The files which were added, removed or modified since `earlier`
+    pub fn changes(&self, earlier: &Snapshot) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = self
+            .files
+            .iter()
+            .filter(|(path, stamp)| earlier.files.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            earlier
+                .files
+                .keys()
+                .filter(|path| !self.files.contains_key(*path))
+                .cloned(),
+        );
+        changed.sort();
+        changed
+    }
+}
+
+/// How the tests of a day went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestRun {
+    /// Whether cargo succeeded, it doesn't if the tests fail or don't compile
+    pub success: bool,
+    pub passed: usize,
+    pub failed: usize,
+    /// Everything cargo printed, to show what went wrong
+    pub output: String,
+}
+
+/// Runs the unit tests of the day with cargo, which builds the current sources first
+pub fn run_tests(day: u32) -> Result<TestRun> {
+    let output = Command::new("cargo")
+        .args(["test", "--quiet", "--lib", &format!("day{:02}::", day)])
+        .output()
+        .map_err(|source| Error::Io {
+            file: "cargo".to_string(),
+            source,
+        })?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let (passed, failed) = test_counts(&text);
+    Ok(TestRun {
+        success: output.status.success(),
+        passed,
+        failed,
+        output: text,
+    })
+}
+
+/// Adds up the passed and failed tests of all `test result:` lines
+fn test_counts(output: &str) -> (usize, usize) {
+    let count = |line: &str, label: &str| -> usize {
+        line.split([';', '.'])
+            .find_map(|part| part.trim().strip_suffix(label)?.trim().parse().ok())
+            .unwrap_or(0)
+    };
+    output
+        .lines()
+        .filter(|line| line.starts_with("test result:"))
+        .fold((0, 0), |(passed, failed), line| {
+            (
+                passed + count(line, "passed"),
+                failed + count(line, "failed"),
+            )
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn inputs_and_sources_are_watched() {
+        let files = watched_files(9, Some("input"));
+        assert!(files.contains(&PathBuf::from("input/day09.txt")));
+        assert!(files.contains(&PathBuf::from("input/day09-larger.txt")));
+        assert!(files.contains(&PathBuf::from("input/day09-larger.toml")));
+        assert!(files.contains(&PathBuf::from("src/day09/mod.rs")));
+        // day 1 is no prefix of day 16
+        assert!(!watched_files(1, Some("input")).contains(&PathBuf::from("input/day16.txt")));
+    }
+
+    #[test]
+    fn changes_are_noticed() {
+        let dir = env::temp_dir().join(format!("watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.txt"), dir.join("b.txt"));
+        fs::write(&a, "1").unwrap();
+        let files = vec![a.clone(), b.clone()];
+        let before = Snapshot::take(&files);
+        assert!(Snapshot::take(&files).changes(&before).is_empty());
+
+        fs::write(&a, "12").unwrap();
+        fs::write(&b, "new").unwrap();
+        let after = Snapshot::take(&files);
+        assert_eq!(vec![a.clone(), b.clone()], after.changes(&before));
+
+        fs::remove_file(&b).unwrap();
+        assert_eq!(vec![b.clone()], Snapshot::take(&files).changes(&after));
+        // files which aren't watched any more count as changed, too
+        assert_eq!(vec![b], Snapshot::take(&[a]).changes(&after));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_results_are_counted() {
+        let output = "\
+running 3 tests
+...
+test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 160 filtered out; finished in 0.01s
+
+test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.20s
+";
+        assert_eq!((4, 2), test_counts(output));
+        assert_eq!((0, 0), test_counts("error[E0308]: mismatched types"));
+    }
+}
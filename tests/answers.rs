@@ -0,0 +1,35 @@
+use adventofcode::verify::{read_answers, Status, ANSWERS_FILE};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+
+#[test]
+fn recorded_answers_are_reproduced() {
+    let answers = read_answers(ANSWERS_FILE).unwrap();
+    // some days still walk their maps recursively
+    let pool = ThreadPoolBuilder::new()
+        .stack_size(256 * 1024 * 1024)
+        .build()
+        .unwrap();
+
+    let failures: Vec<String> = pool.install(|| {
+        answers
+            .par_iter()
+            .filter(|recorded| !recorded.slow)
+            .filter_map(|recorded| {
+                let problem = match recorded.verify() {
+                    Status::Pass => return None,
+                    Status::Fail(answer) => {
+                        format!("expected {}, got {}", recorded.expected(), answer)
+                    }
+                    Status::Error(error) => error.to_string(),
+                };
+                Some(format!(
+                    "day {:02} part {} ({}): {}",
+                    recorded.day, recorded.part, recorded.input, problem
+                ))
+            })
+            .collect()
+    });
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
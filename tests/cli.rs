@@ -0,0 +1,58 @@
+//! Scripts only see the exit code, so every failure has to show up there
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs the binary with `args` and `input` on standard input
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_adventofcode"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn solved_days_succeed() {
+    let output = run(&["1", "--test", "--format", "plain"], "");
+    assert!(output.status.success());
+}
+
+#[test]
+fn broken_inputs_fail() {
+    // parsed, but both parts fail without a 0
+    let output = run(&["20", "--input", "-", "--format", "json"], "1\n2\n");
+    assert!(!output.status.success());
+    // not parsed at all
+    let output = run(&["20", "--input", "-", "--format", "json"], "bad\n");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn unknown_days_fail() {
+    let output = run(&["30", "--format", "json"], "");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#""status":"error""#), "{}", stdout);
+    let output = run(&["generate", "30"], "");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn timeouts_fail() {
+    let output = run(
+        &["19", "--test", "--timeout", "0.01", "--format", "json"],
+        "",
+    );
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#""status":"timeout""#), "{}", stdout);
+}
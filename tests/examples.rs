@@ -0,0 +1,32 @@
+//! One test per day, part and example input, see `discover_examples` for the file layout
+use adventofcode::verify::{discover_examples, Status};
+use libtest_mimic::{Arguments, Failed, Trial};
+
+fn main() {
+    let args = Arguments::from_args();
+    let examples = discover_examples("input").unwrap();
+
+    let trials = examples
+        .into_iter()
+        .map(|example| {
+            let variant = example
+                .input
+                .trim_end_matches(".txt")
+                .rsplit_once('-')
+                .map_or("", |(_, variant)| variant)
+                .to_string();
+            let name = format!("day{:02}::part{}::{}", example.day, example.part, variant);
+            Trial::test(name, move || match example.verify() {
+                Status::Pass => Ok(()),
+                Status::Fail(answer) => Err(Failed::from(format!(
+                    "expected {}, got {}",
+                    example.expected(),
+                    answer
+                ))),
+                Status::Error(error) => Err(Failed::from(error.to_string())),
+            })
+        })
+        .collect();
+
+    libtest_mimic::run(&args, trials).exit();
+}
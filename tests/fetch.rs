@@ -0,0 +1,110 @@
+mod common;
+
+use adventofcode::http::{PlainHttp, RateLimit};
+use adventofcode::site::{fetch_input, Fetched, Site};
+use common::{temp_dir, StandIn};
+use std::fs;
+use std::time::{Duration, Instant};
+
+fn site(server: &StandIn, limit: RateLimit) -> Site {
+    Site::new(&server.url, "secret", Box::new(PlainHttp), limit)
+}
+
+fn unlimited() -> RateLimit {
+    RateLimit::new(Duration::ZERO, None)
+}
+
+#[test]
+fn inputs_are_downloaded_with_the_session() {
+    let server = StandIn::start(|request| (200, format!("input of {}\n", request.path)));
+    let dir = temp_dir("fetch-download");
+    let input_dir = dir.to_str();
+
+    let fetched = fetch_input(&mut site(&server, unlimited()), 7, input_dir).unwrap();
+    let path = dir.join("day07.txt");
+    assert_eq!(Fetched::Downloaded(path.clone()), fetched);
+    assert_eq!(
+        "input of /2022/day/7/input\n",
+        fs::read_to_string(&path).unwrap()
+    );
+    let requests = server.requests();
+    assert_eq!(1, requests.len());
+    assert_eq!("GET", requests[0].method);
+    assert_eq!(Some("session=secret"), requests[0].header("Cookie"));
+    assert!(requests[0].header("User-Agent").is_some());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn inputs_are_never_downloaded_twice() {
+    let server = StandIn::start(|_| (200, "fresh\n".to_string()));
+    let dir = temp_dir("fetch-cached");
+    fs::write(dir.join("day03.txt"), "kept\n").unwrap();
+
+    let fetched = fetch_input(&mut site(&server, unlimited()), 3, dir.to_str()).unwrap();
+    assert_eq!(Fetched::Cached(dir.join("day03.txt")), fetched);
+    assert_eq!("kept\n", fs::read_to_string(dir.join("day03.txt")).unwrap());
+    assert!(server.requests().is_empty());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn empty_placeholders_are_replaced() {
+    let server = StandIn::start(|_| (200, "fresh\n".to_string()));
+    let dir = temp_dir("fetch-placeholder");
+    fs::write(dir.join("day23.txt"), "").unwrap();
+
+    let fetched = fetch_input(&mut site(&server, unlimited()), 23, dir.to_str()).unwrap();
+    assert_eq!(Fetched::Downloaded(dir.join("day23.txt")), fetched);
+    assert_eq!(
+        "fresh\n",
+        fs::read_to_string(dir.join("day23.txt")).unwrap()
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn refusals_are_reported_and_leave_no_file() {
+    let server = StandIn::start(|request| match request.path.contains("/day/25/") {
+        true => (
+            404,
+            "Please don't repeatedly request this endpoint".to_string(),
+        ),
+        false => (400, "Puzzle inputs differ by user".to_string()),
+    });
+    let dir = temp_dir("fetch-refused");
+    let mut site = site(&server, unlimited());
+
+    let error = fetch_input(&mut site, 25, dir.to_str()).unwrap_err();
+    assert_eq!(
+        format!(
+            "Request to {}/2022/day/25/input failed: day 25 isn't unlocked yet",
+            server.url
+        ),
+        error.to_string()
+    );
+    let error = fetch_input(&mut site, 1, dir.to_str()).unwrap_err();
+    assert!(error
+        .to_string()
+        .ends_with("the session token was rejected"));
+    assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn requests_keep_their_distance() {
+    let server = StandIn::start(|_| (200, "input\n".to_string()));
+    let dir = temp_dir("fetch-limit");
+    let stamp = dir.join("last-request");
+    let interval = Duration::from_millis(300);
+
+    let start = Instant::now();
+    let mut first = site(&server, RateLimit::new(interval, Some(stamp.clone())));
+    fetch_input(&mut first, 1, dir.to_str()).unwrap();
+    // a separate run knows about the last request from the stamp file
+    let mut second = site(&server, RateLimit::new(interval, Some(stamp)));
+    fetch_input(&mut second, 2, dir.to_str()).unwrap();
+    assert!(start.elapsed() >= interval);
+    assert_eq!(2, server.requests().len());
+    fs::remove_dir_all(dir).unwrap();
+}
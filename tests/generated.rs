@@ -0,0 +1,64 @@
+//! The generated inputs have to be accepted and solved by the days they are made for
+use adventofcode::random::Random;
+use adventofcode::{solutions, Solution};
+use proptest::prelude::*;
+
+/// Parts which take seconds, however small the input is
+const SLOW: [(u32, u32); 2] = [(15, 2), (19, 2)];
+
+fn generators() -> impl Iterator<Item = &'static dyn Solution> {
+    solutions()
+        .iter()
+        .copied()
+        .filter(|solution| solution.generate(1, &mut Random::new(0)).is_ok())
+}
+
+#[test]
+fn every_parsed_day_has_a_generator() {
+    let days: Vec<u32> = generators().map(|solution| solution.day()).collect();
+    assert_eq!((1..=22).collect::<Vec<_>>(), days);
+}
+
+#[test]
+fn generated_inputs_are_solved() {
+    for solution in generators() {
+        for seed in 0..3 {
+            let input = solution.generate(8, &mut Random::new(seed)).unwrap();
+            for part in 1..=2 {
+                if SLOW.contains(&(solution.day(), part)) {
+                    continue;
+                }
+                if let Err(error) = solution.solve(part, &input) {
+                    panic!(
+                        "day {} part {} with seed {}: {}\n{}",
+                        solution.day(),
+                        part,
+                        seed,
+                        error,
+                        input
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn seeds_repeat_inputs() {
+    for solution in generators() {
+        let input = |seed| solution.generate(20, &mut Random::new(seed)).unwrap();
+        assert_eq!(input(5), input(5), "day {}", solution.day());
+        assert_ne!(input(5), input(6), "day {}", solution.day());
+    }
+}
+
+proptest! {
+    #[test]
+    fn generated_inputs_are_parsed(seed in any::<u64>(), size in 0_usize..60) {
+        for solution in generators() {
+            let input = solution.generate(size, &mut Random::new(seed)).unwrap();
+            let parsed = solution.parse(&input);
+            prop_assert!(parsed.is_ok(), "day {}: {:?}\n{}", solution.day(), parsed, input);
+        }
+    }
+}
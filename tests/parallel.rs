@@ -0,0 +1,68 @@
+//! Days run next to each other must not starve the parallel work inside a day. This needs a
+//! global rayon pool with a single thread, so it's a test binary of its own.
+use adventofcode::bench::{run_each, timed_with_limit};
+use adventofcode::{Answer, Result, Solution};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::thread;
+use std::time::Duration;
+
+/// Spreads its work over the global pool, like day 19
+struct Spread;
+
+impl Solution for Spread {
+    fn day(&self) -> u32 {
+        1
+    }
+    fn title(&self) -> &'static str {
+        "Spread"
+    }
+    fn part1(&self, _input: &str) -> Result<Answer> {
+        Ok((1..=100_i64).into_par_iter().sum::<i64>().into())
+    }
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+}
+
+/// Takes a while on its own thread
+struct Slow;
+
+impl Solution for Slow {
+    fn day(&self) -> u32 {
+        2
+    }
+    fn title(&self) -> &'static str {
+        "Slow"
+    }
+    fn part1(&self, _input: &str) -> Result<Answer> {
+        thread::sleep(Duration::from_millis(200));
+        Ok(Answer::Number(7))
+    }
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unsolved)
+    }
+}
+
+static SPREAD: Spread = Spread;
+static SLOW: Slow = Slow;
+
+#[test]
+fn parallel_days_finish_next_to_each_other() {
+    ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build_global()
+        .unwrap();
+
+    let days: [&'static dyn Solution; 2] = [&SPREAD, &SLOW];
+    let answers = run_each(&days, |solution| {
+        let solution = *solution;
+        timed_with_limit(Some(Duration::from_secs(10)), move || {
+            solution.solve(1, "").unwrap()
+        })
+        .map(|(answer, _)| answer)
+    });
+
+    assert_eq!(Ok(Answer::Number(5050)), answers[0]);
+    assert_eq!(Ok(Answer::Number(7)), answers[1]);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use adventofcode::http::{PlainHttp, RateLimit};
+use adventofcode::site::Site;
+use adventofcode::submit::{submit_answer, Attempt, History, Verdict};
+use common::{temp_dir, StandIn};
+use std::time::Duration;
+
+/// Accepts 42 for part 1 of every day and hints at it for anything else
+fn mock_site() -> StandIn {
+    StandIn::start(|request| {
+        let answer: i64 = request
+            .body
+            .split('&')
+            .find_map(|field| field.strip_prefix("answer="))
+            .and_then(|answer| answer.parse().ok())
+            .unwrap_or(0);
+        let text = match answer {
+            42 => "That's the right answer!  You are <span>one gold star</span> closer.",
+            answer if answer > 42 => {
+                "That's not the right answer; your answer is too high.  Please wait one minute \
+                 before trying again."
+            }
+            _ => "That's not the right answer; your answer is too low.",
+        };
+        (
+            200,
+            format!("<main><article><p>{}</p></article></main>", text),
+        )
+    })
+}
+
+fn site(server: &StandIn) -> Site {
+    let limit = RateLimit::new(Duration::ZERO, None);
+    Site::new(&server.url, "secret", Box::new(PlainHttp), limit)
+}
+
+#[test]
+fn answers_are_posted_as_form() {
+    let server = mock_site();
+    let reply = submit_answer(&mut site(&server), 5, 1, "42").unwrap();
+    assert_eq!(Verdict::Correct, reply.verdict);
+    assert_eq!(
+        "That's the right answer! You are one gold star closer.",
+        reply.message
+    );
+
+    let requests = server.requests();
+    assert_eq!("POST", requests[0].method);
+    assert_eq!("/2022/day/5/answer", requests[0].path);
+    assert_eq!("level=1&answer=42", requests[0].body);
+    assert_eq!(Some("session=secret"), requests[0].header("Cookie"));
+    assert_eq!(
+        Some("application/x-www-form-urlencoded"),
+        requests[0].header("Content-Type")
+    );
+}
+
+#[test]
+fn history_narrows_down_the_guesses() {
+    let server = mock_site();
+    let mut site = site(&server);
+    let dir = temp_dir("submit-history");
+    let file = dir.join("submissions.toml");
+    let file = file.to_str().unwrap();
+    // far enough apart that the minute to wait is over
+    let mut time = 1_670_000_000;
+    let mut guess = |answer: &str| -> Result<Verdict, String> {
+        let mut history = History::read(file).unwrap();
+        history
+            .check(3, 1, answer, time)
+            .map_err(|error| error.to_string())?;
+        let reply = submit_answer(&mut site, 3, 1, answer).unwrap();
+        history.record(Attempt::new(3, 1, answer, &reply, time));
+        history.write(file).unwrap();
+        time += 3600;
+        Ok(reply.verdict)
+    };
+
+    assert_eq!(Ok(Verdict::TooHigh), guess("100"));
+    assert_eq!(Ok(Verdict::TooLow), guess("10"));
+    assert_eq!(
+        Err("100 was sent before and is too high".to_string()),
+        guess("100")
+    );
+    assert_eq!(
+        Err("200 is too high, 100 was too high already".to_string()),
+        guess("200")
+    );
+    assert_eq!(
+        Err("3 is too low, 10 was too low already".to_string()),
+        guess("3")
+    );
+    assert_eq!(Ok(Verdict::Correct), guess("42"));
+    assert_eq!(
+        Err("Day 3 part 1 is solved already, the answer was 42".to_string()),
+        guess("43")
+    );
+    // refused guesses never reach the website
+    assert_eq!(3, server.requests().len());
+
+    let history = History::read(file).unwrap();
+    assert_eq!(3, history.attempts.len());
+    assert_eq!(Some(60), history.attempts[0].wait);
+    std::fs::remove_dir_all(dir).unwrap();
+}